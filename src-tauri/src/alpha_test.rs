@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{AlphaMode, ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba, RgbaImage};
+
+    /// Straight-alpha edge color used by the tests
+    const EDGE: Rgba<u8> = Rgba([200, 100, 50, 128]);
+
+    /// Helper function to create an image with an opaque center and a
+    /// semi-transparent edge whose color was premultiplied (darkened)
+    fn create_premultiplied_image() -> RgbaImage {
+        ImageBuffer::from_fn(8, 8, |x, _y| {
+            if (2..6).contains(&x) {
+                Rgba([200, 100, 50, 255])
+            } else {
+                // 200 * 128 / 255 etc.
+                Rgba([100, 50, 25, 128])
+            }
+        })
+    }
+
+    /// Helper function to wrap an image as PNG ImageData
+    fn encode_png(img: &RgbaImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back into RGBA pixels
+    fn decode_rgba(image_data: &ImageData) -> RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    /// Whether two pixels differ by at most one step per channel
+    fn close(a: &Rgba<u8>, b: &Rgba<u8>) -> bool {
+        a.0.iter().zip(b.0).all(|(x, y)| x.abs_diff(y) <= 1)
+    }
+
+    #[tokio::test]
+    async fn test_premultiplied_to_straight_lightens_edges() {
+        let original = create_premultiplied_image();
+
+        let result = crate::premultiplied_to_straight(encode_png(&original)).await.unwrap();
+        let straight = decode_rgba(&result);
+
+        let edge = straight.get_pixel(0, 0);
+        assert!(edge.0[0] > original.get_pixel(0, 0).0[0]);
+        assert!(close(edge, &EDGE), "edge was {:?}", edge);
+        // Opaque pixels are unchanged
+        assert_eq!(straight.get_pixel(3, 3), original.get_pixel(3, 3));
+    }
+
+    #[tokio::test]
+    async fn test_alpha_conversion_round_trips() {
+        let straight: RgbaImage = ImageBuffer::from_pixel(4, 4, EDGE);
+
+        let premultiplied = crate::straight_to_premultiplied(encode_png(&straight)).await.unwrap();
+        assert!(close(decode_rgba(&premultiplied).get_pixel(0, 0), &Rgba([100, 50, 25, 128])));
+
+        let restored = crate::premultiplied_to_straight(premultiplied).await.unwrap();
+        assert!(close(decode_rgba(&restored).get_pixel(0, 0), &EDGE));
+    }
+
+    #[tokio::test]
+    async fn test_load_and_save_with_premultiplied_hint() {
+        let temp_dir = std::env::temp_dir().join("test_alpha_mode");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source = temp_dir.join("premultiplied.png");
+        create_premultiplied_image().save(&source).unwrap();
+
+        let loaded = crate::load_image(
+            source.to_string_lossy().to_string(),
+            Some(AlphaMode::Premultiplied),
+            None,
+        ).await.unwrap();
+        assert!(close(decode_rgba(&loaded).get_pixel(0, 0), &EDGE));
+
+        let target = temp_dir.join("saved.png");
+        crate::save_image(loaded, target.to_string_lossy().to_string(), Some(AlphaMode::Premultiplied), None, limiter())
+            .await
+            .unwrap();
+        let saved = image::open(&target).unwrap().to_rgba8();
+        assert!(close(saved.get_pixel(0, 0), &Rgba([100, 50, 25, 128])));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+}
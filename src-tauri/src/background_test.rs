@@ -36,6 +36,7 @@ mod tests {
             format: ImageFormat::PNG,
             data: base64_data,
             has_alpha: true,
+            display_data: None,
         }
     }
 
@@ -61,6 +62,7 @@ mod tests {
             format: ImageFormat::JPEG,
             data: base64_data,
             has_alpha: false,
+            display_data: None,
         }
     }
 
@@ -69,7 +71,7 @@ mod tests {
         let image_data = create_test_image_with_alpha();
         
         // Set white background
-        let result = set_background(image_data, 255, 255, 255).await;
+        let result = set_background(image_data, 255, 255, 255, None).await;
         
         assert!(result.is_ok());
         let result_image = result.unwrap();
@@ -85,11 +87,12 @@ mod tests {
         let image_data = create_test_image_without_alpha();
         
         // Try to set background on non-transparent image
-        let result = set_background(image_data, 255, 255, 255).await;
+        let result = set_background(image_data, 255, 255, 255, None).await;
         
         assert!(result.is_err());
         let error_msg = result.unwrap_err();
-        assert!(error_msg.contains("does not have transparency"));
+        assert_eq!(error_msg.code, "NO_ALPHA");
+        assert!(error_msg.message.contains("does not have transparency"));
     }
 
     #[tokio::test]
@@ -106,7 +109,7 @@ mod tests {
         ];
         
         for (r, g, b) in colors {
-            let result = set_background(image_data.clone(), r, g, b).await;
+            let result = set_background(image_data.clone(), r, g, b, None).await;
             assert!(result.is_ok(), "Failed to set background with color ({}, {}, {})", r, g, b);
             
             let result_image = result.unwrap();
@@ -120,7 +123,7 @@ mod tests {
         let original_width = image_data.width;
         let original_height = image_data.height;
         
-        let result = set_background(image_data, 128, 128, 128).await;
+        let result = set_background(image_data, 128, 128, 128, None).await;
         
         assert!(result.is_ok());
         let result_image = result.unwrap();
@@ -134,7 +137,7 @@ mod tests {
         let image_data = create_test_image_with_alpha();
         let original_format = image_data.format.clone();
         
-        let result = set_background(image_data, 200, 200, 200).await;
+        let result = set_background(image_data, 200, 200, 200, None).await;
         
         assert!(result.is_ok());
         let result_image = result.unwrap();
@@ -174,10 +177,11 @@ mod tests {
             format: ImageFormat::PNG,
             data: base64_data,
             has_alpha: true,
+            display_data: None,
         };
         
         // Set white background (255, 255, 255)
-        let result = set_background(image_data, 255, 255, 255).await;
+        let result = set_background(image_data, 255, 255, 255, None).await;
         
         assert!(result.is_ok());
         let result_image = result.unwrap();
@@ -204,4 +208,34 @@ mod tests {
         assert_eq!(pixel1.0[3], 255);
         assert_eq!(pixel2.0[3], 255);
     }
+
+    #[tokio::test]
+    async fn test_set_background_linear_blend_brightens_midtones() {
+        // 50% transparent white, composited over black
+        let img = ImageBuffer::from_pixel(4, 4, Rgba([255u8, 255, 255, 128]));
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "test_linear.png".to_string(),
+            width: 4,
+            height: 4,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        };
+
+        let gamma = set_background(image_data.clone(), 0, 0, 0, None).await.unwrap();
+        let linear = set_background(image_data, 0, 0, 0, Some(true)).await.unwrap();
+
+        let value = |result: &ImageData| {
+            let decoded = general_purpose::STANDARD.decode(&result.data).unwrap();
+            image::load_from_memory(&decoded).unwrap().to_rgba8().get_pixel(1, 1).0[0]
+        };
+        assert_eq!(value(&gamma), 128);
+        // Half the light of white is about 188 in sRGB
+        assert!((185..=191).contains(&value(&linear)), "linear value was {}", value(&linear));
+    }
 }
@@ -0,0 +1,153 @@
+use exif::{Context, DateTime, In, Reader, Tag, Value};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Camera, lens and exposure settings read from a photo's EXIF data
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CameraInfo {
+    /// Camera manufacturer (EXIF Make)
+    pub camera_make: Option<String>,
+    /// Camera model (EXIF Model)
+    pub camera_model: Option<String>,
+    /// Lens model (EXIF LensModel)
+    pub lens_model: Option<String>,
+    /// Focal length in millimeters
+    pub focal_length: Option<f64>,
+    /// Aperture as an f-number (e.g. 2.8)
+    pub aperture: Option<f64>,
+    /// Shutter speed in seconds (e.g. 0.004 for 1/250)
+    pub exposure_time: Option<f64>,
+    /// ISO sensitivity
+    pub iso: Option<u32>,
+}
+
+impl CameraInfo {
+    /// Check whether the camera make, camera model or lens model contains the
+    /// query (case-insensitive)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.camera_make, &self.camera_model, &self.lens_model]
+            .into_iter()
+            .flatten()
+            .any(|value| value.to_lowercase().contains(&query))
+    }
+}
+
+/// Read camera information from a JPEG or TIFF file
+///
+/// Returns None if the file can't be read or has no EXIF data.
+pub fn read_camera_info(path: &Path) -> Option<CameraInfo> {
+    let file = File::open(path).ok()?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+
+    let text = |tag: Tag| match exif.get_field(tag, In::PRIMARY).map(|field| &field.value) {
+        Some(Value::Ascii(values)) => values
+            .first()
+            .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    };
+
+    let number = |tag: Tag| match exif.get_field(tag, In::PRIMARY).map(|field| &field.value) {
+        Some(Value::Rational(values)) => values
+            .first()
+            .filter(|value| value.denom != 0)
+            .map(|value| value.to_f64()),
+        _ => None,
+    };
+
+    let iso = exif
+        .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0));
+
+    Some(CameraInfo {
+        camera_make: text(Tag::Make),
+        camera_model: text(Tag::Model),
+        lens_model: text(Tag::LensModel),
+        focal_length: number(Tag::FocalLength),
+        aperture: number(Tag::FNumber),
+        exposure_time: number(Tag::ExposureTime),
+        iso,
+    })
+}
+
+/// Read the date a photo was taken as (year, month, day)
+///
+/// Uses DateTimeOriginal, falling back to the DateTime of the last change.
+/// Returns None if the file has no usable EXIF date.
+pub fn read_capture_date(path: &Path) -> Option<(u16, u8, u8)> {
+    let file = File::open(path).ok()?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+
+    [Tag::DateTimeOriginal, Tag::DateTime]
+        .into_iter()
+        .filter_map(|tag| match exif.get_field(tag, In::PRIMARY).map(|field| &field.value) {
+            Some(Value::Ascii(values)) => values.first().and_then(|bytes| DateTime::from_ascii(bytes).ok()),
+            _ => None,
+        })
+        .find(|date| date.year > 0 && (1..=12).contains(&date.month) && date.day > 0)
+        .map(|date| (date.year, date.month, date.day))
+}
+
+/// Read the JPEG thumbnail embedded in a photo's EXIF data
+///
+/// Cameras store a small preview (typically 160x120) in the second IFD, which
+/// is far cheaper to decode than the photo itself. Returns the thumbnail's
+/// encoded JPEG bytes, or None if the file has no EXIF thumbnail.
+pub fn read_exif_thumbnail(path: &Path) -> Option<Vec<u8>> {
+    let file = File::open(path).ok()?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+
+    let value = |tag: Tag| {
+        exif.get_field(tag, In::THUMBNAIL)
+            .and_then(|field| field.value.get_uint(0))
+            .map(|value| value as usize)
+    };
+    let offset = value(Tag::JPEGInterchangeFormat)?;
+    let length = value(Tag::JPEGInterchangeFormatLength)?;
+
+    exif.buf()
+        .get(offset..offset.checked_add(length)?)
+        .map(|thumbnail| thumbnail.to_vec())
+}
+
+/// Remove the GPS location from a raw EXIF block (a TIFF structure, as stored
+/// in a JPEG APP1 segment after the "Exif" header)
+///
+/// Every other field is written back unchanged, including the embedded JPEG
+/// thumbnail. Returns None if the block can't be parsed or rewritten; callers
+/// should then drop the metadata rather than risk leaking the location.
+pub fn strip_gps(exif_block: &[u8]) -> Option<Vec<u8>> {
+    let exif = Reader::new().read_raw(exif_block.to_vec()).ok()?;
+
+    let mut writer = exif::experimental::Writer::new();
+    for field in exif.fields().filter(|field| field.tag.context() != Context::Gps) {
+        writer.push_field(field);
+    }
+
+    // The writer lays out thumbnail data itself, so hand it the original bytes
+    let thumbnail_value = |tag: Tag| {
+        exif.get_field(tag, In::THUMBNAIL)
+            .and_then(|field| field.value.get_uint(0))
+            .map(|value| value as usize)
+    };
+    if let (Some(offset), Some(length)) = (
+        thumbnail_value(Tag::JPEGInterchangeFormat),
+        thumbnail_value(Tag::JPEGInterchangeFormatLength),
+    ) {
+        let thumbnail = exif.buf().get(offset..offset.checked_add(length)?)?;
+        writer.set_jpeg(thumbnail, In::THUMBNAIL);
+    }
+
+    let mut output = std::io::Cursor::new(Vec::new());
+    writer.write(&mut output, exif.little_endian()).ok()?;
+    Some(output.into_inner())
+}
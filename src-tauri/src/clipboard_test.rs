@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::ImageFormat;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba};
+
+    /// Helper function to decode known PNG bytes into the raw RGBA the
+    /// clipboard hands out
+    fn png_to_rgba(img: &image::RgbaImage) -> Vec<u8> {
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        image::load_from_memory(&png).unwrap().to_rgba8().into_raw()
+    }
+
+    #[test]
+    fn test_clipboard_pixels_become_png_image_data() {
+        let screenshot = ImageBuffer::from_fn(30, 20, |x, y| Rgba([x as u8 * 8, y as u8 * 12, 90, 255]));
+
+        let image_data = crate::image_data_from_rgba(&png_to_rgba(&screenshot), 30, 20, "clipboard.png".to_string())
+            .unwrap();
+
+        assert_eq!((image_data.width, image_data.height), (30, 20));
+        assert_eq!(image_data.format, ImageFormat::PNG);
+        assert_eq!(image_data.path, "clipboard.png");
+        assert!(!image_data.has_alpha);
+
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        assert_eq!(image::guess_format(&bytes).unwrap(), image::ImageFormat::Png);
+        assert_eq!(image::load_from_memory(&bytes).unwrap().to_rgba8(), screenshot);
+    }
+
+    #[test]
+    fn test_clipboard_alpha_is_detected() {
+        let translucent = ImageBuffer::from_pixel(4, 4, Rgba([255u8, 0, 0, 100]));
+
+        let image_data = crate::image_data_from_rgba(&png_to_rgba(&translucent), 4, 4, "clipboard.png".to_string())
+            .unwrap();
+
+        assert!(image_data.has_alpha);
+    }
+
+    #[test]
+    fn test_clipboard_rejects_mismatched_size() {
+        let result = crate::image_data_from_rgba(&[0; 12], 2, 2, "clipboard.png".to_string());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_copied_image_is_converted_to_rgba_buffer() {
+        // An RGB JPEG must be expanded to the clipboard's RGBA layout
+        let img = image::RgbImage::from_pixel(24, 16, image::Rgb([200, 100, 50]));
+        let mut jpeg = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .unwrap();
+        let image_data = crate::types::ImageData {
+            path: "photo.jpg".to_string(),
+            width: 24,
+            height: 16,
+            format: ImageFormat::JPEG,
+            data: general_purpose::STANDARD.encode(&jpeg),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let (rgba, width, height) = crate::image_data_to_rgba(&image_data).unwrap();
+
+        assert_eq!((width, height), (24, 16));
+        assert_eq!(rgba.len(), 24 * 16 * 4);
+        assert!(rgba.chunks(4).all(|pixel| pixel[3] == 255));
+        assert!(rgba[0].abs_diff(200) <= 4);
+
+        // Round trip back through the paste path
+        let pasted = crate::image_data_from_rgba(&rgba, width, height, "clipboard.png".to_string()).unwrap();
+        assert_eq!((pasted.width, pasted.height), (24, 16));
+    }
+}
@@ -0,0 +1,89 @@
+use image::{DynamicImage, GenericImageView, ImageBuffer};
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformOptions};
+use crate::error::{AppError, AppResult};
+
+/// Largest per-channel difference at which a profile still counts as sRGB
+const SRGB_TOLERANCE: u8 = 1;
+
+/// Convert an image from its embedded ICC profile to sRGB
+///
+/// Returns None when nothing needs to change: the profile can't be parsed,
+/// doesn't describe RGB data, or already behaves like sRGB. Callers then keep
+/// the original pixels (and bytes) untouched. Images with more than 8 bits
+/// per channel are converted at 16 bits so their precision is kept.
+pub fn convert_to_srgb(img: &DynamicImage, icc_profile: &[u8]) -> AppResult<Option<DynamicImage>> {
+    let source = match ColorProfile::new_from_slice(icc_profile) {
+        Ok(profile) if profile.color_space == DataColorSpace::Rgb => profile,
+        _ => return Ok(None),
+    };
+    let srgb = ColorProfile::new_srgb();
+
+    if is_srgb_equivalent(&source, &srgb)? {
+        return Ok(None);
+    }
+
+    let has_alpha = img.color().has_alpha();
+    let layout = if has_alpha { Layout::Rgba } else { Layout::Rgb };
+    let (width, height) = img.dimensions();
+    let high_bit_depth = img.color().bits_per_pixel() / img.color().channel_count() as u16 > 8;
+
+    let converted = if high_bit_depth {
+        let transform = source
+            .create_transform_16bit(layout, &srgb, layout, TransformOptions::default())
+            .map_err(cms_error)?;
+        let pixels = if has_alpha { img.to_rgba16().into_raw() } else { img.to_rgb16().into_raw() };
+        let mut output = vec![0u16; pixels.len()];
+        transform.transform(&pixels, &mut output).map_err(cms_error)?;
+
+        if has_alpha {
+            ImageBuffer::from_raw(width, height, output).map(DynamicImage::ImageRgba16)
+        } else {
+            ImageBuffer::from_raw(width, height, output).map(DynamicImage::ImageRgb16)
+        }
+    } else {
+        let transform = source
+            .create_transform_8bit(layout, &srgb, layout, TransformOptions::default())
+            .map_err(cms_error)?;
+        let pixels = if has_alpha { img.to_rgba8().into_raw() } else { img.to_rgb8().into_raw() };
+        let mut output = vec![0u8; pixels.len()];
+        transform.transform(&pixels, &mut output).map_err(cms_error)?;
+
+        if has_alpha {
+            ImageBuffer::from_raw(width, height, output).map(DynamicImage::ImageRgba8)
+        } else {
+            ImageBuffer::from_raw(width, height, output).map(DynamicImage::ImageRgb8)
+        }
+    };
+
+    converted
+        .map(Some)
+        .ok_or_else(|| AppError::OperationFailed("Color conversion produced a buffer of the wrong size".to_string()))
+}
+
+/// Whether converting from profile to sRGB would leave every color unchanged
+///
+/// Embedded sRGB profiles come in many byte-level variants, so the check runs
+/// a grid of sample colors through the transform instead of comparing bytes.
+fn is_srgb_equivalent(profile: &ColorProfile, srgb: &ColorProfile) -> AppResult<bool> {
+    let transform = profile
+        .create_transform_8bit(Layout::Rgb, srgb, Layout::Rgb, TransformOptions::default())
+        .map_err(cms_error)?;
+
+    let steps: Vec<u8> = (0..=255u8).step_by(51).collect();
+    let mut samples = Vec::with_capacity(steps.len().pow(3) * 3);
+    for &r in &steps {
+        for &g in &steps {
+            for &b in &steps {
+                samples.extend([r, g, b]);
+            }
+        }
+    }
+    let mut output = vec![0u8; samples.len()];
+    transform.transform(&samples, &mut output).map_err(cms_error)?;
+
+    Ok(samples.iter().zip(&output).all(|(a, b)| a.abs_diff(*b) <= SRGB_TOLERANCE))
+}
+
+fn cms_error(error: moxcms::CmsError) -> AppError {
+    AppError::OperationFailed(format!("Color conversion failed: {}", error))
+}
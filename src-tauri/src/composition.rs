@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+/// Golden ratio
+const PHI: f32 = 1.618_034;
+/// Number of squares the golden spiral is traced through
+const SPIRAL_TURNS: usize = 10;
+/// Line segments used to approximate each quarter arc of the spiral
+const ARC_SEGMENTS: usize = 16;
+
+/// A guide line from one point to another, in pixel coordinates
+pub type GuideLine = ((f32, f32), (f32, f32));
+
+/// Composition guide drawn over an image
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GridType {
+    /// Rule of thirds: two lines each way at 1/3 and 2/3
+    Thirds,
+    /// Golden spiral stretched to fill the frame
+    Golden,
+    /// Both corner-to-corner diagonals plus a 45° line from every corner
+    Diagonal,
+    /// Two lines each way at the golden sections (0.382 and 0.618)
+    PhiGrid,
+}
+
+/// Compute the guide lines of a grid for an image of the given size
+///
+/// Coordinates run from 0 to width - 1 and height - 1 so every line stays
+/// inside the image.
+pub fn guide_lines(grid_type: GridType, width: u32, height: u32) -> Vec<GuideLine> {
+    let right = width.saturating_sub(1) as f32;
+    let bottom = height.saturating_sub(1) as f32;
+
+    // Full-height and full-width lines at fractions of the frame
+    let sections = |fractions: &[f32]| -> Vec<GuideLine> {
+        fractions
+            .iter()
+            .flat_map(|&f| {
+                let x = (width as f32 * f).round().min(right);
+                let y = (height as f32 * f).round().min(bottom);
+                [((x, 0.0), (x, bottom)), ((0.0, y), (right, y))]
+            })
+            .collect()
+    };
+
+    match grid_type {
+        GridType::Thirds => sections(&[1.0 / 3.0, 2.0 / 3.0]),
+        GridType::PhiGrid => sections(&[1.0 - 1.0 / PHI, 1.0 / PHI]),
+        GridType::Diagonal => {
+            let reach = right.min(bottom);
+            vec![
+                ((0.0, 0.0), (right, bottom)),
+                ((right, 0.0), (0.0, bottom)),
+                ((0.0, 0.0), (reach, reach)),
+                ((right, 0.0), (right - reach, reach)),
+                ((0.0, bottom), (reach, bottom - reach)),
+                ((right, bottom), (right - reach, bottom - reach)),
+            ]
+        }
+        GridType::Golden => golden_spiral(right, bottom),
+    }
+}
+
+/// Trace a golden spiral through a golden rectangle scaled to the frame
+///
+/// Squares are cut off the left, top, right and bottom in turn, with a quarter
+/// arc drawn through each, so the spiral winds in towards the lower-right
+/// golden section point.
+fn golden_spiral(right: f32, bottom: f32) -> Vec<GuideLine> {
+    // Work on a PHI x 1 rectangle, then stretch it to the image
+    let scale = |(x, y): (f32, f32)| (x / PHI * right, y * bottom);
+
+    let (mut x, mut y, mut w, mut h) = (0.0f32, 0.0f32, PHI, 1.0f32);
+    let mut lines = Vec::new();
+
+    for turn in 0..SPIRAL_TURNS {
+        // Arc center, starting point and side of the square cut off this turn
+        let (center, start, side) = match turn % 4 {
+            0 => {
+                let s = h;
+                let arc = ((x + s, y + s), (x, y + s), s);
+                x += s;
+                w -= s;
+                arc
+            }
+            1 => {
+                let s = w;
+                let arc = ((x, y + s), (x, y), s);
+                y += s;
+                h -= s;
+                arc
+            }
+            2 => {
+                let s = h;
+                let arc = ((x + w - s, y), (x + w, y), s);
+                w -= s;
+                arc
+            }
+            _ => {
+                let s = w;
+                let arc = ((x + s, y + h - s), (x + s, y + h), s);
+                h -= s;
+                arc
+            }
+        };
+
+        // Each arc turns a quarter circle clockwise on screen
+        let start_angle = (start.1 - center.1).atan2(start.0 - center.0);
+        let point = |step: usize| {
+            let angle = start_angle + std::f32::consts::FRAC_PI_2 * step as f32 / ARC_SEGMENTS as f32;
+            scale((center.0 + side * angle.cos(), center.1 + side * angle.sin()))
+        };
+        lines.extend((0..ARC_SEGMENTS).map(|step| (point(step), point(step + 1))));
+    }
+
+    lines
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use crate::composition::{guide_lines, GridType};
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::cancel_registry;
+    use base64::{Engine as _, engine::general_purpose};
+
+    /// Helper function to create a black PNG test image
+    fn create_black_image(width: u32, height: u32) -> ImageData {
+        let mut buffer = Vec::new();
+        image::RgbImage::new(width, height)
+            .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width,
+            height,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back into RGB pixels
+    fn decode_rgb(image_data: &ImageData) -> image::RgbImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgb8()
+    }
+
+    #[tokio::test]
+    async fn test_thirds_lines_at_one_and_two_thirds() {
+        let result = crate::draw_composition_grid(
+            create_black_image(90, 60),
+            GridType::Thirds,
+            "#FFFFFF".to_string(),
+            1.0,
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+        let img = decode_rgb(&result);
+
+        let white = image::Rgb([255, 255, 255]);
+        let black = image::Rgb([0, 0, 0]);
+        for y in [5, 30, 55] {
+            assert_eq!(*img.get_pixel(30, y), white);
+            assert_eq!(*img.get_pixel(60, y), white);
+        }
+        for x in [5, 45, 85] {
+            assert_eq!(*img.get_pixel(x, 20), white);
+            assert_eq!(*img.get_pixel(x, 40), white);
+        }
+        assert_eq!(*img.get_pixel(15, 10), black);
+        assert_eq!(*img.get_pixel(45, 30), black);
+        assert_eq!(*img.get_pixel(31, 10), black);
+    }
+
+    #[tokio::test]
+    async fn test_grid_opacity_blends_lines() {
+        let result = crate::draw_composition_grid(
+            create_black_image(90, 60),
+            GridType::PhiGrid,
+            "#FFFFFF".to_string(),
+            0.5,
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+        let img = decode_rgb(&result);
+
+        // 0.382 * 90 = 34.4
+        let value = img.get_pixel(34, 10).0[0];
+        assert!((120..=135).contains(&value), "value was {}", value);
+
+        let bad_opacity = crate::draw_composition_grid(
+            create_black_image(10, 10),
+            GridType::Thirds,
+            "#FFFFFF".to_string(),
+            1.5,
+            cancel_registry(),
+        );
+        assert!(bad_opacity.await.is_err());
+    }
+
+    #[test]
+    fn test_guides_stay_inside_frame() {
+        for grid_type in [GridType::Thirds, GridType::Golden, GridType::Diagonal, GridType::PhiGrid] {
+            let lines = guide_lines(grid_type, 300, 200);
+            assert!(!lines.is_empty());
+            for (start, end) in lines {
+                for (x, y) in [start, end] {
+                    assert!((-0.01..=299.01).contains(&x), "{:?} x {}", grid_type, x);
+                    assert!((-0.01..=199.01).contains(&y), "{:?} y {}", grid_type, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_type_names() {
+        let parsed: Vec<GridType> =
+            serde_json::from_str(r#"["thirds", "golden", "diagonal", "phi_grid"]"#).unwrap();
+        assert_eq!(parsed, vec![GridType::Thirds, GridType::Golden, GridType::Diagonal, GridType::PhiGrid]);
+    }
+}
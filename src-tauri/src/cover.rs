@@ -0,0 +1,126 @@
+use image::{imageops, Rgba, RgbaImage};
+use rayon::prelude::*;
+use std::path::Path;
+use crate::error::{AppError, AppResult};
+use crate::types::ResampleOptions;
+
+/// Space between the cells of a cover grid and around its edge
+const GAP: u32 = 4;
+/// Smallest cover side that leaves room for a 2x2 grid
+const MIN_SIZE: u32 = GAP * 3 + 2;
+
+const BACKGROUND: Rgba<u8> = Rgba([36, 36, 40, 255]);
+/// Placeholder gradient colors, top-left to bottom-right
+const GRADIENT_START: [f32; 3] = [72.0, 86.0, 140.0];
+const GRADIENT_END: [f32; 3] = [150.0, 82.0, 120.0];
+
+/// Build a square cover image for a collection of images
+///
+/// The first four readable images are laid out in a 2x2 grid, each scaled and
+/// center-cropped to fill its cell; a single image fills the whole cover.
+/// Unreadable files are skipped, and a collection without readable images gets
+/// a diagonal gradient placeholder.
+pub fn render_collection_cover(paths: &[&Path], size: u32) -> AppResult<RgbaImage> {
+    if size < MIN_SIZE {
+        return Err(AppError::InvalidParameters(
+            format!("Cover size must be at least {} pixels", MIN_SIZE)
+        ));
+    }
+
+    let members: Vec<_> = paths
+        .iter()
+        .filter_map(|path| crate::open_image(path).ok())
+        .take(4)
+        .collect();
+
+    let filter = ResampleOptions::balanced().filter.to_filter_type();
+
+    match members.as_slice() {
+        [] => Ok(gradient_placeholder(size)),
+        [only] => Ok(only.resize_to_fill(size, size, filter).to_rgba8()),
+        _ => {
+            let mut cover = RgbaImage::from_pixel(size, size, BACKGROUND);
+            let cell = (size - GAP * 3) / 2;
+
+            for (index, member) in members.iter().enumerate() {
+                let column = index as u32 % 2;
+                let row = index as u32 / 2;
+                let thumbnail = member.resize_to_fill(cell, cell, filter).to_rgba8();
+                imageops::overlay(
+                    &mut cover,
+                    &thumbnail,
+                    (GAP + column * (cell + GAP)) as i64,
+                    (GAP + row * (cell + GAP)) as i64,
+                );
+            }
+
+            Ok(cover)
+        }
+    }
+}
+
+/// Diagonal gradient used for empty collections
+fn gradient_placeholder(size: u32) -> RgbaImage {
+    let span = (2 * (size - 1)).max(1) as f32;
+
+    RgbaImage::from_fn(size, size, |x, y| {
+        let t = (x + y) as f32 / span;
+        let mut pixel = [255u8; 4];
+        for ((channel, start), end) in pixel.iter_mut().zip(GRADIENT_START).zip(GRADIENT_END) {
+            *channel = (start + (end - start) * t).round() as u8;
+        }
+        Rgba(pixel)
+    })
+}
+
+/// Lay out images in a grid of equal cells (a contact sheet)
+///
+/// Each image is scaled to fit its cell_size x cell_size cell, keeping its
+/// aspect ratio, and centered in it. Cells are separated from each other and
+/// from the sheet's edge by `gap` pixels, filled with the background. The last
+/// row may be partial; its empty cells are left as background.
+pub fn render_contact_sheet(
+    paths: &[&Path],
+    columns: u32,
+    cell_size: u32,
+    gap: u32,
+    background: Rgba<u8>,
+) -> AppResult<RgbaImage> {
+    if paths.is_empty() || columns == 0 || cell_size == 0 {
+        return Err(AppError::InvalidParameters(
+            "A contact sheet needs images, at least one column and a positive cell size".to_string()
+        ));
+    }
+
+    let rows = (paths.len() as u32).div_ceil(columns);
+    let extent = |cells: u32| {
+        cells.checked_mul(cell_size)?.checked_add(gap.checked_mul(cells + 1)?)
+    };
+    let (Some(width), Some(height)) = (extent(columns), extent(rows)) else {
+        return Err(AppError::InvalidParameters("Contact sheet is too large".to_string()));
+    };
+
+    let filter = ResampleOptions::balanced().filter.to_filter_type();
+    let mut sheet = RgbaImage::from_pixel(width, height, background);
+
+    // Decoding and scaling dominate, so the thumbnails are made in parallel
+    let thumbnails: Vec<RgbaImage> = paths
+        .par_iter()
+        .map(|path| {
+            let member = crate::open_image(path).map_err(|e| AppError::InvalidImageData(
+                format!("Failed to load {}: {}", path.display(), e)
+            ))?;
+            Ok(member.resize(cell_size, cell_size, filter).to_rgba8())
+        })
+        .collect::<AppResult<_>>()?;
+
+    for (index, thumbnail) in thumbnails.iter().enumerate() {
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        let x = gap + column * (cell_size + gap) + (cell_size - thumbnail.width()) / 2;
+        let y = gap + row * (cell_size + gap) + (cell_size - thumbnail.height()) / 2;
+        imageops::overlay(&mut sheet, thumbnail, x as i64, y as i64);
+    }
+
+    Ok(sheet)
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose};
+    use std::fs;
+    use crate::test_state::limiter;
+
+    const COLORS: [[u8; 3]; 4] = [[220, 40, 40], [40, 200, 40], [40, 40, 220], [230, 220, 40]];
+
+    /// Helper function to decode a cover back to RGBA pixels
+    fn decode(image_data: &crate::ImageData) -> image::RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    #[tokio::test]
+    async fn test_cover_composites_2x2_grid() {
+        let temp_dir = std::env::temp_dir().join("test_collection_cover");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // Five members with different aspect ratios; only the first four are used
+        let mut paths = Vec::new();
+        for (index, color) in COLORS.iter().chain([[0, 0, 0]].iter()).enumerate() {
+            let path = temp_dir.join(format!("member_{}.png", index));
+            image::RgbImage::from_pixel(60 + index as u32 * 20, 50, image::Rgb(*color))
+                .save(&path)
+                .unwrap();
+            paths.push(path.to_string_lossy().to_string());
+        }
+
+        let result = crate::generate_collection_cover(paths, Some(100), limiter()).await.unwrap();
+
+        assert_eq!((result.width, result.height), (100, 100));
+        let cover = decode(&result);
+
+        // Cells are 46 pixels wide, starting at 4 and 54
+        for (index, color) in COLORS.iter().enumerate() {
+            let x = 4 + (index as u32 % 2) * 50 + 23;
+            let y = 4 + (index as u32 / 2) * 50 + 23;
+            let pixel = cover.get_pixel(x, y).0;
+            assert_eq!([pixel[0], pixel[1], pixel[2]], *color, "cell {}", index);
+        }
+        // The gap between cells shows the background
+        assert_eq!(cover.get_pixel(50, 50).0, [36, 36, 40, 255]);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_empty_collection_gets_gradient_placeholder() {
+        let missing = vec!["/nonexistent/cover_member.png".to_string()];
+
+        let result = crate::generate_collection_cover(missing, Some(64), limiter()).await.unwrap();
+
+        let cover = decode(&result);
+        assert_eq!(cover.dimensions(), (64, 64));
+        assert_ne!(cover.get_pixel(0, 0), cover.get_pixel(63, 63));
+        assert_eq!(cover.get_pixel(63, 0), cover.get_pixel(0, 63));
+
+        assert!(crate::generate_collection_cover(vec![], Some(8), limiter()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contact_sheet_lays_out_grid() {
+        let temp_dir = std::env::temp_dir().join("test_contact_sheet");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let mut paths = Vec::new();
+        for (index, color) in COLORS.iter().enumerate() {
+            let path = temp_dir.join(format!("sheet_{}.png", index));
+            image::RgbImage::from_pixel(80, 40 + index as u32 * 20, image::Rgb(*color))
+                .save(&path)
+                .unwrap();
+            paths.push(path.to_string_lossy().to_string());
+        }
+
+        let result = crate::create_contact_sheet(paths.clone(), 2, 50, 6, "#FFFFFF".to_string(), limiter())
+            .await
+            .unwrap();
+
+        // 2 columns and 2 rows of 50 pixel cells with 6 pixel gaps
+        assert_eq!((result.width, result.height), (2 * 50 + 3 * 6, 2 * 50 + 3 * 6));
+        assert_eq!(result.format, crate::ImageFormat::PNG);
+
+        let sheet = decode(&result);
+        for (index, color) in COLORS.iter().enumerate() {
+            let x = 6 + (index as u32 % 2) * 56 + 25;
+            let y = 6 + (index as u32 / 2) * 56 + 25;
+            let pixel = sheet.get_pixel(x, y).0;
+            assert_eq!([pixel[0], pixel[1], pixel[2]], *color, "cell {}", index);
+        }
+        // Gaps and the letterbox around the wide first image show the background
+        assert_eq!(sheet.get_pixel(2, 2).0, [255, 255, 255, 255]);
+        assert_eq!(sheet.get_pixel(31, 8).0, [255, 255, 255, 255]);
+
+        // A final partial row: three images in two columns still need two rows
+        let partial = crate::create_contact_sheet(paths[..3].to_vec(), 2, 50, 6, "#000000".to_string(), limiter())
+            .await
+            .unwrap();
+        assert_eq!((partial.width, partial.height), (118, 118));
+        assert_eq!(decode(&partial).get_pixel(6 + 56 + 25, 6 + 56 + 25).0, [0, 0, 0, 255]);
+
+        assert!(crate::create_contact_sheet(paths.clone(), 0, 50, 6, "#000000".to_string(), limiter()).await.is_err());
+        assert!(crate::create_contact_sheet(vec![], 2, 50, 6, "#000000".to_string(), limiter()).await.is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
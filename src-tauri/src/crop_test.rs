@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::{limiter, cancel_registry};
     use base64::{Engine as _, engine::general_purpose};
     use image::{ImageBuffer, Rgba};
 
@@ -31,6 +32,7 @@ mod tests {
             format: ImageFormat::PNG,
             data: base64_data,
             has_alpha: false,
+            display_data: None,
         }
     }
 
@@ -39,7 +41,7 @@ mod tests {
         let image_data = create_test_image(100, 100);
 
         // Crop a 50x50 region from the center
-        let result = crate::crop_image(image_data, 25, 25, 50, 50).await;
+        let result = crate::crop_image(image_data, 25, 25, 50, 50, None).await;
 
         assert!(result.is_ok());
         let cropped = result.unwrap();
@@ -53,7 +55,7 @@ mod tests {
         let image_data = create_test_image(100, 100);
 
         // Try to crop beyond image boundaries
-        let result = crate::crop_image(image_data, 80, 80, 50, 50).await;
+        let result = crate::crop_image(image_data, 80, 80, 50, 50, None).await;
 
         assert!(result.is_ok());
         let cropped = result.unwrap();
@@ -68,11 +70,11 @@ mod tests {
         let image_data = create_test_image(100, 100);
 
         // Try to crop with zero width
-        let result = crate::crop_image(image_data.clone(), 10, 10, 0, 50).await;
+        let result = crate::crop_image(image_data.clone(), 10, 10, 0, 50, None).await;
         assert!(result.is_err());
 
         // Try to crop with zero height
-        let result = crate::crop_image(image_data, 10, 10, 50, 0).await;
+        let result = crate::crop_image(image_data, 10, 10, 50, 0, None).await;
         assert!(result.is_err());
     }
 
@@ -81,7 +83,7 @@ mod tests {
         let image_data = create_test_image(100, 100);
 
         // Crop the entire image
-        let result = crate::crop_image(image_data.clone(), 0, 0, 100, 100).await;
+        let result = crate::crop_image(image_data.clone(), 0, 0, 100, 100, None).await;
 
         assert!(result.is_ok());
         let cropped = result.unwrap();
@@ -94,7 +96,7 @@ mod tests {
         let image_data = create_test_image(100, 100);
         let original_format = image_data.format.clone();
 
-        let result = crate::crop_image(image_data, 10, 10, 50, 50).await;
+        let result = crate::crop_image(image_data, 10, 10, 50, 50, None).await;
 
         assert!(result.is_ok());
         let cropped = result.unwrap();
@@ -106,11 +108,528 @@ mod tests {
         let image_data = create_test_image(100, 100);
 
         // Crop a very small 1x1 region
-        let result = crate::crop_image(image_data, 50, 50, 1, 1).await;
+        let result = crate::crop_image(image_data, 50, 50, 1, 1, None).await;
 
         assert!(result.is_ok());
         let cropped = result.unwrap();
         assert_eq!(cropped.width, 1);
         assert_eq!(cropped.height, 1);
     }
+
+    /// Helper function to encode an RGBA buffer as PNG ImageData
+    fn encode_png(img: &image::RgbaImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_autocrop_opaque_shape_on_transparent_canvas() {
+        // 20x30 opaque rectangle at (40, 35) on a transparent 100x100 canvas
+        let img = ImageBuffer::from_fn(100, 100, |x, y| {
+            if (40..60).contains(&x) && (35..65).contains(&y) {
+                Rgba([200, 10, 10, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+
+        let result = crate::autocrop_opaque(encode_png(&img), None, None).await;
+
+        assert!(result.is_ok());
+        let cropped = result.unwrap();
+        assert_eq!(cropped.width, 20);
+        assert_eq!(cropped.height, 30);
+        assert!(!cropped.has_alpha);
+    }
+
+    #[tokio::test]
+    async fn test_remove_letterbox_crops_black_bars() {
+        // 80x40 picture centered in a 120x70 frame, with slightly noisy bars
+        let img = ImageBuffer::from_fn(120, 70, |x, y| {
+            if (20..100).contains(&x) && (15..55).contains(&y) {
+                Rgba([180, 120, 60, 255])
+            } else {
+                Rgba([((x + y) % 6) as u8, 3, 0, 255])
+            }
+        });
+
+        let result = crate::remove_letterbox(encode_png(&img), None).await.unwrap();
+
+        assert_eq!((result.width, result.height), (80, 40));
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        let trimmed = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert!(trimmed.pixels().all(|p| *p == Rgba([180, 120, 60, 255])));
+    }
+
+    #[tokio::test]
+    async fn test_remove_letterbox_only_trims_symmetric_bars() {
+        // 5-row bars, plus 3 more dark rows of content along the top edge
+        let img = ImageBuffer::from_fn(60, 60, |_x, y| {
+            if (8..55).contains(&y) { Rgba([200, 200, 200, 255]) } else { Rgba([0, 0, 0, 255]) }
+        });
+
+        let result = crate::remove_letterbox(encode_png(&img), Some(8)).await.unwrap();
+        assert_eq!((result.width, result.height), (60, 50));
+
+        let black = ImageBuffer::from_pixel(10, 10, Rgba([0, 0, 0, 255]));
+        assert!(crate::remove_letterbox(encode_png(&black), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_autocrop_opaque_uniform_border() {
+        // White border around a 10x10 dark square
+        let img = ImageBuffer::from_fn(50, 50, |x, y| {
+            if (5..15).contains(&x) && (20..30).contains(&y) {
+                Rgba([20, 20, 20, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        });
+
+        let result = crate::autocrop_opaque(encode_png(&img), None, Some(8)).await;
+
+        assert!(result.is_ok());
+        let cropped = result.unwrap();
+        assert_eq!(cropped.width, 10);
+        assert_eq!(cropped.height, 10);
+    }
+
+    #[tokio::test]
+    async fn test_autocrop_opaque_fully_transparent() {
+        let img = ImageBuffer::from_pixel(10, 10, Rgba([0, 0, 0, 0]));
+
+        let result = crate::autocrop_opaque(encode_png(&img), None, None).await;
+
+        assert!(result.is_err());
+    }
+
+    /// Helper function to decode ImageData back into RGBA pixels
+    fn decode_rgba(image_data: &ImageData) -> image::RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    #[tokio::test]
+    async fn test_crop_supersampled_is_smoother_than_hard_crop() {
+        let image_data = create_test_image(100, 100);
+
+        // Hard 4x4 crop, magnified 8x with nearest neighbour as a loupe would
+        let hard = crate::crop_image(image_data.clone(), 40, 40, 4, 4, None).await.unwrap();
+        let hard_magnified = image::imageops::resize(
+            &decode_rgba(&hard),
+            32,
+            32,
+            image::imageops::FilterType::Nearest,
+        );
+
+        let smooth = crate::crop_supersampled(image_data, 40.0, 40.0, 4.0, 4.0, 32, 32, limiter()).await;
+
+        assert!(smooth.is_ok());
+        let smooth = smooth.unwrap();
+        assert_eq!(smooth.width, 32);
+        assert_eq!(smooth.height, 32);
+        let smooth_pixels = decode_rgba(&smooth);
+
+        // The red channel follows the horizontal gradient; the supersampled
+        // crop has many more intermediate steps along a row
+        let distinct = |img: &image::RgbaImage| {
+            let mut values: Vec<u8> = (0..32).map(|x| img.get_pixel(x, 16).0[0]).collect();
+            values.dedup();
+            values.len()
+        };
+        assert_eq!(distinct(&hard_magnified), 4);
+        assert!(distinct(&smooth_pixels) > 8);
+
+        // Largest jump between neighbouring pixels is smaller
+        let max_step = |img: &image::RgbaImage| {
+            (1..32)
+                .map(|x| img.get_pixel(x, 16).0[0].abs_diff(img.get_pixel(x - 1, 16).0[0]))
+                .max()
+                .unwrap()
+        };
+        assert!(max_step(&smooth_pixels) < max_step(&hard_magnified));
+    }
+
+    #[tokio::test]
+    async fn test_crop_supersampled_rejects_out_of_bounds() {
+        let image_data = create_test_image(100, 100);
+
+        let result = crate::crop_supersampled(image_data.clone(), 98.5, 0.0, 4.0, 4.0, 16, 16, limiter()).await;
+        assert!(result.is_err());
+
+        let result = crate::crop_supersampled(image_data, 10.0, 10.0, 0.0, 4.0, 16, 16, limiter()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crop_normalized_matches_pixel_crop() {
+        let image_data = create_test_image(100, 100);
+
+        let normalized = crate::crop_image_normalized(image_data.clone(), 0.25, 0.25, 0.5, 0.5)
+            .await
+            .unwrap();
+        let pixel = crate::crop_image(image_data, 25, 25, 50, 50, None).await.unwrap();
+
+        assert_eq!(normalized.width, 50);
+        assert_eq!(normalized.height, 50);
+        assert_eq!(normalized.data, pixel.data);
+    }
+
+    #[tokio::test]
+    async fn test_crop_normalized_rejects_out_of_range() {
+        let image_data = create_test_image(100, 100);
+
+        assert!(crate::crop_image_normalized(image_data.clone(), -0.1, 0.0, 0.5, 0.5).await.is_err());
+        assert!(crate::crop_image_normalized(image_data.clone(), 0.0, 0.0, 1.5, 0.5).await.is_err());
+        assert!(crate::crop_image_normalized(image_data, 0.0, 0.0, f32::NAN, 0.5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crop_aspect_square() {
+        let image_data = create_test_image(100, 80);
+
+        let cropped = crate::crop_image_aspect(image_data, 10, 10, 40, 1, 1).await.unwrap();
+
+        assert_eq!(cropped.width, 40);
+        assert_eq!(cropped.height, 40);
+    }
+
+    #[tokio::test]
+    async fn test_crop_aspect_16_9() {
+        let image_data = create_test_image(200, 200);
+
+        // 100 is not a multiple of 16, so the width rounds down to 96 for an exact ratio
+        let cropped = crate::crop_image_aspect(image_data, 0, 0, 100, 16, 9).await.unwrap();
+
+        assert_eq!((cropped.width, cropped.height), (96, 54));
+        assert_eq!(cropped.width * 9, cropped.height * 16);
+    }
+
+    #[tokio::test]
+    async fn test_crop_aspect_shrinks_to_fit() {
+        let image_data = create_test_image(200, 100);
+
+        // A 160 wide 16:9 region is 90 tall, but only 60 rows remain below y = 40
+        let cropped = crate::crop_image_aspect(image_data, 20, 40, 160, 32, 18).await.unwrap();
+
+        assert_eq!((cropped.width, cropped.height), (96, 54));
+        assert_eq!(cropped.width * 9, cropped.height * 16);
+    }
+
+    #[tokio::test]
+    async fn test_crop_aspect_rejects_invalid_ratio() {
+        let image_data = create_test_image(100, 100);
+
+        assert!(crate::crop_image_aspect(image_data.clone(), 0, 0, 50, 0, 1).await.is_err());
+        assert!(crate::crop_image_aspect(image_data, 0, 0, 10, 16, 9).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crop_rounded_corners_are_transparent() {
+        let image_data = create_test_image(100, 100);
+
+        let cropped = crate::crop_image_rounded(image_data, 10, 10, 60, 40, 12).await.unwrap();
+
+        assert_eq!((cropped.width, cropped.height), (60, 40));
+        assert!(cropped.has_alpha);
+
+        let pixels = decode_rgba(&cropped);
+        for (x, y) in [(0, 0), (59, 0), (0, 39), (59, 39)] {
+            assert_eq!(pixels.get_pixel(x, y).0[3], 0, "corner {},{}", x, y);
+        }
+        // Straight edges and the middle are untouched
+        for (x, y) in [(30, 0), (0, 20), (59, 20), (30, 39), (30, 20)] {
+            assert_eq!(pixels.get_pixel(x, y).0[3], 255, "pixel {},{}", x, y);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crop_circle_converts_jpeg_to_png() {
+        let img = image::RgbImage::from_pixel(64, 64, image::Rgb([200, 100, 50]));
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Jpeg)
+            .unwrap();
+        let image_data = ImageData {
+            path: "avatar.jpg".to_string(),
+            width: 64,
+            height: 64,
+            format: ImageFormat::JPEG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let cropped = crate::crop_image_circle(image_data, 0, 0, 64, 64).await.unwrap();
+
+        assert_eq!(cropped.format, ImageFormat::PNG);
+        assert_eq!(cropped.path, "avatar.png");
+
+        let pixels = decode_rgba(&cropped);
+        assert_eq!(pixels.get_pixel(0, 0).0[3], 0);
+        assert_eq!(pixels.get_pixel(63, 63).0[3], 0);
+        assert_eq!(pixels.get_pixel(12, 12).0[3], 255);
+        assert_eq!(pixels.get_pixel(32, 32).0[3], 255);
+        // The anti-aliased outline is mostly opaque where it touches the region edge
+        assert!(pixels.get_pixel(32, 0).0[3] > 200);
+        assert_eq!(pixels.get_pixel(32, 2).0[3], 255);
+    }
+
+    #[tokio::test]
+    async fn test_crop_polygon_triangle() {
+        let image_data = create_test_image(100, 100);
+        let triangle = vec![(50.0, 10.0), (90.0, 90.0), (10.0, 90.0)];
+
+        let result = crate::crop_polygon(image_data, triangle, None).await.unwrap();
+
+        assert_eq!((result.width, result.height), (100, 100));
+        let pixels = decode_rgba(&result);
+
+        // Corners outside the triangle are transparent, the interior is opaque
+        for (x, y) in [(0, 0), (99, 0), (0, 99), (99, 99), (15, 15), (85, 15)] {
+            assert_eq!(pixels.get_pixel(x, y).0[3], 0, "pixel {},{}", x, y);
+        }
+        for (x, y) in [(50, 20), (50, 60), (20, 85), (80, 85)] {
+            assert_eq!(pixels.get_pixel(x, y).0[3], 255, "pixel {},{}", x, y);
+        }
+
+        // The slanted edges are anti-aliased
+        let partial = pixels.pixels().filter(|p| p.0[3] > 0 && p.0[3] < 255).count();
+        assert!(partial > 50);
+    }
+
+    #[tokio::test]
+    async fn test_crop_polygon_to_bounds() {
+        let image_data = create_test_image(100, 100);
+        let triangle = vec![(50.0, 10.0), (90.0, 90.0), (10.0, 90.0)];
+
+        let result = crate::crop_polygon(image_data, triangle, Some(true)).await.unwrap();
+
+        assert_eq!((result.width, result.height), (80, 80));
+        let pixels = decode_rgba(&result);
+        assert_eq!(pixels.get_pixel(0, 0).0[3], 0);
+        assert_eq!(pixels.get_pixel(40, 50).0[3], 255);
+    }
+
+    #[tokio::test]
+    async fn test_crop_polygon_rejects_degenerate_input() {
+        let image_data = create_test_image(20, 20);
+
+        assert!(crate::crop_polygon(image_data.clone(), vec![(0.0, 0.0), (5.0, 5.0)], None).await.is_err());
+        let outside = vec![(50.0, 50.0), (60.0, 50.0), (60.0, 60.0)];
+        assert!(crate::crop_polygon(image_data, outside, Some(true)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_split_panorama_tiles_reassemble_to_original_width() {
+        let panorama = create_test_image(250, 40);
+
+        let tiles = crate::split_panorama(panorama.clone(), 100, 30, limiter()).await.unwrap();
+
+        // Tiles start at 0, 70, 140 and 210; the last one is cut off at the edge
+        let widths: Vec<u32> = tiles.iter().map(|tile| tile.width).collect();
+        assert_eq!(widths, vec![100, 100, 100, 40]);
+        assert!(tiles.iter().all(|tile| tile.height == 40));
+        assert_eq!(tiles[1].path, "test_tile1.png");
+
+        // Dropping each tile's overlap with its predecessor rebuilds the panorama
+        let original = decode_rgba(&panorama);
+        let mut reassembled = image::RgbaImage::new(250, 40);
+        let mut x = 0;
+        for (index, tile) in tiles.iter().enumerate() {
+            let skip = if index == 0 { 0 } else { 30 };
+            let pixels = decode_rgba(tile);
+            let visible = image::imageops::crop_imm(&pixels, skip, 0, tile.width - skip, 40).to_image();
+            image::imageops::replace(&mut reassembled, &visible, x, 0);
+            x += (tile.width - skip) as i64;
+        }
+        assert_eq!(x, 250);
+        assert_eq!(reassembled, original);
+
+        assert!(crate::split_panorama(panorama, 50, 50, limiter()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_border_grows_canvas_and_fills_color() {
+        let original = create_test_image(40, 30);
+        let original_pixels = decode_rgba(&original);
+
+        let framed = crate::add_border(original, 5, "#FFFFFF".to_string(), None, None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!((framed.width, framed.height), (40 + 2 * 5, 30 + 2 * 5));
+        let pixels = decode_rgba(&framed);
+        for (x, y) in [(0, 0), (4, 20), (49, 39), (25, 0), (25, 39), (45, 15)] {
+            assert_eq!(pixels.get_pixel(x, y), &Rgba([255, 255, 255, 255]), "({}, {})", x, y);
+        }
+
+        // The original sits centered and unchanged
+        let inner = image::imageops::crop_imm(&pixels, 5, 5, 40, 30).to_image();
+        assert_eq!(inner, original_pixels);
+    }
+
+    #[tokio::test]
+    async fn test_add_border_per_side() {
+        let framed = crate::add_border(create_test_image(20, 20), 2, "#102030".to_string(), Some(10), None, Some(0), Some(6))
+            .await
+            .unwrap();
+
+        assert_eq!((framed.width, framed.height), (6 + 20 + 2, 10 + 20));
+        let pixels = decode_rgba(&framed);
+        assert_eq!(pixels.get_pixel(10, 5), &Rgba([0x10, 0x20, 0x30, 255]));
+        assert_eq!(pixels.get_pixel(3, 15), &Rgba([0x10, 0x20, 0x30, 255]));
+        assert_eq!(pixels.get_pixel(27, 15), &Rgba([0x10, 0x20, 0x30, 255]));
+        assert_ne!(pixels.get_pixel(10, 29), &Rgba([0x10, 0x20, 0x30, 255]));
+
+        let invalid = crate::add_border(create_test_image(20, 20), 2, "white".to_string(), None, None, None, None).await;
+        assert_eq!(invalid.unwrap_err().code, "INVALID_PARAMETERS");
+    }
+
+    #[tokio::test]
+    async fn test_pad_to_aspect_adds_centered_bars() {
+        let square = create_test_image(100, 100);
+        let square_pixels = decode_rgba(&square);
+
+        // A square is too narrow for 16:9, so bars go left and right
+        let wide = crate::pad_to_aspect(square.clone(), 16, 9, "#000000".to_string()).await.unwrap();
+        assert_eq!((wide.width, wide.height), (178, 100));
+        let pixels = decode_rgba(&wide);
+        assert_eq!(pixels.get_pixel(0, 50), &Rgba([0, 0, 0, 255]));
+        assert_eq!(pixels.get_pixel(177, 50), &Rgba([0, 0, 0, 255]));
+        assert_eq!(image::imageops::crop_imm(&pixels, 39, 0, 100, 100).to_image(), square_pixels);
+
+        // ...and too wide for 9:16, so bars go above and below
+        let tall = crate::pad_to_aspect(square.clone(), 9, 16, "#FFFFFF".to_string()).await.unwrap();
+        assert_eq!((tall.width, tall.height), (100, 178));
+        let pixels = decode_rgba(&tall);
+        assert_eq!(pixels.get_pixel(50, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(pixels.get_pixel(50, 177), &Rgba([255, 255, 255, 255]));
+        assert_eq!(image::imageops::crop_imm(&pixels, 0, 39, 100, 100).to_image(), square_pixels);
+
+        // An image that already matches is unchanged in size
+        let same = crate::pad_to_aspect(square.clone(), 1, 1, "#000000".to_string()).await.unwrap();
+        assert_eq!((same.width, same.height), (100, 100));
+
+        assert!(crate::pad_to_aspect(square, 0, 9, "#000000".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_comparison_side_by_side() {
+        use crate::types::ComparisonOrientation;
+
+        let before = encode_png(&image::RgbaImage::from_pixel(100, 100, Rgba([200, 0, 0, 255])));
+        let after = encode_png(&image::RgbaImage::from_pixel(100, 100, Rgba([0, 0, 200, 255])));
+
+        let result = crate::create_comparison(
+            before.clone(),
+            after.clone(),
+            ComparisonOrientation::Horizontal,
+            8,
+            limiter(),
+        )
+            .await
+            .unwrap();
+
+        assert_eq!((result.width, result.height), (200 + 8, 100));
+        let pixels = decode_rgba(&result);
+        assert_eq!(pixels.get_pixel(50, 50), &Rgba([200, 0, 0, 255]));
+        assert_eq!(pixels.get_pixel(104, 50), &Rgba([255, 255, 255, 255]));
+        assert_eq!(pixels.get_pixel(158, 50), &Rgba([0, 0, 200, 255]));
+
+        let stacked = crate::create_comparison(before, after, ComparisonOrientation::Vertical, 0, limiter())
+            .await
+            .unwrap();
+        assert_eq!((stacked.width, stacked.height), (100, 200));
+        let pixels = decode_rgba(&stacked);
+        assert_eq!(pixels.get_pixel(50, 99), &Rgba([200, 0, 0, 255]));
+        assert_eq!(pixels.get_pixel(50, 100), &Rgba([0, 0, 200, 255]));
+    }
+
+    #[tokio::test]
+    async fn test_create_comparison_scales_smaller_image() {
+        use crate::types::ComparisonOrientation;
+
+        let before = encode_png(&image::RgbaImage::from_pixel(80, 40, Rgba([200, 0, 0, 255])));
+        let after = encode_png(&image::RgbaImage::from_pixel(60, 120, Rgba([0, 0, 200, 255])));
+
+        // The 80x40 image is scaled to 240x120 to share the height
+        let wide = crate::create_comparison(
+            before.clone(),
+            after.clone(),
+            ComparisonOrientation::Horizontal,
+            4,
+            limiter(),
+        )
+            .await
+            .unwrap();
+        assert_eq!((wide.width, wide.height), (240 + 4 + 60, 120));
+
+        // The 60x120 image is scaled to 80x160 to share the width
+        let tall = crate::create_comparison(before, after, ComparisonOrientation::Vertical, 4, limiter())
+            .await
+            .unwrap();
+        assert_eq!((tall.width, tall.height), (80, 40 + 4 + 160));
+    }
+
+    #[tokio::test]
+    async fn test_pixelate_region_fills_blocks_with_one_color() {
+        // A gradient so that every pixel starts out different from its neighbours
+        let gradient = image::RgbaImage::from_fn(60, 40, |x, y| Rgba([(x * 4) as u8, (y * 6) as u8, 128, 255]));
+
+        let result = crate::pixelate_region(
+            encode_png(&gradient),
+            10,
+            10,
+            40,
+            25,
+            10,
+            cancel_registry(),
+        ).await.unwrap();
+        let pixels = decode_rgba(&result);
+
+        for block_y in [10, 20] {
+            for block_x in [10, 20, 30, 40] {
+                let first = pixels.get_pixel(block_x, block_y);
+                for y in block_y..block_y + 10 {
+                    for x in block_x..block_x + 10 {
+                        assert_eq!(pixels.get_pixel(x, y), first, "pixel {},{}", x, y);
+                    }
+                }
+            }
+        }
+        // The leftover 5-pixel strip at the bottom forms its own smaller blocks
+        assert_eq!(pixels.get_pixel(10, 30), pixels.get_pixel(19, 34));
+        assert_ne!(pixels.get_pixel(10, 30), pixels.get_pixel(10, 29));
+
+        // Pixels outside the region are untouched
+        assert_eq!(pixels.get_pixel(5, 5), gradient.get_pixel(5, 5));
+        assert_eq!(pixels.get_pixel(55, 20), gradient.get_pixel(55, 20));
+        assert_eq!(pixels.get_pixel(20, 36), gradient.get_pixel(20, 36));
+    }
+
+    #[tokio::test]
+    async fn test_pixelate_region_clamps_to_image() {
+        let img = image::RgbaImage::from_fn(30, 30, |x, y| Rgba([(x * 8) as u8, (y * 8) as u8, 0, 255]));
+
+        let result = crate::pixelate_region(encode_png(&img), 20, 20, 100, 100, 10, cancel_registry()).await.unwrap();
+        assert_eq!((result.width, result.height), (30, 30));
+        let pixels = decode_rgba(&result);
+        assert_eq!(pixels.get_pixel(20, 20), pixels.get_pixel(29, 29));
+
+        assert!(crate::pixelate_region(encode_png(&img), 0, 0, 10, 10, 0, cancel_registry()).await.is_err());
+    }
 }
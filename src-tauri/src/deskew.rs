@@ -0,0 +1,80 @@
+use image::GrayImage;
+
+/// Largest skew, in degrees either way, that detection looks for
+const MAX_SKEW: f32 = 15.0;
+/// Step of the coarse angle search, in degrees
+const COARSE_STEP: f32 = 0.5;
+/// Step of the refinement around the best coarse angle, in degrees
+const FINE_STEP: f32 = 0.05;
+/// Longest side the image is reduced to before voting
+const ANALYSIS_SIZE: u32 = 600;
+/// Fewer dark pixels than this can't give a meaningful angle
+const MIN_POINTS: usize = 16;
+
+/// Detect the skew of the text lines or rules in a scanned page
+///
+/// Dark pixels (below the Otsu threshold) vote in a Hough accumulator for
+/// every line angle within ±15°. Text lines put many votes into few distance
+/// bins at their true angle, so the angle whose bins have the largest sum of
+/// squares wins. Returns the angle in degrees, positive when the lines slope
+/// down to the right (clockwise), or 0 if the page has nothing to measure.
+pub fn detect_skew(img: &GrayImage) -> f32 {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return 0.0;
+    }
+
+    let scale = (ANALYSIS_SIZE as f32 / width.max(height) as f32).min(1.0);
+    let small;
+    let img = if scale < 1.0 {
+        small = image::imageops::resize(
+            img,
+            ((width as f32 * scale).round() as u32).max(1),
+            ((height as f32 * scale).round() as u32).max(1),
+            image::imageops::FilterType::Triangle,
+        );
+        &small
+    } else {
+        img
+    };
+
+    let threshold = imageproc::contrast::otsu_level(img);
+    let center_x = img.width() as f32 / 2.0;
+    let center_y = img.height() as f32 / 2.0;
+    let points: Vec<(f32, f32)> = img
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel.0[0] < threshold)
+        .map(|(x, y, _)| (x as f32 - center_x, y as f32 - center_y))
+        .collect();
+
+    if points.len() < MIN_POINTS {
+        return 0.0;
+    }
+
+    let max_distance = center_x.hypot(center_y).ceil() as usize + 1;
+    let mut bins = vec![0u64; 2 * max_distance + 1];
+    let mut score = |degrees: f32| -> u64 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        bins.iter_mut().for_each(|bin| *bin = 0);
+        for &(x, y) in &points {
+            // Signed distance of the line through (x, y) at this angle
+            let distance = (y * cos - x * sin).round() as isize + max_distance as isize;
+            bins[distance as usize] += 1;
+        }
+        bins.iter().map(|&count| count * count).sum()
+    };
+
+    let mut best = |from: f32, to: f32, step: f32| -> f32 {
+        let steps = ((to - from) / step).round() as i32;
+        (0..=steps)
+            .map(|i| from + i as f32 * step)
+            .map(|angle| (score(angle), angle))
+            // Prefer the smallest correction when scores tie
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.abs().total_cmp(&a.1.abs())))
+            .map(|(_, angle)| angle)
+            .unwrap_or(0.0)
+    };
+
+    let coarse = best(-MAX_SKEW, MAX_SKEW, COARSE_STEP);
+    best(coarse - COARSE_STEP, coarse + COARSE_STEP, FINE_STEP)
+}
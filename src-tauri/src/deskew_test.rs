@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use crate::deskew::detect_skew;
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{GrayImage, Luma, Rgba, RgbaImage};
+
+    /// Helper function to draw a page of "text": rows of dark word blocks on white
+    fn create_text_page() -> RgbaImage {
+        let mut page = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+        for line in 0..10u32 {
+            let top = 40 + line * 22;
+            let mut x = 40 + (line * 17) % 30;
+            let mut word = line;
+            while x < 350 {
+                let length = 20 + (word * 13) % 35;
+                for y in top..top + 8 {
+                    for dx in 0..length.min(360 - x) {
+                        page.put_pixel(x + dx, y, Rgba([20, 20, 20, 255]));
+                    }
+                }
+                x += length + 10;
+                word += 3;
+            }
+        }
+        page
+    }
+
+    /// Helper function to encode an image as PNG ImageData
+    fn encode(img: &RgbaImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png).unwrap();
+
+        ImageData {
+            path: "scan.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back to grayscale pixels
+    fn decode_gray(image_data: &ImageData) -> GrayImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_luma8()
+    }
+
+    #[test]
+    fn test_detect_skew_measures_rotation() {
+        let page = create_text_page();
+        let straight = image::DynamicImage::ImageRgba8(page.clone()).to_luma8();
+        assert!(detect_skew(&straight).abs() < 0.2);
+
+        for angle in [4.0f32, -7.5] {
+            let skewed = crate::rotate_by_angle(&page, angle, Rgba([255, 255, 255, 255]));
+            let detected = detect_skew(&image::DynamicImage::ImageRgba8(skewed).to_luma8());
+            assert!((detected - angle).abs() < 0.3, "expected {}, detected {}", angle, detected);
+        }
+    }
+
+    #[test]
+    fn test_detect_skew_of_blank_page_is_zero() {
+        assert_eq!(detect_skew(&GrayImage::from_pixel(50, 50, Luma([255]))), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_auto_deskew_straightens_text() {
+        let skewed = crate::rotate_by_angle(&create_text_page(), 6.0, Rgba([255, 255, 255, 255]));
+
+        let result = crate::auto_deskew(encode(&skewed), None, limiter()).await.unwrap();
+        let pixels = decode_gray(&result);
+        assert!(detect_skew(&pixels).abs() < 0.3);
+
+        // Exposed corners are filled with white, not black or transparent
+        assert_eq!(pixels.get_pixel(0, 0).0[0], 255);
+        assert_eq!(pixels.get_pixel(pixels.width() - 1, pixels.height() - 1).0[0], 255);
+    }
+
+    #[tokio::test]
+    async fn test_auto_deskew_crops_to_content() {
+        let skewed = crate::rotate_by_angle(&create_text_page(), -5.0, Rgba([255, 255, 255, 255]));
+
+        let uncropped = crate::auto_deskew(encode(&skewed), Some(false), limiter()).await.unwrap();
+        let cropped = crate::auto_deskew(encode(&skewed), Some(true), limiter()).await.unwrap();
+
+        assert!(cropped.width < uncropped.width && cropped.height < uncropped.height);
+        // The text block spans roughly 320x206 pixels on the straight page
+        assert!((310..=340).contains(&cropped.width), "width {}", cropped.width);
+        assert!((200..=215).contains(&cropped.height), "height {}", cropped.height);
+        assert!(detect_skew(&decode_gray(&cropped)).abs() < 0.3);
+    }
+}
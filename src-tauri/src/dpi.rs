@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, AppResult};
+use crate::types::ImageFormat;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// Density assumed for files that don't record one (the usual screen density)
+pub const DEFAULT_DPI: u32 = 96;
+
+/// JFIF density unit for dots per inch
+const JFIF_UNIT_INCHES: u8 = 1;
+/// JFIF density unit for dots per centimeter
+const JFIF_UNIT_CENTIMETERS: u8 = 2;
+
+/// Physical size of an image when printed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrintSize {
+    pub width_inches: f64,
+    pub height_inches: f64,
+    pub width_mm: f64,
+    pub height_mm: f64,
+    /// Density used for the calculation
+    pub dpi: u32,
+    /// Whether the density was read from the file rather than assumed
+    pub dpi_embedded: bool,
+}
+
+impl PrintSize {
+    /// Size of an image with the given pixel dimensions at the given density
+    pub fn new(width: u32, height: u32, dpi: u32, dpi_embedded: bool) -> Self {
+        let width_inches = width as f64 / dpi as f64;
+        let height_inches = height as f64 / dpi as f64;
+
+        PrintSize {
+            width_inches,
+            height_inches,
+            width_mm: width_inches * 25.4,
+            height_mm: height_inches * 25.4,
+            dpi,
+            dpi_embedded,
+        }
+    }
+}
+
+/// Store a pixel density in encoded image bytes without re-encoding the pixels
+///
+/// PNG gets a pHYs chunk (replacing any existing one) and JPEG gets the density
+/// of its JFIF header set, inserting the header if the file has none.
+pub fn set_dpi(bytes: &[u8], format: &ImageFormat, dpi: u32) -> AppResult<Vec<u8>> {
+    if dpi == 0 || dpi > u16::MAX as u32 {
+        return Err(AppError::InvalidParameters(
+            format!("DPI must be between 1 and {}", u16::MAX)
+        ));
+    }
+
+    match format {
+        ImageFormat::PNG => set_png_dpi(bytes, dpi),
+        ImageFormat::JPEG => set_jpeg_dpi(bytes, dpi as u16),
+        other => Err(AppError::UnsupportedFormat(
+            format!("Cannot store DPI in {} images", other)
+        )),
+    }
+}
+
+/// Read the pixel density of encoded image bytes, if the file records one
+pub fn read_dpi(bytes: &[u8], format: &ImageFormat) -> Option<u32> {
+    match format {
+        ImageFormat::PNG => png_chunks(bytes)?
+            .find(|(chunk_type, _)| chunk_type == b"pHYs")
+            .and_then(|(_, data)| {
+                // Only a unit of 1 (meters) gives an absolute density
+                if data.len() != 9 || data[8] != 1 {
+                    return None;
+                }
+                let pixels_per_meter = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                Some((pixels_per_meter as f64 * METERS_PER_INCH).round() as u32)
+            }),
+        ImageFormat::JPEG => {
+            let app0 = jfif_segment(bytes)?;
+            let density = u16::from_be_bytes([bytes[app0 + 12], bytes[app0 + 13]]) as f64;
+            match bytes[app0 + 11] {
+                JFIF_UNIT_INCHES => Some(density as u32),
+                JFIF_UNIT_CENTIMETERS => Some((density * 2.54).round() as u32),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Read the pixel aspect ratio (pixel width / pixel height) recorded in encoded
+/// image bytes
+///
+/// PNG files record it in the pHYs chunk and JPEG files in the JFIF density,
+/// both of which store separate horizontal and vertical values even when no
+/// absolute unit is given. Returns None if the file doesn't record one.
+pub fn read_pixel_aspect(bytes: &[u8], format: &ImageFormat) -> Option<f64> {
+    let (horizontal, vertical) = match format {
+        ImageFormat::PNG => png_chunks(bytes)?
+            .find(|(chunk_type, _)| chunk_type == b"pHYs")
+            .filter(|(_, data)| data.len() == 9)
+            .map(|(_, data)| (
+                u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as f64,
+                u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as f64,
+            ))?,
+        ImageFormat::JPEG => {
+            let app0 = jfif_segment(bytes)?;
+            (
+                u16::from_be_bytes([bytes[app0 + 12], bytes[app0 + 13]]) as f64,
+                u16::from_be_bytes([bytes[app0 + 14], bytes[app0 + 15]]) as f64,
+            )
+        }
+        _ => return None,
+    };
+
+    // More pixels per unit vertically means each pixel is wider than it is tall
+    (horizontal > 0.0 && vertical > 0.0).then(|| vertical / horizontal)
+}
+
+/// Iterate over the (type, data) chunks of a PNG file
+fn png_chunks(bytes: &[u8]) -> Option<impl Iterator<Item = ([u8; 4], &[u8])>> {
+    if !bytes.starts_with(&PNG_SIGNATURE) {
+        return None;
+    }
+
+    let mut offset = PNG_SIGNATURE.len();
+    Some(std::iter::from_fn(move || {
+        let header = bytes.get(offset..offset + 8)?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let chunk_type = [header[4], header[5], header[6], header[7]];
+        let data = bytes.get(offset + 8..offset + 8 + length)?;
+        offset += 12 + length;
+        Some((chunk_type, data))
+    }))
+}
+
+fn set_png_dpi(bytes: &[u8], dpi: u32) -> AppResult<Vec<u8>> {
+    let chunks: Vec<_> = png_chunks(bytes)
+        .ok_or_else(|| AppError::InvalidImageData("Not a PNG file".to_string()))?
+        .collect();
+
+    let pixels_per_meter = (dpi as f64 / METERS_PER_INCH).round() as u32;
+    let mut phys = Vec::with_capacity(9);
+    phys.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    phys.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    phys.push(1);
+
+    let mut output = Vec::with_capacity(bytes.len() + 21);
+    output.extend_from_slice(&PNG_SIGNATURE);
+    for (chunk_type, data) in chunks {
+        if &chunk_type == b"pHYs" {
+            continue;
+        }
+        write_png_chunk(&mut output, &chunk_type, data);
+        // pHYs must come before the image data, so place it right after the header
+        if &chunk_type == b"IHDR" {
+            write_png_chunk(&mut output, b"pHYs", &phys);
+        }
+    }
+
+    Ok(output)
+}
+
+fn write_png_chunk(output: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(chunk_type);
+    crc.update(data);
+
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(chunk_type);
+    output.extend_from_slice(data);
+    output.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+/// Offset of the JFIF APP0 segment directly after the SOI marker, if present
+fn jfif_segment(bytes: &[u8]) -> Option<usize> {
+    let is_jfif = bytes.get(2..4)? == [0xFF, 0xE0]
+        && bytes.get(6..11)? == b"JFIF\0"
+        && bytes.len() >= 18;
+    is_jfif.then_some(2)
+}
+
+fn set_jpeg_dpi(bytes: &[u8], dpi: u16) -> AppResult<Vec<u8>> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return Err(AppError::InvalidImageData("Not a JPEG file".to_string()));
+    }
+
+    let mut output = bytes.to_vec();
+    match jfif_segment(bytes) {
+        Some(app0) => {
+            output[app0 + 11] = JFIF_UNIT_INCHES;
+            output[app0 + 12..app0 + 14].copy_from_slice(&dpi.to_be_bytes());
+            output[app0 + 14..app0 + 16].copy_from_slice(&dpi.to_be_bytes());
+        }
+        None => {
+            let mut app0 = vec![0xFF, 0xE0, 0x00, 0x10];
+            app0.extend_from_slice(b"JFIF\0");
+            app0.extend_from_slice(&[1, 1, JFIF_UNIT_INCHES]);
+            app0.extend_from_slice(&dpi.to_be_bytes());
+            app0.extend_from_slice(&dpi.to_be_bytes());
+            app0.extend_from_slice(&[0, 0]);
+            output.splice(2..2, app0);
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+
+    /// Helper function to encode a small gradient in the given format
+    fn create_image(format: ImageFormat) -> ImageData {
+        let img = image::RgbImage::from_fn(24, 16, |x, y| image::Rgb([(x * 10) as u8, (y * 15) as u8, 90]));
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), format.to_image_format().unwrap())
+            .unwrap();
+
+        ImageData {
+            path: "print.img".to_string(),
+            width: 24,
+            height: 16,
+            format,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back to RGB pixels
+    fn decode(image_data: &ImageData) -> image::RgbImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgb8()
+    }
+
+    #[tokio::test]
+    async fn test_png_dpi_round_trips() {
+        let original = create_image(ImageFormat::PNG);
+        assert_eq!(crate::get_image_dpi(original.clone()).await.unwrap(), None);
+
+        let with_dpi = crate::set_image_dpi(original.clone(), 300).await.unwrap();
+        assert_eq!(crate::get_image_dpi(with_dpi.clone()).await.unwrap(), Some(300));
+
+        // The png decoder sees the pHYs chunk and the pixels are untouched
+        let bytes = general_purpose::STANDARD.decode(&with_dpi.data).unwrap();
+        let reader = png::Decoder::new(std::io::Cursor::new(bytes)).read_info().unwrap();
+        let dims = reader.info().pixel_dims.unwrap();
+        assert_eq!(dims.unit, png::Unit::Meter);
+        assert_eq!(dims.xppu, 11811);
+        assert_eq!(decode(&with_dpi), decode(&original));
+
+        // Setting it again replaces the previous value
+        let changed = crate::set_image_dpi(with_dpi, 72).await.unwrap();
+        assert_eq!(crate::get_image_dpi(changed).await.unwrap(), Some(72));
+    }
+
+    #[tokio::test]
+    async fn test_jpeg_dpi_round_trips() {
+        let original = create_image(ImageFormat::JPEG);
+
+        let with_dpi = crate::set_image_dpi(original.clone(), 600).await.unwrap();
+
+        assert_eq!(crate::get_image_dpi(with_dpi.clone()).await.unwrap(), Some(600));
+        assert_eq!(decode(&with_dpi), decode(&original));
+    }
+
+    #[tokio::test]
+    async fn test_set_dpi_rejects_invalid_values_and_formats() {
+        assert!(crate::set_image_dpi(create_image(ImageFormat::PNG), 0).await.is_err());
+        assert!(crate::set_image_dpi(create_image(ImageFormat::PNG), 70000).await.is_err());
+        assert!(crate::set_image_dpi(create_image(ImageFormat::BMP), 300).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_print_size_uses_embedded_dpi() {
+        let img = image::RgbImage::from_pixel(300, 150, image::Rgb([0, 0, 0]));
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "print.png".to_string(),
+            width: 300,
+            height: 150,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let assumed = crate::get_print_size(image_data.clone()).await.unwrap();
+        assert_eq!(assumed.dpi, 96);
+        assert!(!assumed.dpi_embedded);
+        assert!((assumed.width_inches - 3.125).abs() < 1e-9);
+
+        let with_dpi = crate::set_image_dpi(image_data, 300).await.unwrap();
+        let size = crate::get_print_size(with_dpi).await.unwrap();
+
+        assert_eq!(size.dpi, 300);
+        assert!(size.dpi_embedded);
+        assert!((size.width_inches - 1.0).abs() < 1e-9);
+        assert!((size.width_mm - 25.4).abs() < 1e-9);
+        assert!((size.height_inches - 0.5).abs() < 1e-9);
+        assert!((size.height_mm - 12.7).abs() < 1e-9);
+    }
+
+    /// Helper function to encode a PNG whose pHYs chunk declares a pixel aspect ratio
+    fn create_anamorphic_png(xppu: u32, yppu: u32) -> ImageData {
+        // Two columns: left half dark, right half light
+        let img = image::RgbImage::from_fn(40, 30, |x, _| {
+            if x < 20 { image::Rgb([20, 20, 20]) } else { image::Rgb([230, 230, 230]) }
+        });
+        let mut buffer = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut buffer, 40, 30);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_pixel_dims(Some(png::PixelDimensions { xppu, yppu, unit: png::Unit::Unspecified }));
+            encoder.write_header().unwrap().write_image_data(img.as_raw()).unwrap();
+        }
+
+        ImageData {
+            path: "anamorphic.png".to_string(),
+            width: 40,
+            height: 30,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_correct_2_to_1_pixel_aspect_doubles_width() {
+        let anamorphic = create_anamorphic_png(1, 2);
+        assert_eq!(crate::get_pixel_aspect(anamorphic.clone()).await.unwrap(), Some(2.0));
+
+        let corrected = crate::correct_pixel_aspect(anamorphic, None, limiter()).await.unwrap();
+
+        assert_eq!((corrected.width, corrected.height), (80, 30));
+        // The dark half still covers the left half of the wider image
+        let pixels = decode(&corrected);
+        assert!(pixels.get_pixel(30, 15).0[0] < 60);
+        assert!(pixels.get_pixel(50, 15).0[0] > 190);
+    }
+
+    #[tokio::test]
+    async fn test_pixel_aspect_defaults_to_square_and_can_be_overridden() {
+        let plain = create_image(ImageFormat::PNG);
+        assert_eq!(crate::get_pixel_aspect(plain.clone()).await.unwrap(), None);
+
+        let unchanged = crate::correct_pixel_aspect(plain.clone(), None, limiter()).await.unwrap();
+        assert_eq!((unchanged.width, unchanged.height), (24, 16));
+
+        let overridden = crate::correct_pixel_aspect(plain.clone(), Some(0.75), limiter()).await.unwrap();
+        assert_eq!((overridden.width, overridden.height), (18, 16));
+
+        // An explicit ratio wins over the one in the file
+        let forced = crate::correct_pixel_aspect(create_anamorphic_png(1, 2), Some(1.0), limiter()).await.unwrap();
+        assert_eq!((forced.width, forced.height), (40, 30));
+
+        assert!(crate::correct_pixel_aspect(plain.clone(), Some(0.0), limiter()).await.is_err());
+        assert!(crate::correct_pixel_aspect(plain, Some(f64::NAN), limiter()).await.is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use thiserror::Error;
 
 /// Application error types
@@ -24,6 +25,15 @@ pub enum AppError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    #[error("File already exists: {0}")]
+    FileExists(String),
+
+    #[error("Image does not have transparency: {0}")]
+    NoAlpha(String),
+
+    #[error("Image file is truncated or corrupt, try downloading or copying it again: {0}")]
+    CorruptImage(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -34,6 +44,28 @@ pub enum AppError {
     Base64Error(#[from] base64::DecodeError),
 }
 
+impl AppError {
+    /// Stable identifier of the error kind, for the frontend to match on
+    /// instead of the English message
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FileNotFound(_) => "FILE_NOT_FOUND",
+            AppError::UnsupportedFormat(_) => "UNSUPPORTED_FORMAT",
+            AppError::InvalidImageData(_) => "INVALID_IMAGE_DATA",
+            AppError::InvalidParameters(_) => "INVALID_PARAMETERS",
+            AppError::SaveFailed(_) => "SAVE_FAILED",
+            AppError::OperationFailed(_) => "OPERATION_FAILED",
+            AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::FileExists(_) => "FILE_EXISTS",
+            AppError::NoAlpha(_) => "NO_ALPHA",
+            AppError::CorruptImage(_) => "CORRUPT_IMAGE",
+            AppError::IoError(_) => "IO_ERROR",
+            AppError::ImageError(_) => "IMAGE_ERROR",
+            AppError::Base64Error(_) => "BASE64_ERROR",
+        }
+    }
+}
+
 /// Result type alias for application operations
 pub type AppResult<T> = Result<T, AppError>;
 
@@ -44,6 +76,40 @@ impl From<AppError> for String {
     }
 }
 
+/// Error returned by Tauri commands, serialized as `{ code, message }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandError {
+    /// One of the codes from AppError::code
+    pub code: &'static str,
+    /// Human-readable description, as produced by AppError's Display
+    pub message: String,
+}
+
+impl From<AppError> for CommandError {
+    fn from(error: AppError) -> Self {
+        CommandError {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Plain string errors from helpers that don't use AppError
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError {
+            code: AppError::OperationFailed(String::new()).code(),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Utility functions for error handling
 pub mod utils {
     use super::AppError;
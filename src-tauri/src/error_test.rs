@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::error::{AppError, utils};
+    use crate::error::{AppError, CommandError, utils};
+    use crate::test_state::limiter;
 
     #[test]
     fn test_validate_dimensions_valid() {
@@ -65,6 +66,7 @@ mod tests {
             AppError::SaveFailed("disk full".to_string()),
             AppError::OperationFailed("unknown".to_string()),
             AppError::PermissionDenied("readonly".to_string()),
+            AppError::FileExists("photo.png".to_string()),
         ];
 
         for error in errors {
@@ -72,4 +74,76 @@ mod tests {
             assert!(!error_string.is_empty());
         }
     }
+
+    #[test]
+    fn test_error_codes() {
+        let io_error = std::io::Error::other("disk");
+        let image_error = image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Unknown,
+                image::error::UnsupportedErrorKind::GenericFeature("test".to_string()),
+            ),
+        );
+        let base64_error = base64::DecodeError::InvalidLength(3);
+
+        let cases = vec![
+            (AppError::FileNotFound("test.png".to_string()), "FILE_NOT_FOUND"),
+            (AppError::UnsupportedFormat("XYZ".to_string()), "UNSUPPORTED_FORMAT"),
+            (AppError::InvalidImageData("corrupted".to_string()), "INVALID_IMAGE_DATA"),
+            (AppError::InvalidParameters("bad params".to_string()), "INVALID_PARAMETERS"),
+            (AppError::SaveFailed("disk full".to_string()), "SAVE_FAILED"),
+            (AppError::OperationFailed("unknown".to_string()), "OPERATION_FAILED"),
+            (AppError::PermissionDenied("readonly".to_string()), "PERMISSION_DENIED"),
+            (AppError::FileExists("photo.png".to_string()), "FILE_EXISTS"),
+            (AppError::NoAlpha("needs transparency".to_string()), "NO_ALPHA"),
+            (AppError::CorruptImage("photo.png".to_string()), "CORRUPT_IMAGE"),
+            (AppError::IoError(io_error), "IO_ERROR"),
+            (AppError::ImageError(image_error), "IMAGE_ERROR"),
+            (AppError::Base64Error(base64_error), "BASE64_ERROR"),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_command_error_carries_code_and_message() {
+        let error: CommandError = AppError::FileNotFound("test.png".to_string()).into();
+        assert_eq!(error.code, "FILE_NOT_FOUND");
+        assert_eq!(error.message, "File not found: test.png");
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "FILE_NOT_FOUND", "message": "File not found: test.png" }));
+
+        // Helpers that still report plain strings surface as generic failures
+        let error: CommandError = "Failed to read fonts directory".to_string().into();
+        assert_eq!(error.code, "OPERATION_FAILED");
+        assert_eq!(error.message, "Failed to read fonts directory");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_base64_is_reported_the_same_by_every_command() {
+        let image_data = crate::types::ImageData {
+            path: "broken.png".to_string(),
+            width: 10,
+            height: 10,
+            format: crate::types::ImageFormat::PNG,
+            data: "not*valid*base64!".to_string(),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let errors = vec![
+            crate::resize_image(image_data.clone(), 5, 5, false, None, None, limiter()).await.unwrap_err(),
+            crate::crop_image(image_data.clone(), 0, 0, 5, 5, None).await.unwrap_err(),
+            crate::convert_format(image_data.clone(), "JPEG".to_string(), None, None, limiter()).await.unwrap_err(),
+            crate::posterize_image(image_data, 4).await.unwrap_err(),
+        ];
+
+        for error in errors {
+            assert_eq!(error.code, "BASE64_ERROR");
+            assert!(error.message.starts_with("Base64 decode error: "), "{}", error.message);
+        }
+    }
 }
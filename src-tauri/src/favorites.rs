@@ -1,15 +1,22 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::camera::{self, CameraInfo};
 use crate::error::{AppError, AppResult};
 
 /// Favorite image entry with tags
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FavoriteImage {
     pub path: String,
     pub tags: Vec<String>,
     pub added_at: i64, // Unix timestamp
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub last_viewed: Option<i64>, // Unix timestamp
+    #[serde(default)]
+    pub camera: Option<CameraInfo>, // Read from EXIF when favorited
 }
 
 /// Favorites configuration
@@ -65,20 +72,62 @@ impl FavoritesConfig {
         Ok(())
     }
     
-    /// Add or update a favorite image
-    pub fn add_favorite(&mut self, path: String, tags: Vec<String>) {
-        let now = std::time::SystemTime::now()
+    /// Current time as a Unix timestamp
+    fn now() -> i64 {
+        std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_secs() as i64;
+            .as_secs() as i64
+    }
+    
+    /// Add or update a favorite image
+    /// 
+    /// Updating an existing favorite keeps its note and last viewed time.
+    /// Camera and lens information is (re)read from the file's EXIF data.
+    pub fn add_favorite(&mut self, path: String, tags: Vec<String>) {
+        let (note, last_viewed) = self.favorites
+            .get(&path)
+            .map(|existing| (existing.note.clone(), existing.last_viewed))
+            .unwrap_or((None, None));
+        
+        let camera = camera::read_camera_info(Path::new(&path));
         
         self.favorites.insert(path.clone(), FavoriteImage {
             path,
             tags,
-            added_at: now,
+            added_at: Self::now(),
+            note,
+            last_viewed,
+            camera,
         });
     }
     
+    /// Set or clear the note of a favorite image
+    /// 
+    /// Returns false if the image is not a favorite
+    pub fn update_note(&mut self, path: &str, note: Option<String>) -> bool {
+        match self.favorites.get_mut(path) {
+            Some(favorite) => {
+                favorite.note = note.filter(|n| !n.trim().is_empty());
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Update the last viewed time of a favorite image to now
+    /// 
+    /// Returns false if the image is not a favorite
+    pub fn touch(&mut self, path: &str) -> bool {
+        match self.favorites.get_mut(path) {
+            Some(favorite) => {
+                favorite.last_viewed = Some(Self::now());
+                true
+            }
+            None => false,
+        }
+    }
+    
     /// Remove a favorite image
     pub fn remove_favorite(&mut self, path: &str) -> bool {
         self.favorites.remove(path).is_some()
@@ -89,6 +138,28 @@ impl FavoritesConfig {
         self.favorites.contains_key(path)
     }
     
+    /// List favorites whose file no longer exists
+    pub fn list_missing(&self) -> Vec<String> {
+        let mut missing: Vec<String> = self.favorites
+            .keys()
+            .filter(|path| !Path::new(path).exists())
+            .cloned()
+            .collect();
+        missing.sort();
+        missing
+    }
+    
+    /// Remove favorites whose file no longer exists
+    /// 
+    /// Returns the removed paths
+    pub fn prune_missing(&mut self) -> Vec<String> {
+        let missing = self.list_missing();
+        for path in &missing {
+            self.favorites.remove(path);
+        }
+        missing
+    }
+    
     /// Get all favorites
     pub fn get_all(&self) -> Vec<FavoriteImage> {
         let mut favorites: Vec<FavoriteImage> = self.favorites.values().cloned().collect();
@@ -98,20 +169,36 @@ impl FavoritesConfig {
     }
     
     /// Search favorites by tags
-    pub fn search_by_tags(&self, tags: &[String]) -> Vec<FavoriteImage> {
+    /// 
+    /// If match_all is true, a favorite must match every search tag (AND),
+    /// otherwise matching any of them is enough (OR).
+    /// If exact is true, tags must be equal (case-insensitive), otherwise
+    /// substring matching is used. An empty tag list returns all favorites.
+    pub fn search_by_tags(&self, tags: &[String], match_all: bool, exact: bool) -> Vec<FavoriteImage> {
         if tags.is_empty() {
             return self.get_all();
         }
         
+        let tag_matches = |fav: &FavoriteImage, search_tag: &String| {
+            let search_tag = search_tag.to_lowercase();
+            fav.tags.iter().any(|fav_tag| {
+                let fav_tag = fav_tag.to_lowercase();
+                if exact {
+                    fav_tag == search_tag
+                } else {
+                    fav_tag.contains(&search_tag)
+                }
+            })
+        };
+        
         let mut results: Vec<FavoriteImage> = self.favorites
             .values()
             .filter(|fav| {
-                // Check if favorite has any of the search tags
-                tags.iter().any(|search_tag| {
-                    fav.tags.iter().any(|fav_tag| {
-                        fav_tag.to_lowercase().contains(&search_tag.to_lowercase())
-                    })
-                })
+                if match_all {
+                    tags.iter().all(|search_tag| tag_matches(fav, search_tag))
+                } else {
+                    tags.iter().any(|search_tag| tag_matches(fav, search_tag))
+                }
             })
             .cloned()
             .collect();
@@ -121,6 +208,47 @@ impl FavoritesConfig {
         results
     }
     
+    /// Search favorites by camera make, camera model or lens model
+    /// 
+    /// Matching is case-insensitive substring matching. Favorites without
+    /// EXIF camera information never match.
+    pub fn search_by_camera(&self, query: &str) -> Vec<FavoriteImage> {
+        let mut results: Vec<FavoriteImage> = self.favorites
+            .values()
+            .filter(|fav| fav.camera.as_ref().is_some_and(|info| info.matches(query)))
+            .cloned()
+            .collect();
+        
+        // Sort by added_at descending (newest first)
+        results.sort_by_key(|fav| std::cmp::Reverse(fav.added_at));
+        results
+    }
+    
+    /// Rename a tag on every favorite that has it
+    /// 
+    /// If a favorite already has the new tag, the old one is simply removed so
+    /// the tag isn't duplicated. Returns the number of favorites changed.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        if old == new {
+            return 0;
+        }
+        
+        let mut affected = 0;
+        
+        for favorite in self.favorites.values_mut() {
+            if let Some(index) = favorite.tags.iter().position(|tag| tag == old) {
+                if favorite.tags.iter().any(|tag| tag == new) {
+                    favorite.tags.remove(index);
+                } else {
+                    favorite.tags[index] = new.to_string();
+                }
+                affected += 1;
+            }
+        }
+        
+        affected
+    }
+    
     /// Get all unique tags
     pub fn get_all_tags(&self) -> Vec<String> {
         let mut tags_set: std::collections::HashSet<String> = std::collections::HashSet::new();
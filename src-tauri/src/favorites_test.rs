@@ -83,20 +83,20 @@ mod tests {
         );
 
         // Search for "nature" tag
-        let results = config.search_by_tags(&vec!["nature".to_string()]);
+        let results = config.search_by_tags(&vec!["nature".to_string()], false, false);
         assert_eq!(results.len(), 2);
 
         // Search for "portrait" tag
-        let results = config.search_by_tags(&vec!["portrait".to_string()]);
+        let results = config.search_by_tags(&vec!["portrait".to_string()], false, false);
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].path, "/path/to/image2.png");
 
         // Search for non-existent tag
-        let results = config.search_by_tags(&vec!["architecture".to_string()]);
+        let results = config.search_by_tags(&vec!["architecture".to_string()], false, false);
         assert_eq!(results.len(), 0);
 
         // Empty search returns all
-        let results = config.search_by_tags(&vec![]);
+        let results = config.search_by_tags(&vec![], false, false);
         assert_eq!(results.len(), 3);
 
         cleanup_test_config();
@@ -150,4 +150,252 @@ mod tests {
 
         cleanup_test_config();
     }
+
+    #[test]
+    fn test_update_note_and_touch() {
+        let mut config = FavoritesConfig::default();
+        
+        config.add_favorite(
+            "/path/to/image1.png".to_string(),
+            vec!["nature".to_string()]
+        );
+
+        assert!(config.update_note("/path/to/image1.png", Some("Print for the hallway".to_string())));
+        assert!(config.touch("/path/to/image1.png"));
+        assert!(!config.update_note("/path/to/missing.png", Some("note".to_string())));
+        assert!(!config.touch("/path/to/missing.png"));
+
+        let favorites = config.get_all();
+        assert_eq!(favorites[0].note.as_deref(), Some("Print for the hallway"));
+        assert!(favorites[0].last_viewed.is_some());
+
+        // Re-adding with new tags keeps the note
+        config.add_favorite(
+            "/path/to/image1.png".to_string(),
+            vec!["nature".to_string(), "sunset".to_string()]
+        );
+        assert_eq!(config.get_all()[0].note.as_deref(), Some("Print for the hallway"));
+    }
+
+    #[test]
+    fn test_load_old_schema_without_note_fields() {
+        let old_json = r#"{
+            "favorites": {
+                "/path/to/image1.png": {
+                    "path": "/path/to/image1.png",
+                    "tags": ["nature"],
+                    "added_at": 1700000000
+                }
+            }
+        }"#;
+
+        let config: FavoritesConfig = serde_json::from_str(old_json).unwrap();
+
+        let favorites = config.get_all();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].added_at, 1700000000);
+        assert!(favorites[0].note.is_none());
+        assert!(favorites[0].last_viewed.is_none());
+    }
+
+    #[test]
+    fn test_search_by_tags_match_all() {
+        let mut config = FavoritesConfig::default();
+        
+        config.add_favorite(
+            "/path/to/image1.png".to_string(),
+            vec!["nature".to_string(), "sunset".to_string()]
+        );
+        
+        config.add_favorite(
+            "/path/to/image2.png".to_string(),
+            vec!["nature".to_string(), "forest".to_string()]
+        );
+        
+        config.add_favorite(
+            "/path/to/image3.png".to_string(),
+            vec!["city".to_string(), "sunset".to_string()]
+        );
+
+        let search = vec!["nature".to_string(), "sunset".to_string()];
+
+        // OR mode matches any of the tags
+        let results = config.search_by_tags(&search, false, false);
+        assert_eq!(results.len(), 3);
+
+        // AND mode requires both tags
+        let results = config.search_by_tags(&search, true, false);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "/path/to/image1.png");
+
+        // AND mode with a tag nobody has matches nothing
+        let results = config.search_by_tags(
+            &["nature".to_string(), "portrait".to_string()], true, false
+        );
+        assert_eq!(results.len(), 0);
+
+        // Empty search still returns all in AND mode
+        let results = config.search_by_tags(&[], true, true);
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn test_search_by_tags_exact() {
+        let mut config = FavoritesConfig::default();
+        
+        config.add_favorite(
+            "/path/to/image1.png".to_string(),
+            vec!["sunset".to_string()]
+        );
+        
+        config.add_favorite(
+            "/path/to/image2.png".to_string(),
+            vec!["sun".to_string()]
+        );
+
+        // Substring matching finds both
+        let results = config.search_by_tags(&["sun".to_string()], false, false);
+        assert_eq!(results.len(), 2);
+
+        // Exact matching only finds the exact tag, ignoring case
+        let results = config.search_by_tags(&["SUN".to_string()], false, true);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "/path/to/image2.png");
+    }
+
+    #[test]
+    fn test_rename_tag() {
+        let mut config = FavoritesConfig::default();
+        
+        for i in 1..=3 {
+            config.add_favorite(
+                format!("/path/to/image{}.png", i),
+                vec!["vacation".to_string(), format!("tag{}", i)]
+            );
+        }
+        
+        config.add_favorite(
+            "/path/to/image4.png".to_string(),
+            vec!["work".to_string()]
+        );
+
+        let affected = config.rename_tag("vacation", "holiday");
+        assert_eq!(affected, 3);
+
+        let results = config.search_by_tags(&["holiday".to_string()], false, true);
+        assert_eq!(results.len(), 3);
+        assert!(config.search_by_tags(&["vacation".to_string()], false, true).is_empty());
+        assert!(!config.get_all_tags().contains(&"vacation".to_string()));
+
+        // Renaming onto a tag the favorite already has de-duplicates
+        let affected = config.rename_tag("tag1", "holiday");
+        assert_eq!(affected, 1);
+        let image1 = &config.favorites["/path/to/image1.png"];
+        assert_eq!(image1.tags, vec!["holiday".to_string()]);
+
+        // Unknown tags affect nothing
+        assert_eq!(config.rename_tag("missing", "other"), 0);
+    }
+
+    #[test]
+    fn test_prune_missing_favorites() {
+        let existing = std::env::temp_dir().join("test_favorites_existing.png");
+        let deleted = std::env::temp_dir().join("test_favorites_deleted.png");
+        fs::write(&existing, b"image").unwrap();
+        fs::write(&deleted, b"image").unwrap();
+
+        let mut config = FavoritesConfig::default();
+        config.add_favorite(existing.to_string_lossy().to_string(), vec![]);
+        config.add_favorite(deleted.to_string_lossy().to_string(), vec![]);
+
+        fs::remove_file(&deleted).unwrap();
+
+        let deleted_path = deleted.to_string_lossy().to_string();
+        assert_eq!(config.list_missing(), vec![deleted_path.clone()]);
+        // Listing is non-destructive
+        assert_eq!(config.favorites.len(), 2);
+
+        let removed = config.prune_missing();
+        assert_eq!(removed, vec![deleted_path.clone()]);
+        assert_eq!(config.favorites.len(), 1);
+        assert!(!config.is_favorite(&deleted_path));
+        assert!(config.is_favorite(&existing.to_string_lossy()));
+        assert!(config.list_missing().is_empty());
+
+        fs::remove_file(&existing).unwrap();
+    }
+
+    /// Helper function to write a JPEG with camera and lens EXIF data
+    fn write_jpeg_with_exif(path: &std::path::Path) {
+        use exif::{Field, In, Rational, Tag, Value};
+
+        let ascii = |text: &str| Value::Ascii(vec![text.as_bytes().to_vec()]);
+        let rational = |num, denom| Value::Rational(vec![Rational { num, denom }]);
+        let fields = [
+            Field { tag: Tag::Make, ifd_num: In::PRIMARY, value: ascii("Fujifilm") },
+            Field { tag: Tag::Model, ifd_num: In::PRIMARY, value: ascii("X-T4") },
+            Field { tag: Tag::LensModel, ifd_num: In::PRIMARY, value: ascii("XF35mmF1.4 R") },
+            Field { tag: Tag::FocalLength, ifd_num: In::PRIMARY, value: rational(35, 1) },
+            Field { tag: Tag::FNumber, ifd_num: In::PRIMARY, value: rational(14, 10) },
+            Field { tag: Tag::ExposureTime, ifd_num: In::PRIMARY, value: rational(1, 250) },
+            Field { tag: Tag::PhotographicSensitivity, ifd_num: In::PRIMARY, value: Value::Short(vec![400]) },
+        ];
+
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut jpeg = Vec::new();
+        image::RgbImage::from_pixel(8, 8, image::Rgb([120, 80, 40]))
+            .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .unwrap();
+
+        // Insert an APP1 Exif segment right after the SOI marker
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        jpeg.splice(2..2, segment);
+
+        fs::write(path, jpeg).unwrap();
+    }
+
+    #[test]
+    fn test_favorite_reads_camera_info_and_searches_by_camera() {
+        let photo = std::env::temp_dir().join("test_favorites_camera.jpg");
+        let plain = std::env::temp_dir().join("test_favorites_no_exif.png");
+        write_jpeg_with_exif(&photo);
+        image::RgbImage::from_pixel(8, 8, image::Rgb([0, 0, 0])).save(&plain).unwrap();
+
+        let photo_path = photo.to_string_lossy().to_string();
+        let plain_path = plain.to_string_lossy().to_string();
+
+        let mut config = FavoritesConfig::default();
+        config.add_favorite(photo_path.clone(), vec![]);
+        config.add_favorite(plain_path.clone(), vec![]);
+
+        let camera = config.favorites[&photo_path].camera.clone().unwrap();
+        assert_eq!(camera.camera_make.as_deref(), Some("Fujifilm"));
+        assert_eq!(camera.camera_model.as_deref(), Some("X-T4"));
+        assert_eq!(camera.lens_model.as_deref(), Some("XF35mmF1.4 R"));
+        assert_eq!(camera.focal_length, Some(35.0));
+        assert_eq!(camera.aperture, Some(1.4));
+        assert_eq!(camera.exposure_time, Some(0.004));
+        assert_eq!(camera.iso, Some(400));
+        assert!(config.favorites[&plain_path].camera.is_none());
+
+        // Camera and lens searches are case-insensitive substrings
+        let by_body = config.search_by_camera("x-t4");
+        assert_eq!(by_body.len(), 1);
+        assert_eq!(by_body[0].path, photo_path);
+        assert_eq!(config.search_by_camera("XF35").len(), 1);
+        assert!(config.search_by_camera("Canon").is_empty());
+
+        fs::remove_file(&photo).unwrap();
+        fs::remove_file(&plain).unwrap();
+    }
 }
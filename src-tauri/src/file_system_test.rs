@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use crate::test_state::limiter;
     use std::fs;
 
     #[tokio::test]
@@ -22,7 +23,7 @@ mod tests {
     async fn test_get_directory_images_nonexistent() {
         let result = get_directory_images("/nonexistent/path/12345".to_string()).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+        assert!(result.unwrap_err().message.contains("not found"));
     }
 
     #[tokio::test]
@@ -37,7 +38,7 @@ mod tests {
         fs::remove_file(&temp_file).unwrap();
         
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not a directory"));
+        assert!(result.unwrap_err().message.contains("not a directory"));
     }
 
     #[tokio::test]
@@ -51,11 +52,12 @@ mod tests {
             format: ImageFormat::PNG,
             data: test_data.to_string(),
             has_alpha: false,
+            display_data: None,
         };
         
         // Save to temp file
         let temp_file = std::env::temp_dir().join("test_save_image.png");
-        let result = save_image(image_data, temp_file.to_string_lossy().to_string()).await;
+        let result = save_image(image_data, temp_file.to_string_lossy().to_string(), None, None, limiter()).await;
         
         assert!(result.is_ok());
         assert!(temp_file.exists());
@@ -74,13 +76,20 @@ mod tests {
             format: ImageFormat::PNG,
             data: test_data.to_string(),
             has_alpha: false,
+            display_data: None,
         };
         
         // Try to save to non-existent directory
-        let result = save_image(image_data, "/nonexistent/path/12345/test.png".to_string()).await;
+        let result = save_image(
+            image_data,
+            "/nonexistent/path/12345/test.png".to_string(),
+            None,
+            None,
+            limiter(),
+        ).await;
         
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not exist"));
+        assert!(result.unwrap_err().message.contains("not exist"));
     }
 
     #[tokio::test]
@@ -92,13 +101,14 @@ mod tests {
             format: ImageFormat::PNG,
             data: "invalid-base64!!!".to_string(),
             has_alpha: false,
+            display_data: None,
         };
         
         let temp_file = std::env::temp_dir().join("test_invalid.png");
-        let result = save_image(image_data, temp_file.to_string_lossy().to_string()).await;
+        let result = save_image(image_data, temp_file.to_string_lossy().to_string(), None, None, limiter()).await;
         
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("decode"));
+        assert!(result.unwrap_err().message.contains("decode"));
     }
 
     #[tokio::test]
@@ -132,4 +142,192 @@ mod tests {
         assert!(!images.iter().any(|p| p.ends_with("document.txt")));
         assert!(!images.iter().any(|p| p.ends_with("data.json")));
     }
+
+    #[tokio::test]
+    async fn test_save_unedited_jpeg_is_byte_identical() {
+        let temp_dir = std::env::temp_dir().join("test_save_unedited_jpeg");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        
+        let source = temp_dir.join("photo.jpg");
+        let img = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 128]));
+        img.save(&source).unwrap();
+        
+        // Open and save without editing
+        let loaded = load_image(source.to_string_lossy().to_string(), None, None).await.unwrap();
+        let target = temp_dir.join("copy.jpg");
+        save_image(loaded, target.to_string_lossy().to_string(), None, None, limiter()).await.unwrap();
+        
+        let original_bytes = fs::read(&source).unwrap();
+        let saved_bytes = fs::read(&target).unwrap();
+        
+        // Clean up
+        fs::remove_dir_all(&temp_dir).unwrap();
+        
+        // The original encoding is written back verbatim, not re-encoded
+        assert_eq!(saved_bytes, original_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_save_unedited_wide_gamut_jpeg_is_byte_identical() {
+        use image::ImageEncoder;
+
+        let temp_dir = std::env::temp_dir().join("test_save_unedited_p3_jpeg");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        
+        // A Display-P3 JPEG with camera data, which load_image converts for display
+        let mut exif = exif::experimental::Writer::new();
+        let make = exif::Field {
+            tag: exif::Tag::Make,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Ascii(vec![b"Fujifilm".to_vec()]),
+        };
+        exif.push_field(&make);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        exif.write(&mut tiff, false).unwrap();
+        
+        let source = temp_dir.join("photo.jpg");
+        let img = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 128]));
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(fs::File::create(&source).unwrap(), 95);
+        encoder
+            .set_icc_profile(moxcms::ColorProfile::new_display_p3().encode().unwrap())
+            .unwrap();
+        encoder.set_exif_metadata(tiff.into_inner()).unwrap();
+        encoder
+            .write_image(img.as_raw(), 64, 48, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        
+        let loaded = load_image(source.to_string_lossy().to_string(), None, None).await.unwrap();
+        assert!(loaded.display_data.is_some());
+        let target = temp_dir.join("copy.jpg");
+        save_image(loaded, target.to_string_lossy().to_string(), None, None, limiter()).await.unwrap();
+        
+        let original_bytes = fs::read(&source).unwrap();
+        let saved_bytes = fs::read(&target).unwrap();
+        
+        // Clean up
+        fs::remove_dir_all(&temp_dir).unwrap();
+        
+        // The profile and EXIF survive because the file is written back as it was
+        assert_eq!(saved_bytes, original_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_save_png_data_to_jpg_path_writes_jpeg() {
+        let temp_dir = std::env::temp_dir().join("test_save_as_jpeg");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        
+        let img = image::RgbaImage::from_pixel(32, 24, image::Rgba([30, 160, 90, 255]));
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        let image_data = ImageData {
+            path: "edited.png".to_string(),
+            width: 32,
+            height: 24,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&png),
+            has_alpha: false,
+            display_data: None,
+        };
+        
+        let target = temp_dir.join("edited.JPG");
+        save_image(image_data.clone(), target.to_string_lossy().to_string(), None, None, limiter()).await.unwrap();
+        
+        let bytes = fs::read(&target).unwrap();
+        assert_eq!(image::guess_format(&bytes).unwrap(), image::ImageFormat::Jpeg);
+        let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::Jpeg).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 24));
+        
+        // Unknown extensions keep the original encoding
+        let raw = temp_dir.join("edited.bin");
+        save_image(image_data.clone(), raw.to_string_lossy().to_string(), None, None, limiter()).await.unwrap();
+        assert_eq!(fs::read(&raw).unwrap(), png);
+        
+        // Formats that can't be encoded are rejected rather than mislabeled
+        let svg = temp_dir.join("edited.svg");
+        assert!(save_image(image_data, svg.to_string_lossy().to_string(), None, None, limiter()).await.is_err());
+        
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_without_overwrite_keeps_existing_file() {
+        let temp_dir = std::env::temp_dir().join("test_save_no_overwrite");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        
+        let img = image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 40, 40, 255]));
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        let image_data = ImageData {
+            path: "new.png".to_string(),
+            width: 8,
+            height: 8,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&png),
+            has_alpha: false,
+            display_data: None,
+        };
+        
+        let target = temp_dir.join("original.png");
+        fs::write(&target, b"original bytes").unwrap();
+        let target_path = target.to_string_lossy().to_string();
+        
+        let result = save_image(image_data.clone(), target_path.clone(), None, Some(false), limiter()).await;
+        assert!(result.unwrap_err().message.contains("File already exists"));
+        assert_eq!(fs::read(&target).unwrap(), b"original bytes");
+        
+        // Overwriting replaces the file and leaves no temporary file behind
+        save_image(image_data, target_path, None, Some(true), limiter()).await.unwrap();
+        assert_eq!(fs::read(&target).unwrap(), png);
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+        
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+    
+    #[test]
+    fn test_write_without_overwrite_never_replaces_target() {
+        let temp_dir = std::env::temp_dir().join("test_write_no_clobber");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = temp_dir.join("image.png");
+        
+        // A file that appears after the exists() check in save_image is kept
+        fs::write(&target, b"written by someone else").unwrap();
+        let error = write_file_atomically(&target, b"new bytes", false).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&target).unwrap(), b"written by someone else");
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+        
+        fs::remove_file(&target).unwrap();
+        write_file_atomically(&target, b"new bytes", false).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"new bytes");
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+        
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+    
+    #[test]
+    fn test_concurrent_writes_use_separate_temp_files() {
+        let temp_dir = std::env::temp_dir().join("test_write_concurrently");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = temp_dir.join("image.png");
+        
+        let payloads: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 64 * 1024]).collect();
+        std::thread::scope(|scope| {
+            for payload in &payloads {
+                let target = &target;
+                scope.spawn(move || write_file_atomically(target, payload, true).unwrap());
+            }
+        });
+        
+        // One complete payload wins and no temporary files are left behind
+        assert!(payloads.contains(&fs::read(&target).unwrap()));
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+        
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::types::{ImageData, ImageFormat, ConversionOptions};
+    use crate::test_state::limiter;
     use base64::{Engine as _, engine::general_purpose};
     use image::{ImageBuffer, Rgba};
 
@@ -28,6 +29,7 @@ mod tests {
             format,
             data: base64_data,
             has_alpha: false,
+            display_data: None,
         }
     }
 
@@ -38,7 +40,8 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "JPEG".to_string(),
-            Some(ConversionOptions { quality: Some(90) })
+            Some(ConversionOptions { quality: Some(90), ..Default::default() }),
+            None, limiter()
         ).await;
 
         assert!(result.is_ok());
@@ -56,7 +59,8 @@ mod tests {
         let result = crate::convert_format(
             jpeg_image.clone(),
             "PNG".to_string(),
-            None
+            None,
+            None, limiter()
         ).await;
 
         assert!(result.is_ok());
@@ -67,6 +71,29 @@ mod tests {
         assert!(converted.path.ends_with(".png"));
     }
 
+    #[tokio::test]
+    async fn test_convert_keeps_custom_output_path() {
+        let png_image = create_test_image(40, 40, ImageFormat::PNG);
+
+        // The destination is taken verbatim, even with a mismatched extension
+        let output_path = "/exports/batch 2/photo_final.JPEG.bak".to_string();
+        let converted = crate::convert_format(
+            png_image.clone(),
+            "JPEG".to_string(),
+            None,
+            Some(output_path.clone()),
+            limiter(),
+        )
+            .await
+            .unwrap();
+        assert_eq!(converted.path, output_path);
+        assert_eq!(converted.format, ImageFormat::JPEG);
+
+        // Without one the source path gets the new extension
+        let default = crate::convert_format(png_image, "JPEG".to_string(), None, None, limiter()).await.unwrap();
+        assert!(default.path.ends_with(".jpg"));
+    }
+
     #[tokio::test]
     async fn test_convert_with_quality_parameter() {
         let png_image = create_test_image(100, 100, ImageFormat::PNG);
@@ -75,7 +102,8 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "JPEG".to_string(),
-            Some(ConversionOptions { quality: Some(50) })
+            Some(ConversionOptions { quality: Some(50), ..Default::default() }),
+            None, limiter()
         ).await;
 
         assert!(result.is_ok());
@@ -91,11 +119,12 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "JPEG".to_string(),
-            Some(ConversionOptions { quality: Some(150) })
+            Some(ConversionOptions { quality: Some(150), ..Default::default() }),
+            None, limiter()
         ).await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Quality parameter must be between 1 and 100"));
+        assert!(result.unwrap_err().message.contains("Quality parameter must be between 1 and 100"));
     }
 
     #[tokio::test]
@@ -106,11 +135,12 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "INVALID".to_string(),
-            None
+            None,
+            None, limiter()
         ).await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unsupported target format"));
+        assert!(result.unwrap_err().message.contains("Unsupported target format"));
     }
 
     #[tokio::test]
@@ -121,7 +151,8 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "SVG".to_string(),
-            None
+            None,
+            None, limiter()
         ).await;
 
         assert!(result.is_err());
@@ -135,7 +166,8 @@ mod tests {
         let result = crate::convert_format(
             png_image.clone(),
             "BMP".to_string(),
-            None
+            None,
+            None, limiter()
         ).await;
 
         assert!(result.is_ok());
@@ -155,7 +187,8 @@ mod tests {
             let result = crate::convert_format(
                 original.clone(),
                 format.to_string(),
-                None
+                None,
+                None, limiter()
             ).await;
 
             assert!(result.is_ok(), "Failed to convert to {}", format);
@@ -163,4 +196,284 @@ mod tests {
             assert_eq!(converted.format.to_string(), format);
         }
     }
+
+    #[tokio::test]
+    async fn test_format_capabilities() {
+        let svg = crate::get_format_capabilities(ImageFormat::SVG).await.unwrap();
+        assert!(!svg.can_resize);
+        assert!(!svg.can_crop);
+        assert!(!svg.can_rotate);
+        assert!(!svg.can_set_background);
+        assert!(!svg.can_convert);
+        assert!(svg.supports_alpha);
+
+        let png = crate::get_format_capabilities(ImageFormat::PNG).await.unwrap();
+        assert!(png.can_resize);
+        assert!(png.can_crop);
+        assert!(png.can_rotate);
+        assert!(png.can_set_background);
+        assert!(png.can_convert);
+        assert!(png.can_animate);
+        assert!(png.supports_alpha);
+
+        let jpeg = crate::get_format_capabilities(ImageFormat::JPEG).await.unwrap();
+        assert!(jpeg.can_resize);
+        assert!(!jpeg.can_set_background);
+        assert!(!jpeg.supports_alpha);
+        assert!(!jpeg.can_animate);
+    }
+
+    #[tokio::test]
+    async fn test_convert_oversized_to_ico_errors_by_default() {
+        let png_image = create_test_image(1000, 1000, ImageFormat::PNG);
+
+        let result = crate::convert_format(png_image, "ICO".to_string(), None, None, limiter()).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("cannot exceed 256x256"));
+    }
+
+    #[tokio::test]
+    async fn test_convert_oversized_to_ico_downscales_when_requested() {
+        let png_image = create_test_image(1000, 500, ImageFormat::PNG);
+
+        let result = crate::convert_format(
+            png_image,
+            "ICO".to_string(),
+            Some(ConversionOptions { downscale_to_fit: Some(true), ..Default::default() }),
+            None, limiter()
+        ).await;
+
+        assert!(result.is_ok());
+        let converted = result.unwrap();
+        assert_eq!(converted.width, 256);
+        assert_eq!(converted.height, 128);
+
+        // The written ICO must decode with the reported size
+        let bytes = general_purpose::STANDARD.decode(&converted.data).unwrap();
+        let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::Ico).unwrap();
+        assert_eq!(decoded.width(), 256);
+        assert_eq!(decoded.height(), 128);
+    }
+
+    /// Helper function to decode every frame of a GIF with its delay in milliseconds
+    fn decode_gif_frames(image_data: &ImageData) -> Vec<(image::RgbaImage, u32)> {
+        use image::AnimationDecoder;
+
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::codecs::gif::GifDecoder::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .into_frames()
+            .map(|frame| {
+                let frame = frame.unwrap();
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                (frame.into_buffer(), numer / denom)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_create_gif_round_trips_frames() {
+        use crate::types::GifFrame;
+
+        let red = create_test_image(24, 16, ImageFormat::PNG);
+        let blue_pixels = ImageBuffer::from_pixel(24, 16, Rgba([0u8, 0u8, 255u8, 255u8]));
+        let mut blue = Vec::new();
+        blue_pixels.write_to(&mut std::io::Cursor::new(&mut blue), image::ImageFormat::Png).unwrap();
+
+        let frames = vec![
+            GifFrame { data: red.data.clone(), delay_ms: 100 },
+            GifFrame { data: general_purpose::STANDARD.encode(&blue), delay_ms: 250 },
+        ];
+        let gif = crate::create_gif(frames, None, limiter()).await.unwrap();
+
+        assert_eq!(gif.format, ImageFormat::GIF);
+        assert_eq!((gif.width, gif.height), (24, 16));
+        assert!(gif.path.ends_with(".gif"));
+
+        let decoded = decode_gif_frames(&gif);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].1, 100);
+        assert_eq!(decoded[1].1, 250);
+        assert_eq!(*decoded[0].0.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
+        assert_eq!(*decoded[1].0.get_pixel(5, 5), Rgba([0, 0, 255, 255]));
+    }
+
+    #[tokio::test]
+    async fn test_create_gif_validates_frames() {
+        use crate::types::GifFrame;
+
+        assert!(crate::create_gif(vec![], None, limiter()).await.is_err());
+
+        let small = create_test_image(10, 10, ImageFormat::PNG);
+        let large = create_test_image(20, 10, ImageFormat::PNG);
+        let frames = vec![
+            GifFrame { data: small.data, delay_ms: 100 },
+            GifFrame { data: large.data, delay_ms: 100 },
+        ];
+        let result = crate::create_gif(frames, Some(3), limiter()).await;
+        assert!(result.unwrap_err().message.contains("same dimensions"));
+    }
+
+    /// Helper function to list the (width, height) entries of an ICO directory
+    fn ico_entries(bytes: &[u8]) -> Vec<(u32, u32)> {
+        // A stored size of 0 means 256
+        let size = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+        let count = u16::from_le_bytes([bytes[4], bytes[5]]) as usize;
+        (0..count)
+            .map(|i| (size(bytes[6 + i * 16]), size(bytes[7 + i * 16])))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_create_icon_contains_every_size() {
+        let source = create_test_image(64, 64, ImageFormat::PNG);
+
+        let icon = crate::create_icon(source, vec![48, 16, 32], limiter()).await.unwrap();
+
+        assert_eq!(icon.format, ImageFormat::ICO);
+        assert_eq!(icon.path, "test.ico");
+        assert_eq!((icon.width, icon.height), (48, 48));
+
+        let bytes = general_purpose::STANDARD.decode(&icon.data).unwrap();
+        assert_eq!(ico_entries(&bytes), vec![(16, 16), (32, 32), (48, 48)]);
+
+        // The image crate decodes the largest entry
+        let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::Ico).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (48, 48));
+        assert_eq!(decoded.to_rgba8().get_pixel(24, 24), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[tokio::test]
+    async fn test_create_icon_pads_wide_images_and_validates_sizes() {
+        let wide = create_test_image(100, 50, ImageFormat::PNG);
+
+        let icon = crate::create_icon(wide.clone(), vec![256], limiter()).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&icon.data).unwrap();
+        assert_eq!(ico_entries(&bytes), vec![(256, 256)]);
+
+        // The image is centered with transparent bars above and below
+        let decoded = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(decoded.get_pixel(128, 10).0[3], 0);
+        assert_eq!(decoded.get_pixel(128, 128), &Rgba([255, 0, 0, 255]));
+
+        assert!(crate::create_icon(wide.clone(), vec![], limiter()).await.is_err());
+        assert!(crate::create_icon(wide, vec![16, 512], limiter()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jpeg_only_accepts_444_subsampling() {
+        use crate::types::JpegSubsampling;
+
+        let convert = |target: &str, subsampling| {
+            let options = ConversionOptions {
+                jpeg_subsampling: Some(subsampling),
+                ..Default::default()
+            };
+            let source = create_test_image(8, 8, ImageFormat::PNG);
+            crate::convert_format(source, target.to_string(), Some(options), None, limiter())
+        };
+        assert!(convert("JPEG", JpegSubsampling::S444).await.is_ok());
+        for subsampling in [JpegSubsampling::S422, JpegSubsampling::S420] {
+            let error = convert("JPEG", subsampling).await.unwrap_err();
+            assert_eq!(error.code, "INVALID_PARAMETERS");
+        }
+        // Only JPEG output has chroma subsampling
+        assert!(convert("PNG", JpegSubsampling::S420).await.is_ok());
+
+        // The option names match the usual notation
+        let parsed: JpegSubsampling = serde_json::from_str("\"4:2:0\"").unwrap();
+        assert_eq!(parsed, JpegSubsampling::S420);
+    }
+
+    #[tokio::test]
+    async fn test_tiff_compression_shrinks_flat_image() {
+        use crate::types::TiffCompression;
+
+        let img = ImageBuffer::from_pixel(300, 200, Rgba([40u8, 90, 160, 255]));
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png).unwrap();
+        let source = ImageData {
+            path: "scan.png".to_string(),
+            width: 300,
+            height: 200,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let convert = |compression| {
+            let options = ConversionOptions { tiff_compression: compression, ..Default::default() };
+            crate::convert_format(source.clone(), "TIFF".to_string(), Some(options), None, limiter())
+        };
+        let decode = |converted: &ImageData| {
+            let bytes = general_purpose::STANDARD.decode(&converted.data).unwrap();
+            (bytes.len(), image::load_from_memory(&bytes).unwrap().to_rgba8())
+        };
+
+        let (default_size, _) = decode(&convert(None).await.unwrap());
+        let (uncompressed_size, _) = decode(&convert(Some(TiffCompression::None)).await.unwrap());
+        assert!(uncompressed_size >= 300 * 200 * 3);
+        assert!(default_size >= 300 * 200 * 3);
+
+        for compression in [TiffCompression::Lzw, TiffCompression::Deflate] {
+            let converted = convert(Some(compression)).await.unwrap();
+            assert_eq!(converted.format, ImageFormat::TIFF);
+            let (size, pixels) = decode(&converted);
+            assert!(size * 10 < uncompressed_size, "{:?}: {} vs {}", compression, size, uncompressed_size);
+            // Compression is lossless
+            assert_eq!(pixels, img, "{:?}", compression);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lossless_webp_round_trips_pixels() {
+        // Screenshot-like content: hard edges, text-like detail and transparency
+        let img = ImageBuffer::from_fn(97, 61, |x, y| {
+            let alpha = if x < 8 { 0 } else if y < 8 { 128 } else { 255 };
+            Rgba([(x * 7 % 256) as u8, (y * 13 % 256) as u8, ((x ^ y) * 5 % 256) as u8, alpha])
+        });
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png).unwrap();
+        let source = ImageData {
+            path: "screenshot.png".to_string(),
+            width: 97,
+            height: 61,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        };
+
+        let options = ConversionOptions { webp_lossless: Some(true), quality: Some(10), ..Default::default() };
+        let converted = crate::convert_format(
+            source,
+            "WEBP".to_string(),
+            Some(options),
+            None,
+            limiter(),
+        ).await.unwrap();
+        assert_eq!(converted.format, ImageFormat::WEBP);
+        assert!(converted.path.ends_with(".webp"));
+
+        let bytes = general_purpose::STANDARD.decode(&converted.data).unwrap();
+        let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::WebP).unwrap().to_rgba8();
+        assert_eq!(decoded, img);
+    }
+
+    #[tokio::test]
+    async fn test_lossy_webp_is_rejected() {
+        let options = ConversionOptions { webp_lossless: Some(false), ..Default::default() };
+        let error = crate::convert_format(
+            create_test_image(8, 8, ImageFormat::PNG),
+            "WEBP".to_string(),
+            Some(options),
+            None,
+            limiter(),
+        )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, "INVALID_PARAMETERS");
+    }
 }
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::{AppError, AppResult};
+use crate::favorites::FavoriteImage;
+use crate::naming::{self, CollisionStrategy};
+use crate::types::ResampleOptions;
+
+/// Longest edge of the thumbnails shown on the gallery page
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// Escape text for safe inclusion in HTML content and attributes
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Export favorites as a static HTML gallery
+///
+/// Copies each image into `images/`, writes a PNG thumbnail into `thumbnails/`
+/// and generates an `index.html` laying them out with their tags and notes.
+/// Files are prefixed with their position so images sharing a name don't collide.
+/// Files left over from an earlier export are handled by `collision`: skipped
+/// files are kept and referenced as they are, renamed ones get a numeric
+/// suffix. Returns the path of the `index.html`.
+pub fn export_gallery(
+    entries: &[FavoriteImage],
+    output_dir: &Path,
+    collision: CollisionStrategy,
+) -> AppResult<PathBuf> {
+    if entries.is_empty() {
+        return Err(AppError::InvalidParameters("No images to export".to_string()));
+    }
+
+    let images_dir = output_dir.join("images");
+    let thumbnails_dir = output_dir.join("thumbnails");
+    fs::create_dir_all(&images_dir).map_err(AppError::IoError)?;
+    fs::create_dir_all(&thumbnails_dir).map_err(AppError::IoError)?;
+
+    let mut cards = String::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let source = Path::new(&entry.path);
+        if !source.exists() {
+            return Err(AppError::FileNotFound(entry.path.clone()));
+        }
+
+        let file_name = source.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("image");
+        let stem = source.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image");
+
+        let (image_path, copy_image) =
+            output_path(images_dir.join(format!("{:03}_{}", index, file_name)), collision);
+        let (thumbnail_path, write_thumbnail) =
+            output_path(thumbnails_dir.join(format!("{:03}_{}.png", index, stem)), collision);
+        let image_name = file_name_of(&image_path);
+        let thumbnail_name = file_name_of(&thumbnail_path);
+
+        if copy_image {
+            fs::copy(source, &image_path).map_err(AppError::IoError)?;
+        }
+
+        if write_thumbnail {
+            let img = crate::open_image(source)?;
+            crate::thumbnail_image(&img, THUMBNAIL_SIZE, &ResampleOptions::balanced())
+                .save(&thumbnail_path)
+                .map_err(AppError::ImageError)?;
+        }
+
+        let tags: String = entry.tags
+            .iter()
+            .map(|tag| format!("<span class=\"tag\">{}</span>", escape_html(tag)))
+            .collect();
+        let note = entry.note
+            .as_deref()
+            .map(|note| format!("<p class=\"note\">{}</p>", escape_html(note)))
+            .unwrap_or_default();
+
+        cards.push_str(&format!(
+            "    <figure>\n      <a href=\"images/{image}\"><img src=\"thumbnails/{thumb}\" alt=\"{alt}\"></a>\n      <figcaption>{alt}<div class=\"tags\">{tags}</div>{note}</figcaption>\n    </figure>\n",
+            image = escape_html(&image_name),
+            thumb = escape_html(&thumbnail_name),
+            alt = escape_html(file_name),
+            tags = tags,
+            note = note,
+        ));
+    }
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Favorites</title>
+  <style>
+    body {{ font-family: sans-serif; margin: 24px; background: #f5f5f5; }}
+    .gallery {{ display: grid; grid-template-columns: repeat(auto-fill, minmax({size}px, 1fr)); gap: 16px; }}
+    figure {{ margin: 0; padding: 8px; background: #fff; border-radius: 6px; }}
+    img {{ max-width: 100%; display: block; margin: 0 auto; }}
+    figcaption {{ margin-top: 8px; font-size: 14px; }}
+    .tag {{ display: inline-block; margin: 4px 4px 0 0; padding: 2px 6px; background: #e0e0e0; border-radius: 4px; font-size: 12px; }}
+    .note {{ margin: 6px 0 0; color: #555; font-size: 12px; }}
+  </style>
+</head>
+<body>
+  <div class="gallery">
+{cards}  </div>
+</body>
+</html>
+"#,
+        size = THUMBNAIL_SIZE / 2,
+        cards = cards,
+    );
+
+    let (index_path, write_index) = output_path(output_dir.join("index.html"), collision);
+    if write_index {
+        fs::write(&index_path, html).map_err(AppError::IoError)?;
+    }
+
+    Ok(index_path)
+}
+
+/// Resolve an output file against the collision strategy
+///
+/// Returns the path to reference and whether it should be written; a skipped
+/// file keeps its original path.
+fn output_path(path: PathBuf, collision: CollisionStrategy) -> (PathBuf, bool) {
+    match naming::resolve_collision(path.clone(), collision) {
+        Some(resolved) => (resolved, true),
+        None => (path, false),
+    }
+}
+
+/// File name of a path as text
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
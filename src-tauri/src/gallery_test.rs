@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::favorites::FavoriteImage;
+    use crate::gallery::export_gallery;
+    use crate::naming::CollisionStrategy;
+    use std::fs;
+
+    #[test]
+    fn test_export_gallery_writes_index_referencing_each_image() {
+        let temp_dir = std::env::temp_dir().join("test_gallery_export");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        let mut entries = Vec::new();
+        for (i, name) in ["beach.png", "forest.png"].iter().enumerate() {
+            let path = source_dir.join(name);
+            image::RgbImage::from_pixel(400, 200, image::Rgb([i as u8 * 100, 50, 50]))
+                .save(&path)
+                .unwrap();
+            entries.push(FavoriteImage {
+                path: path.to_string_lossy().to_string(),
+                tags: vec!["holiday".to_string(), "<b>bold</b>".to_string()],
+                ..Default::default()
+            });
+        }
+
+        let result = export_gallery(&entries, &output_dir, CollisionStrategy::Overwrite);
+
+        assert!(result.is_ok());
+        let index_path = result.unwrap();
+        assert_eq!(index_path, output_dir.join("index.html"));
+
+        let html = fs::read_to_string(&index_path).unwrap();
+        assert!(html.contains("images/000_beach.png"));
+        assert!(html.contains("images/001_forest.png"));
+        assert!(html.contains("thumbnails/000_beach.png"));
+        assert!(html.contains("holiday"));
+        // Tags are escaped, not injected as markup
+        assert!(html.contains("&lt;b&gt;bold&lt;/b&gt;"));
+
+        assert!(output_dir.join("images/001_forest.png").exists());
+        let thumbnail = image::open(output_dir.join("thumbnails/000_beach.png")).unwrap();
+        assert_eq!(thumbnail.width(), 320);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_gallery_skip_keeps_existing_and_rename_adds_suffix() {
+        let temp_dir = std::env::temp_dir().join("test_gallery_collisions");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(output_dir.join("images")).unwrap();
+
+        let source = temp_dir.join("beach.png");
+        image::RgbImage::from_pixel(40, 20, image::Rgb([0, 90, 200])).save(&source).unwrap();
+        fs::write(output_dir.join("images/000_beach.png"), "earlier export").unwrap();
+        let entries = vec![FavoriteImage {
+            path: source.to_string_lossy().to_string(),
+            ..Default::default()
+        }];
+
+        let index = export_gallery(&entries, &output_dir, CollisionStrategy::Skip).unwrap();
+        assert_eq!(fs::read_to_string(output_dir.join("images/000_beach.png")).unwrap(), "earlier export");
+        assert!(fs::read_to_string(&index).unwrap().contains("images/000_beach.png"));
+
+        let renamed_index = export_gallery(&entries, &output_dir, CollisionStrategy::Rename).unwrap();
+        assert_eq!(renamed_index, output_dir.join("index_1.html"));
+        let html = fs::read_to_string(&renamed_index).unwrap();
+        assert!(html.contains("images/000_beach_1.png"));
+        assert!(html.contains("thumbnails/000_beach_1.png"));
+        assert!(image::open(output_dir.join("images/000_beach_1.png")).is_ok());
+        assert_eq!(fs::read_to_string(output_dir.join("images/000_beach.png")).unwrap(), "earlier export");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_gallery_rejects_empty_list() {
+        let output_dir = std::env::temp_dir().join("test_gallery_empty");
+        assert!(export_gallery(&[], &output_dir, CollisionStrategy::Overwrite).is_err());
+    }
+}
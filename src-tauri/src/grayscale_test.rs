@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{DynamicImage, ImageBuffer, LumaA, Rgba};
+
+    /// Helper function to wrap an image as PNG ImageData
+    fn encode_png(img: &DynamicImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: crate::detect_alpha_channel(img),
+            display_data: None,
+        }
+    }
+
+    /// Helper function to create a grayscale+alpha PNG with a transparent half
+    fn create_luma_alpha_image(width: u32, height: u32) -> ImageData {
+        let img = ImageBuffer::from_fn(width, height, |x, _y| {
+            LumaA([(x * 255 / width) as u8, if x < width / 2 { 0 } else { 255 }])
+        });
+        encode_png(&DynamicImage::ImageLumaA8(img))
+    }
+
+    /// Color type of the encoded PNG
+    fn encoded_color(image_data: &ImageData) -> image::ColorType {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+            .unwrap()
+            .color()
+    }
+
+    #[test]
+    fn test_detect_alpha_channel_for_luma_alpha() {
+        let transparent = ImageBuffer::from_pixel(2, 2, LumaA([10u16, 0]));
+        let opaque = ImageBuffer::from_pixel(2, 2, LumaA([10u8, 255]));
+
+        assert!(crate::detect_alpha_channel(&DynamicImage::ImageLumaA16(transparent)));
+        assert!(!crate::detect_alpha_channel(&DynamicImage::ImageLumaA8(opaque)));
+    }
+
+    #[tokio::test]
+    async fn test_grayscale_keeps_luma_alpha() {
+        let result = crate::grayscale_image(create_luma_alpha_image(20, 10)).await.unwrap();
+
+        assert_eq!(encoded_color(&result), image::ColorType::La8);
+        assert!(result.has_alpha);
+    }
+
+    #[tokio::test]
+    async fn test_grayscale_of_rgba_produces_luma_alpha() {
+        let img = ImageBuffer::from_pixel(4, 4, Rgba([200u8, 100, 50, 128]));
+
+        let result = crate::grayscale_image(encode_png(&DynamicImage::ImageRgba8(img))).await.unwrap();
+
+        assert_eq!(encoded_color(&result), image::ColorType::La8);
+    }
+
+    #[tokio::test]
+    async fn test_crop_keeps_luma_alpha() {
+        let result = crate::crop_image(create_luma_alpha_image(20, 10), 5, 0, 10, 10, None).await.unwrap();
+
+        assert_eq!(encoded_color(&result), image::ColorType::La8);
+    }
+
+    #[tokio::test]
+    async fn test_rgba_based_operations_keep_luma_alpha() {
+        let cropped = crate::crop_supersampled(create_luma_alpha_image(20, 10), 2.5, 0.0, 10.0, 10.0, 5, 5, limiter())
+            .await
+            .unwrap();
+        assert_eq!(encoded_color(&cropped), image::ColorType::La8);
+
+        let straightened = crate::straighten_by_line(
+            create_luma_alpha_image(20, 10),
+            0.0,
+            0.0,
+            10.0,
+            1.0,
+            None,
+            limiter(),
+        )
+            .await
+            .unwrap();
+        assert_eq!(encoded_color(&straightened), image::ColorType::La8);
+
+        let equalized = crate::equalize_histogram_image(create_luma_alpha_image(20, 10), None, limiter())
+            .await
+            .unwrap();
+        assert_eq!(encoded_color(&equalized), image::ColorType::La8);
+    }
+
+    #[tokio::test]
+    async fn test_opaque_grayscale_stays_single_channel() {
+        let img = ImageBuffer::from_pixel(8, 8, image::Luma([90u8]));
+
+        let result = crate::crop_supersampled(
+            encode_png(&DynamicImage::ImageLuma8(img)),
+            0.0,
+            0.0,
+            4.0,
+            4.0,
+            2,
+            2,
+            limiter(),
+        )
+            .await
+            .unwrap();
+
+        assert_eq!(encoded_color(&result), image::ColorType::L8);
+    }
+}
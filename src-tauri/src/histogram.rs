@@ -0,0 +1,231 @@
+use image::{DynamicImage, Rgba, RgbaImage};
+use imageproc::drawing::{draw_filled_rect_mut, draw_line_segment_mut};
+use imageproc::rect::Rect;
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, AppResult};
+use crate::types::ImageData;
+
+/// Space between the canvas edge and the plot area
+const MARGIN: u32 = 16;
+/// Length of the tick marks below the horizontal axis
+const TICK_LENGTH: u32 = 4;
+
+const BACKGROUND: Rgba<u8> = Rgba([24, 24, 24, 255]);
+const AXIS: Rgba<u8> = Rgba([200, 200, 200, 255]);
+const LUMINANCE_FILL: Rgba<u8> = Rgba([90, 90, 90, 255]);
+
+/// Warning colors of the clipping mask
+const HIGHLIGHT_WARNING: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const SHADOW_WARNING: Rgba<u8> = Rgba([0, 96, 255, 255]);
+
+/// Per-channel pixel counts for every 8-bit value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Histogram {
+    pub red: Vec<u32>,
+    pub green: Vec<u32>,
+    pub blue: Vec<u32>,
+    /// Rec. 709 luma, as used by grayscale conversion
+    pub luminance: Vec<u32>,
+}
+
+/// Count how many pixels have each value in every channel
+///
+/// Fully transparent pixels carry no visible color and are skipped.
+pub fn compute_histogram(img: &DynamicImage) -> Histogram {
+    let mut histogram = Histogram {
+        red: vec![0; 256],
+        green: vec![0; 256],
+        blue: vec![0; 256],
+        luminance: vec![0; 256],
+    };
+
+    let rgba = img.to_rgba8();
+    for pixel in rgba.pixels().filter(|p| p.0[3] > 0) {
+        let [r, g, b, _] = pixel.0;
+        histogram.red[r as usize] += 1;
+        histogram.green[g as usize] += 1;
+        histogram.blue[b as usize] += 1;
+
+        let luma = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+        histogram.luminance[luma.round() as usize] += 1;
+    }
+
+    histogram
+}
+
+/// Find the darkest and brightest value of one channel's histogram
+///
+/// Up to clip_percent of the pixels are ignored at each end so a few outliers
+/// don't hold back the range. Returns None for an empty histogram.
+pub fn channel_bounds(counts: &[u32], clip_percent: f32) -> Option<(u8, u8)> {
+    let total: u64 = counts.iter().map(|&count| count as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let clip = (total as f64 * clip_percent as f64 / 100.0).floor() as u64;
+    // First value where more than `clip` pixels have been passed
+    fn bound<'a>(mut bins: impl Iterator<Item = (usize, &'a u32)>, clip: u64) -> Option<u8> {
+        let mut seen = 0u64;
+        bins.find(|(_, &count)| {
+            seen += count as u64;
+            seen > clip
+        })
+        .map(|(value, _)| value as u8)
+    }
+
+    let low = bound(counts.iter().enumerate(), clip)?;
+    let high = bound(counts.iter().enumerate().rev(), clip)?;
+    Some((low, high.max(low)))
+}
+
+/// Percentage of pixels clipped in each channel (0.0-100.0)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ChannelClipping {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+}
+
+/// Exposure clipping of an image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClippingReport {
+    /// Pixels at pure 0 per channel
+    pub shadows: ChannelClipping,
+    /// Pixels at pure 255 per channel
+    pub highlights: ChannelClipping,
+    /// Overlay marking clipped highlights in red and clipped shadows in blue
+    pub mask: Option<ImageData>,
+}
+
+/// Measure how many pixels are clipped to 0 or 255 in each channel
+///
+/// Like the histogram, fully transparent pixels are skipped. The report's mask
+/// is left empty.
+pub fn compute_clipping(img: &DynamicImage) -> ClippingReport {
+    let rgba = img.to_rgba8();
+    let mut shadows = [0u64; 3];
+    let mut highlights = [0u64; 3];
+    let mut total = 0u64;
+
+    for pixel in rgba.pixels().filter(|p| p.0[3] > 0) {
+        total += 1;
+        for (channel, &value) in pixel.0.iter().take(3).enumerate() {
+            match value {
+                0 => shadows[channel] += 1,
+                255 => highlights[channel] += 1,
+                _ => {}
+            }
+        }
+    }
+
+    let percentages = |counts: [u64; 3]| {
+        let percent = |count: u64| if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 };
+        ChannelClipping {
+            red: percent(counts[0]),
+            green: percent(counts[1]),
+            blue: percent(counts[2]),
+        }
+    };
+
+    ClippingReport {
+        shadows: percentages(shadows),
+        highlights: percentages(highlights),
+        mask: None,
+    }
+}
+
+/// Build an overlay of the image's clipped regions
+///
+/// Pixels with any channel at 255 are red, pixels with any channel at 0 are
+/// blue and everything else is transparent. Highlights win when a pixel is
+/// clipped at both ends.
+pub fn render_clipping_mask(img: &DynamicImage) -> RgbaImage {
+    let rgba = img.to_rgba8();
+    RgbaImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let pixel = rgba.get_pixel(x, y).0;
+        let channels = &pixel[..3];
+        if pixel[3] == 0 {
+            Rgba([0, 0, 0, 0])
+        } else if channels.contains(&255) {
+            HIGHLIGHT_WARNING
+        } else if channels.contains(&0) {
+            SHADOW_WARNING
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
+
+/// Draw a histogram as a chart of the given size
+///
+/// Luminance is drawn as a filled area with the red, green and blue channels
+/// as lines on top. Axes run along the left and bottom edges with ticks at
+/// 0, 64, 128, 192 and 255.
+pub fn render_histogram(histogram: &Histogram, width: u32, height: u32) -> AppResult<RgbaImage> {
+    let min_size = MARGIN * 2 + 16;
+    if width < min_size || height < min_size {
+        return Err(AppError::InvalidParameters(
+            format!("Histogram chart must be at least {}x{} pixels", min_size, min_size)
+        ));
+    }
+
+    let mut canvas = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+    let left = MARGIN as f32;
+    let bottom = (height - MARGIN) as f32;
+    let plot_width = (width - MARGIN * 2) as f32;
+    let plot_height = (height - MARGIN * 2) as f32;
+
+    let peak = [&histogram.red, &histogram.green, &histogram.blue, &histogram.luminance]
+        .iter()
+        .flat_map(|channel| channel.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+
+    let x_for = |value: usize| left + value as f32 / 255.0 * plot_width;
+    let y_for = |count: u32| bottom - count as f32 / peak * plot_height;
+
+    // Luminance as a filled area, one column per plot pixel
+    for column in 0..(width - MARGIN * 2) {
+        let value = (column as f32 / plot_width * 255.0).round() as usize;
+        let top = y_for(histogram.luminance[value.min(255)]).round() as i32;
+        let column_height = (bottom as i32 - top).max(0) as u32;
+        if column_height > 0 {
+            draw_filled_rect_mut(
+                &mut canvas,
+                Rect::at((MARGIN + column) as i32, top).of_size(1, column_height),
+                LUMINANCE_FILL,
+            );
+        }
+    }
+
+    // Color channels as lines
+    let channels = [
+        (&histogram.red, Rgba([230, 70, 70, 255])),
+        (&histogram.green, Rgba([70, 200, 70, 255])),
+        (&histogram.blue, Rgba([80, 120, 240, 255])),
+    ];
+    for (counts, color) in channels {
+        for value in 1..256 {
+            draw_line_segment_mut(
+                &mut canvas,
+                (x_for(value - 1), y_for(counts[value - 1])),
+                (x_for(value), y_for(counts[value])),
+                color,
+            );
+        }
+    }
+
+    // Axes and ticks
+    draw_line_segment_mut(&mut canvas, (left, MARGIN as f32), (left, bottom), AXIS);
+    draw_line_segment_mut(&mut canvas, (left, bottom), (left + plot_width, bottom), AXIS);
+    for tick in [0, 64, 128, 192, 255] {
+        let x = x_for(tick);
+        draw_line_segment_mut(&mut canvas, (x, bottom), (x, bottom + TICK_LENGTH as f32), AXIS);
+    }
+
+    Ok(canvas)
+}
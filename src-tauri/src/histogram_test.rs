@@ -0,0 +1,359 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba};
+
+    /// Helper function to create a low-contrast test image
+    /// Every channel only spans the narrow 100-140 range
+    fn create_low_contrast_image(width: u32, height: u32) -> ImageData {
+        let img = ImageBuffer::from_fn(width, height, |x, _y| {
+            let v = 100 + (x * 40 / width) as u8;
+            Rgba([v, v, v, 200])
+        });
+
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width,
+            height,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back into RGBA pixels
+    fn decode_rgba(image_data: &ImageData) -> image::RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    /// Spread between the darkest and brightest red values
+    fn red_spread(img: &image::RgbaImage) -> u8 {
+        let min = img.pixels().map(|p| p.0[0]).min().unwrap();
+        let max = img.pixels().map(|p| p.0[0]).max().unwrap();
+        max - min
+    }
+
+    #[tokio::test]
+    async fn test_equalize_widens_histogram_spread() {
+        let image_data = create_low_contrast_image(64, 16);
+        let original_spread = red_spread(&decode_rgba(&image_data));
+
+        let result = crate::equalize_histogram_image(image_data, None, limiter()).await;
+
+        assert!(result.is_ok());
+        let equalized = decode_rgba(&result.unwrap());
+        assert!(red_spread(&equalized) > original_spread * 3);
+
+        // Alpha must be preserved
+        assert!(equalized.pixels().all(|p| p.0[3] == 200));
+    }
+
+    #[tokio::test]
+    async fn test_equalize_per_channel_widens_histogram_spread() {
+        let image_data = create_low_contrast_image(64, 16);
+        let original_spread = red_spread(&decode_rgba(&image_data));
+
+        let result = crate::equalize_histogram_image(image_data, Some(true), limiter()).await;
+
+        assert!(result.is_ok());
+        let equalized = result.unwrap();
+        assert_eq!(equalized.width, 64);
+        assert_eq!(equalized.height, 16);
+        assert!(red_spread(&decode_rgba(&equalized)) > original_spread * 3);
+    }
+
+    #[tokio::test]
+    async fn test_auto_levels_stretches_to_full_range() {
+        // Red spans 50-150, green 60-140 and blue 70-130
+        let img = ImageBuffer::from_fn(101, 4, |x, _y| {
+            Rgba([(50 + x) as u8, (60 + x * 4 / 5) as u8, (70 + x * 3 / 5) as u8, 255])
+        });
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "washed_out.png".to_string(),
+            width: 101,
+            height: 4,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let result = crate::auto_levels(image_data.clone(), None, limiter()).await.unwrap();
+        let stretched = decode_rgba(&result);
+
+        for channel in 0..3 {
+            let min = stretched.pixels().map(|p| p.0[channel]).min().unwrap();
+            let max = stretched.pixels().map(|p| p.0[channel]).max().unwrap();
+            assert!(min <= 2, "channel {} min {}", channel, min);
+            assert!(max >= 253, "channel {} max {}", channel, max);
+        }
+        assert_eq!(result.format, ImageFormat::PNG);
+
+        // Clipping saturates the outermost columns
+        let clipped = decode_rgba(&crate::auto_levels(image_data.clone(), Some(5.0), limiter()).await.unwrap());
+        assert_eq!(clipped.get_pixel(2, 0).0[0], 0);
+        assert_eq!(clipped.get_pixel(98, 0).0[0], 255);
+
+        assert!(crate::auto_levels(image_data, Some(50.0), limiter()).await.is_err());
+    }
+
+    #[test]
+    fn test_channel_bounds_skips_outliers() {
+        let mut counts = vec![0u32; 256];
+        counts[0] = 1;
+        counts[100] = 98;
+        counts[255] = 1;
+
+        assert_eq!(crate::histogram::channel_bounds(&counts, 0.0), Some((0, 255)));
+        assert_eq!(crate::histogram::channel_bounds(&counts, 1.0), Some((100, 100)));
+        assert_eq!(crate::histogram::channel_bounds(&[0; 256], 1.0), None);
+    }
+
+    #[test]
+    fn test_compute_histogram_counts_channels() {
+        let img = image::DynamicImage::ImageRgba8(ImageBuffer::from_fn(4, 1, |x, _y| match x {
+            0 => Rgba([255, 0, 0, 255]),
+            1 => Rgba([255, 255, 255, 255]),
+            2 => Rgba([0, 0, 0, 255]),
+            _ => Rgba([10, 10, 10, 0]),
+        }));
+
+        let histogram = crate::histogram::compute_histogram(&img);
+
+        // The fully transparent pixel is ignored
+        assert_eq!(histogram.red[255], 2);
+        assert_eq!(histogram.red[0], 1);
+        assert_eq!(histogram.green[0], 2);
+        assert_eq!(histogram.luminance[255], 1);
+        assert_eq!(histogram.luminance[0], 1);
+        assert_eq!(histogram.luminance[54], 1);
+        assert_eq!(histogram.luminance.iter().sum::<u32>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_compute_histogram_command_on_solid_image() {
+        let mut buffer = Vec::new();
+        image::RgbImage::from_pixel(12, 8, image::Rgb([128, 64, 32]))
+            .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "solid.png".to_string(),
+            width: 12,
+            height: 8,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let histogram = crate::compute_histogram(image_data).await.unwrap();
+
+        // Luma of (128, 64, 32) is 75.3
+        for (channel, bin) in [
+            (&histogram.red, 128),
+            (&histogram.green, 64),
+            (&histogram.blue, 32),
+            (&histogram.luminance, 75),
+        ] {
+            assert_eq!(channel.len(), 256);
+            assert_eq!(channel[bin], 12 * 8);
+            assert_eq!(channel.iter().filter(|&&count| count > 0).count(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_histogram_returns_chart_of_requested_size() {
+        let result = crate::render_histogram(create_low_contrast_image(64, 16), Some(300), Some(150)).await;
+
+        assert!(result.is_ok());
+        let chart = result.unwrap();
+        assert_eq!(chart.width, 300);
+        assert_eq!(chart.height, 150);
+        assert_eq!(chart.format, ImageFormat::PNG);
+        assert_eq!(chart.path, "test_histogram.png");
+
+        // Not blank: the plot contains several distinct colors
+        let pixels = decode_rgba(&chart);
+        let mut colors: Vec<[u8; 4]> = pixels.pixels().map(|p| p.0).collect();
+        colors.sort();
+        colors.dedup();
+        assert!(colors.len() > 3);
+    }
+
+    #[tokio::test]
+    async fn test_render_histogram_rejects_tiny_canvas() {
+        let result = crate::render_histogram(create_low_contrast_image(8, 8), Some(10), Some(10)).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detect_clipping_reports_overexposure() {
+        // Left three quarters blown out to white, the rest a dark gray with a black stripe
+        let img: image::RgbaImage = ImageBuffer::from_fn(40, 10, |x, y| {
+            if x < 30 {
+                Rgba([255, 255, 255, 255])
+            } else if y == 0 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([40, 40, 40, 255])
+            }
+        });
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "photo.jpg".to_string(),
+            width: 40,
+            height: 10,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let report = crate::detect_clipping(image_data, Some(true)).await.unwrap();
+
+        assert_eq!(report.highlights.red, 75.0);
+        assert_eq!(report.highlights.green, 75.0);
+        assert_eq!(report.highlights.blue, 75.0);
+        assert_eq!(report.shadows.red, 2.5);
+
+        let mask_data = report.mask.unwrap();
+        assert_eq!(mask_data.path, "photo_clipping.png");
+        let mask = decode_rgba(&mask_data);
+        assert_eq!(mask.get_pixel(0, 5).0, [255, 0, 0, 255]);
+        assert_eq!(mask.get_pixel(35, 0).0, [0, 96, 255, 255]);
+        assert_eq!(mask.get_pixel(35, 5).0[3], 0);
+    }
+
+    #[tokio::test]
+    async fn test_detect_clipping_without_mask() {
+        let report = crate::detect_clipping(create_low_contrast_image(16, 16), None).await.unwrap();
+
+        assert!(report.mask.is_none());
+        assert_eq!(report.highlights, crate::ChannelClipping::default());
+        assert_eq!(report.shadows, crate::ChannelClipping::default());
+    }
+
+    #[tokio::test]
+    async fn test_posterize_two_levels_collapses_channels() {
+        let img = ImageBuffer::from_fn(64, 16, |x, y| {
+            Rgba([(x * 4) as u8, (y * 16) as u8, 255 - (x * 4) as u8, (x * 4) as u8])
+        });
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png).unwrap();
+        let image_data = ImageData {
+            path: "gradient.png".to_string(),
+            width: 64,
+            height: 16,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        };
+
+        let result = crate::posterize_image(image_data.clone(), 2).await.unwrap();
+
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        let pixels = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        for channel in 0..3 {
+            let mut values: Vec<u8> = pixels.pixels().map(|p| p.0[channel]).collect();
+            values.sort_unstable();
+            values.dedup();
+            assert_eq!(values, vec![0, 255], "channel {}", channel);
+        }
+
+        // Alpha is untouched
+        for (original, posterized) in img.pixels().zip(pixels.pixels()) {
+            assert_eq!(original.0[3], posterized.0[3]);
+        }
+
+        // 256 levels keeps every value
+        let identity = crate::posterize_image(image_data.clone(), 256).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&identity.data).unwrap();
+        assert_eq!(image::load_from_memory(&bytes).unwrap().to_rgba8(), img);
+
+        assert!(crate::posterize_image(image_data.clone(), 1).await.is_err());
+        assert!(crate::posterize_image(image_data, 257).await.is_err());
+    }
+
+    /// Helper function to encode a grayscale image as PNG ImageData
+    fn encode_gray(img: &image::GrayImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png).unwrap();
+
+        ImageData {
+            path: "scan.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode ImageData back into grayscale pixels
+    fn decode_gray(image_data: &ImageData) -> image::GrayImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_luma8()
+    }
+
+    #[tokio::test]
+    async fn test_threshold_gradient_splits_at_threshold() {
+        let gradient = image::GrayImage::from_fn(256, 8, |x, _y| image::Luma([x as u8]));
+
+        let result = crate::threshold_image(encode_gray(&gradient), 100, None).await.unwrap();
+        assert_eq!((result.width, result.height), (256, 8));
+
+        let pixels = decode_gray(&result);
+        let mut values: Vec<u8> = pixels.pixels().map(|p| p.0[0]).collect();
+        values.sort_unstable();
+        values.dedup();
+        assert_eq!(values, vec![0, 255]);
+
+        for (x, _y, pixel) in pixels.enumerate_pixels() {
+            let expected = if x >= 100 { 255 } else { 0 };
+            assert_eq!(pixel.0[0], expected, "column {}", x);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_threshold_adaptive_handles_uneven_lighting() {
+        // A dim left half and a bright right half, each with a darker mark
+        let mut page = image::GrayImage::from_fn(128, 64, |x, _y| image::Luma([if x < 64 { 80 } else { 230 }]));
+        for y in 29..35 {
+            for x in 29..35 {
+                page.put_pixel(x, y, image::Luma([30]));
+                page.put_pixel(x + 64, y, image::Luma([180]));
+            }
+        }
+
+        // A fixed threshold loses the whole dim half and the bright mark
+        let fixed = decode_gray(&crate::threshold_image(encode_gray(&page), 128, None).await.unwrap());
+        assert_eq!(fixed.get_pixel(10, 10).0[0], 0);
+        assert_eq!(fixed.get_pixel(96, 32).0[0], 255);
+
+        let adaptive = decode_gray(&crate::threshold_image(encode_gray(&page), 128, Some(true)).await.unwrap());
+        assert_eq!(adaptive.dimensions(), (128, 64));
+        assert_eq!(adaptive.get_pixel(32, 32).0[0], 0);
+        assert_eq!(adaptive.get_pixel(96, 32).0[0], 0);
+        assert_eq!(adaptive.get_pixel(10, 10).0[0], 255);
+        assert_eq!(adaptive.get_pixel(110, 10).0[0], 255);
+    }
+}
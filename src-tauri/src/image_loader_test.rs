@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
     use crate::{load_image, ImageFormat};
+    use crate::test_state::{limiter, thumbnails};
+    use base64::{Engine as _, engine::general_purpose};
     use std::fs;
     use std::path::PathBuf;
 
@@ -45,7 +47,7 @@ mod tests {
         let test_path = create_test_png();
         let path_str = test_path.to_str().unwrap().to_string();
         
-        let result = load_image(path_str).await;
+        let result = load_image(path_str, None, None).await;
         assert!(result.is_ok(), "Failed to load PNG: {:?}", result.err());
         
         let image_data = result.unwrap();
@@ -64,7 +66,7 @@ mod tests {
         let test_path = create_test_jpeg();
         let path_str = test_path.to_str().unwrap().to_string();
         
-        let result = load_image(path_str).await;
+        let result = load_image(path_str, None, None).await;
         assert!(result.is_ok(), "Failed to load JPEG: {:?}", result.err());
         
         let image_data = result.unwrap();
@@ -78,13 +80,86 @@ mod tests {
         fs::remove_file(test_path).ok();
     }
 
+    /// Helper function to write a PNG tagged with the Display-P3 profile
+    fn create_display_p3_png(name: &str, color: [u8; 3]) -> PathBuf {
+        use image::ImageEncoder;
+
+        let path = std::env::temp_dir().join(name);
+        let img = image::RgbImage::from_pixel(4, 4, image::Rgb(color));
+        let mut encoder = image::codecs::png::PngEncoder::new(fs::File::create(&path).unwrap());
+        encoder
+            .set_icc_profile(moxcms::ColorProfile::new_display_p3().encode().unwrap())
+            .unwrap();
+        encoder
+            .write_image(img.as_raw(), 4, 4, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        path
+    }
+
+    fn first_pixel(data: &str) -> image::Rgba<u8> {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let bytes = general_purpose::STANDARD.decode(data).unwrap();
+        *image::load_from_memory(&bytes).unwrap().to_rgba8().get_pixel(0, 0)
+    }
+
+    #[tokio::test]
+    async fn test_display_p3_converted_to_srgb_on_load() {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let raw = [200, 100, 80];
+        let path = create_display_p3_png("test_display_p3.png", raw);
+        let path_str = path.to_string_lossy().to_string();
+
+        let loaded = load_image(path_str.clone(), None, None).await.unwrap();
+        let display = loaded.display_data.as_deref().expect("P3 images get an sRGB display copy");
+        let pixel = first_pixel(display);
+        // P3 has a wider gamut, so the same color needs more saturated sRGB values
+        assert!(pixel[0] > raw[0], "red should increase: {:?}", pixel);
+        assert!(pixel[1] < raw[1], "green should decrease: {:?}", pixel);
+        assert!(pixel[2] < raw[2], "blue should decrease: {:?}", pixel);
+        assert_eq!(crate::extract_icc_profile(&general_purpose::STANDARD.decode(display).unwrap()), None);
+
+        // The data itself is still the file, profile and all
+        let bytes = general_purpose::STANDARD.decode(&loaded.data).unwrap();
+        assert_eq!(bytes, fs::read(&path).unwrap());
+        assert!(crate::extract_icc_profile(&bytes).is_some());
+
+        let original = load_image(path_str, None, Some(false)).await.unwrap();
+        assert_eq!(original.display_data, None);
+        assert_eq!(first_pixel(&original.data).0, [raw[0], raw[1], raw[2], 255]);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_srgb_profile_is_left_untouched_on_load() {
+        use base64::{Engine as _, engine::general_purpose};
+        use image::ImageEncoder;
+
+        let path = std::env::temp_dir().join("test_srgb_profile.png");
+        let mut encoder = image::codecs::png::PngEncoder::new(fs::File::create(&path).unwrap());
+        encoder
+            .set_icc_profile(moxcms::ColorProfile::new_srgb().encode().unwrap())
+            .unwrap();
+        encoder
+            .write_image(&[200, 100, 80], 1, 1, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        let loaded = load_image(path.to_string_lossy().to_string(), None, None).await.unwrap();
+        assert_eq!(general_purpose::STANDARD.decode(&loaded.data).unwrap(), fs::read(&path).unwrap());
+        assert_eq!(loaded.display_data, None);
+
+        fs::remove_file(path).ok();
+    }
+
     #[tokio::test]
     async fn test_load_nonexistent_file() {
-        let result = load_image("/nonexistent/path/image.png".to_string()).await;
+        let result = load_image("/nonexistent/path/image.png".to_string(), None, None).await;
         assert!(result.is_err(), "Should fail for nonexistent file");
         
         let error_msg = result.unwrap_err();
-        assert!(error_msg.contains("File not found"), "Error should mention file not found");
+        assert!(error_msg.message.contains("File not found"), "Error should mention file not found");
     }
 
     #[tokio::test]
@@ -95,19 +170,86 @@ mod tests {
         // Create a file with invalid image data
         fs::write(&test_path, b"This is not a valid image").unwrap();
         
-        let result = load_image(test_path.to_str().unwrap().to_string()).await;
+        let result = load_image(test_path.to_str().unwrap().to_string(), None, None).await;
         assert!(result.is_err(), "Should fail for invalid image data");
         
         // Cleanup
         fs::remove_file(test_path).ok();
     }
 
+    #[tokio::test]
+    async fn test_format_detected_from_content_not_extension() {
+        let test_path = std::env::temp_dir().join("test_png_named_as.jpg");
+        let img = image::RgbaImage::from_pixel(10, 10, image::Rgba([0u8, 128, 255, 128]));
+        img.save_with_format(&test_path, image::ImageFormat::Png).unwrap();
+
+        let image_data = load_image(test_path.to_str().unwrap().to_string(), None, None).await.unwrap();
+        assert_eq!(image_data.format, ImageFormat::PNG);
+        assert!(image_data.has_alpha);
+
+        // The thumbnail is decoded from the file, not a .jpg decoder's error
+        let preview = crate::quick_look(
+            test_path.to_str().unwrap().to_string(),
+            Some(8),
+            thumbnails(),
+            limiter(),
+        ).await.unwrap();
+        assert_eq!(preview.probe.format, ImageFormat::PNG);
+        assert_eq!((preview.thumbnail.width, preview.thumbnail.height), (8, 8));
+
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_load_truncated_png_is_corrupt() {
+        let test_path = std::env::temp_dir().join("test_truncated_image.png");
+        let img = image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * 4) as u8, (y * 4) as u8, 128]));
+        img.save(&test_path).unwrap();
+
+        // Keep the signature and header but cut the pixel data short, like an interrupted download
+        let bytes = fs::read(&test_path).unwrap();
+        fs::write(&test_path, &bytes[..bytes.len() / 2]).unwrap();
+
+        let error = load_image(test_path.to_str().unwrap().to_string(), None, None).await.unwrap_err();
+        assert_eq!(error.code, "CORRUPT_IMAGE");
+        assert!(error.message.contains("truncated or corrupt"));
+
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_every_loader_reports_corrupt_files() {
+        let truncated_path = std::env::temp_dir().join("test_truncated_everywhere.png");
+        let img = image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * 4) as u8, (y * 4) as u8, 128]));
+        img.save(&truncated_path).unwrap();
+        let bytes = fs::read(&truncated_path).unwrap();
+        fs::write(&truncated_path, &bytes[..bytes.len() / 2]).unwrap();
+        let truncated = truncated_path.to_str().unwrap().to_string();
+
+        // The header is intact, so only the loaders that decode pixels fail
+        let error = crate::load_image_downscaled(truncated.clone(), 16).await.unwrap_err();
+        assert_eq!(error.code, "CORRUPT_IMAGE");
+        let error = crate::quick_look(truncated, Some(16), thumbnails(), limiter()).await.unwrap_err();
+        assert_eq!(error.code, "CORRUPT_IMAGE");
+
+        // A damaged header fails even the header-only probe
+        let garbled_path = std::env::temp_dir().join("test_garbled_header.png");
+        let mut garbled = bytes[..8].to_vec();
+        garbled.extend([0xAB; 64]);
+        fs::write(&garbled_path, garbled).unwrap();
+        let error = crate::probe_image(garbled_path.to_str().unwrap().to_string()).await.unwrap_err();
+        assert_eq!(error.code, "CORRUPT_IMAGE");
+
+        fs::remove_file(truncated_path).ok();
+        fs::remove_file(garbled_path).ok();
+    }
+
     #[tokio::test]
     async fn test_base64_encoding() {
         let test_path = create_test_png();
         let path_str = test_path.to_str().unwrap().to_string();
         
-        let result = load_image(path_str).await;
+        let result = load_image(path_str, None, None).await;
         assert!(result.is_ok());
         
         let image_data = result.unwrap();
@@ -120,4 +262,198 @@ mod tests {
         // Cleanup
         fs::remove_file(test_path).ok();
     }
+
+    #[tokio::test]
+    async fn test_load_image_capped_downscales_to_budget() {
+        let test_path = std::env::temp_dir().join("test_image_capped_20mp.png");
+        image::GrayImage::from_pixel(5000, 4000, image::Luma([90u8]))
+            .save(&test_path)
+            .unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let capped = crate::load_image_capped(path_str, 5.0).await.unwrap();
+
+        assert!(capped.downscaled);
+        assert_eq!((capped.original_width, capped.original_height), (5000, 4000));
+        assert!(capped.image.width as u64 * capped.image.height as u64 <= 5_000_000);
+        assert_eq!((capped.image.width, capped.image.height), (2500, 2000));
+        assert_eq!(capped.image.format, ImageFormat::PNG);
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_load_image_capped_keeps_small_images() {
+        let test_path = std::env::temp_dir().join("test_image_capped_small.jpg");
+        image::RgbImage::from_pixel(10, 10, image::Rgb([0u8, 0u8, 255u8]))
+            .save(&test_path)
+            .unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let capped = crate::load_image_capped(path_str.clone(), 5.0).await.unwrap();
+        let plain = load_image(path_str.clone(), None, None).await.unwrap();
+
+        assert!(!capped.downscaled);
+        assert_eq!((capped.original_width, capped.original_height), (10, 10));
+        assert_eq!(capped.image.data, plain.data);
+
+        assert!(crate::load_image_capped(path_str, 0.0).await.is_err());
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_load_image_downscaled_limits_longest_side() {
+        let test_path = std::env::temp_dir().join("test_image_downscaled_large.tiff");
+        image::GrayImage::from_fn(6000, 3000, |x, _| image::Luma([(x % 256) as u8]))
+            .save(&test_path)
+            .unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let preview = crate::load_image_downscaled(path_str, 1024).await.unwrap();
+
+        assert!(preview.downscaled);
+        assert_eq!((preview.original_width, preview.original_height), (6000, 3000));
+        assert_eq!((preview.image.width, preview.image.height), (1024, 512));
+        assert_eq!(preview.image.format, ImageFormat::TIFF);
+
+        // The payload is bounded by the preview size, not the original
+        assert!(preview.image.data.len() < 1024 * 512 * 2);
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_load_image_downscaled_keeps_small_images() {
+        let test_path = std::env::temp_dir().join("test_image_downscaled_small.png");
+        image::RgbImage::from_pixel(300, 200, image::Rgb([0u8, 128u8, 255u8]))
+            .save(&test_path)
+            .unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let preview = crate::load_image_downscaled(path_str.clone(), 300).await.unwrap();
+
+        assert!(!preview.downscaled);
+        assert_eq!((preview.image.width, preview.image.height), (300, 200));
+        assert_eq!((preview.original_width, preview.original_height), (300, 200));
+
+        assert!(crate::load_image_downscaled(path_str, 0).await.is_err());
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_probe_image_matches_load_image() {
+        let test_path = std::env::temp_dir().join("test_probe_image.png");
+        image::RgbImage::from_pixel(37, 21, image::Rgb([10, 200, 30])).save(&test_path).unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+        
+        let probe = crate::probe_image(path_str.clone()).await.unwrap();
+        let loaded = load_image(path_str.clone(), None, None).await.unwrap();
+        
+        assert_eq!((probe.width, probe.height), (loaded.width, loaded.height));
+        assert_eq!((probe.width, probe.height), (37, 21));
+        assert_eq!(probe.format, ImageFormat::PNG);
+        assert_eq!(probe.file_size, fs::metadata(&test_path).unwrap().len());
+        assert_eq!(probe.path, path_str);
+        
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_probe_image_errors() {
+        assert!(crate::probe_image("/nonexistent/probe.png".to_string()).await.is_err());
+        
+        // A file named like an image that isn't one
+        let bogus = std::env::temp_dir().join("test_probe_bogus.png");
+        fs::write(&bogus, b"not an image").unwrap();
+        assert!(crate::probe_image(bogus.to_str().unwrap().to_string()).await.is_err());
+        fs::remove_file(bogus).ok();
+    }
+
+    /// Helper function to write a blue JPEG photo whose EXIF holds a capture date
+    /// and a red 200x100 thumbnail
+    fn write_jpeg_with_exif_thumbnail(path: &std::path::Path) {
+        use exif::{Field, In, Tag, Value};
+
+        let mut thumbnail = Vec::new();
+        image::RgbImage::from_pixel(200, 100, image::Rgb([220, 0, 0]))
+            .write_to(&mut std::io::Cursor::new(&mut thumbnail), image::ImageFormat::Jpeg)
+            .unwrap();
+
+        let date = Field {
+            tag: Tag::DateTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"2023:07:14 09:30:00".to_vec()]),
+        };
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&date);
+        writer.set_jpeg(&thumbnail, In::THUMBNAIL);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        let mut jpeg = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut jpeg);
+        image::ImageEncoder::set_exif_metadata(&mut encoder, tiff.into_inner()).unwrap();
+        image::RgbImage::from_pixel(800, 400, image::Rgb([0, 0, 220]))
+            .write_with_encoder(encoder)
+            .unwrap();
+
+        fs::write(path, jpeg).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_quick_look_uses_exif_thumbnail() {
+        let test_path = std::env::temp_dir().join("test_quick_look_exif.jpg");
+        write_jpeg_with_exif_thumbnail(&test_path);
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let preview = crate::quick_look(path_str, None, thumbnails(), limiter()).await.unwrap();
+
+        assert_eq!((preview.probe.width, preview.probe.height), (800, 400));
+        assert_eq!(preview.probe.format, ImageFormat::JPEG);
+        assert_eq!(preview.probe.file_size, fs::metadata(&test_path).unwrap().len());
+        assert_eq!(preview.capture_date.as_deref(), Some("2023-07-14"));
+
+        // The red EXIF thumbnail was used instead of the blue photo
+        assert_eq!((preview.thumbnail.width, preview.thumbnail.height), (160, 80));
+        assert_eq!(preview.thumbnail.format, ImageFormat::JPEG);
+        let bytes = general_purpose::STANDARD.decode(&preview.thumbnail.data).unwrap();
+        let pixel = *image::load_from_memory(&bytes).unwrap().to_rgb8().get_pixel(80, 40);
+        assert!(pixel.0[0] > 180 && pixel.0[2] < 60, "{:?}", pixel);
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_quick_look_decodes_images_without_exif() {
+        let test_path = std::env::temp_dir().join("test_quick_look_plain.png");
+        image::RgbaImage::from_pixel(300, 600, image::Rgba([0, 200, 0, 128]))
+            .save(&test_path)
+            .unwrap();
+        let path_str = test_path.to_str().unwrap().to_string();
+
+        let preview = crate::quick_look(path_str.clone(), Some(64), thumbnails(), limiter()).await.unwrap();
+
+        assert_eq!((preview.probe.width, preview.probe.height), (300, 600));
+        assert_eq!((preview.thumbnail.width, preview.thumbnail.height), (32, 64));
+        assert_eq!(preview.thumbnail.format, ImageFormat::PNG);
+        assert!(preview.thumbnail.has_alpha);
+        assert!(!preview.thumbnail.data.is_empty());
+        assert_eq!(preview.capture_date, None);
+
+        // Small images aren't enlarged
+        let full_size = crate::quick_look(path_str.clone(), Some(1000), thumbnails(), limiter()).await.unwrap();
+        assert_eq!((full_size.thumbnail.width, full_size.thumbnail.height), (300, 600));
+
+        assert!(crate::quick_look(path_str, Some(0), thumbnails(), limiter()).await.is_err());
+
+        // Cleanup
+        fs::remove_file(test_path).ok();
+    }
 }
@@ -10,6 +10,7 @@
 #[cfg(test)]
 mod tests {
     use crate::types::{ImageData, ImageFormat, ConversionOptions};
+    use crate::test_state::limiter;
     use image::{DynamicImage, RgbaImage};
     use base64::{Engine as _, engine::general_purpose};
 
@@ -32,6 +33,7 @@ mod tests {
             format: ImageFormat::PNG,
             data: base64_data,
             has_alpha: true,
+            display_data: None,
         }
     }
 
@@ -44,6 +46,7 @@ mod tests {
             format: img.format.clone(),
             data: img.data.clone(),
             has_alpha: img.has_alpha,
+            display_data: None,
         }
     }
 
@@ -64,7 +67,7 @@ mod tests {
         let original_snapshot = clone_image_data(&original);
         
         // Perform resize operation
-        let result = crate::resize_image(original.clone(), 50, 50, false).await;
+        let result = crate::resize_image(original.clone(), 50, 50, false, None, None, limiter()).await;
         
         // Verify operation succeeded
         assert!(result.is_ok(), "Resize operation should succeed");
@@ -95,7 +98,9 @@ mod tests {
         let result = crate::convert_format(
             original.clone(),
             "JPEG".to_string(),
-            Some(ConversionOptions { quality: Some(90) }),
+            Some(ConversionOptions { quality: Some(90), ..Default::default() }),
+            None,
+            limiter(),
         ).await;
         
         // Verify operation succeeded
@@ -123,7 +128,7 @@ mod tests {
         let original_snapshot = clone_image_data(&original);
         
         // Perform crop operation
-        let result = crate::crop_image(original.clone(), 10, 10, 50, 50).await;
+        let result = crate::crop_image(original.clone(), 10, 10, 50, 50, None).await;
         
         // Verify operation succeeded
         assert!(result.is_ok(), "Crop operation should succeed");
@@ -151,7 +156,7 @@ mod tests {
         let original_snapshot = clone_image_data(&original);
         
         // Perform set background operation
-        let result = crate::set_background(original.clone(), 255, 255, 255).await;
+        let result = crate::set_background(original.clone(), 255, 255, 255, None).await;
         
         // Verify operation succeeded
         assert!(result.is_ok(), "Set background operation should succeed");
@@ -178,12 +183,14 @@ mod tests {
         let original_snapshot = clone_image_data(&original);
         
         // Perform multiple operations in sequence
-        let resized = crate::resize_image(original.clone(), 80, 80, false).await.unwrap();
-        let cropped = crate::crop_image(resized, 10, 10, 50, 50).await.unwrap();
+        let resized = crate::resize_image(original.clone(), 80, 80, false, None, None, limiter()).await.unwrap();
+        let cropped = crate::crop_image(resized, 10, 10, 50, 50, None).await.unwrap();
         let converted = crate::convert_format(
             cropped,
             "JPEG".to_string(),
-            Some(ConversionOptions { quality: Some(90) }),
+            Some(ConversionOptions { quality: Some(90), ..Default::default() }),
+            None,
+            limiter(),
         ).await.unwrap();
         
         // Verify original was not mutated through the chain
@@ -1,8 +1,28 @@
 // Module declarations
 pub mod types;
 pub mod error;
+pub mod color_management;
 pub mod favorites;
+pub mod gallery;
+pub mod npy;
+pub mod limiter;
+pub mod pyramid;
+pub mod pipeline;
+pub mod quality;
+pub mod histogram;
+pub mod camera;
+pub mod naming;
+pub mod dpi;
+pub mod cover;
+pub mod composition;
+pub mod palette;
+pub mod phash;
+pub mod deskew;
+pub mod thumbnail_cache;
+pub mod tiff_metadata;
 
+#[cfg(test)]
+mod test_state;
 #[cfg(test)]
 mod error_test;
 
@@ -30,18 +50,87 @@ mod immutability_test;
 #[cfg(test)]
 mod favorites_test;
 
+#[cfg(test)]
+mod histogram_test;
+
+#[cfg(test)]
+mod pyramid_test;
+
+#[cfg(test)]
+mod gallery_test;
+
+#[cfg(test)]
+mod timeline_test;
+
+#[cfg(test)]
+mod npy_test;
+
+#[cfg(test)]
+mod limiter_test;
+#[cfg(test)]
+mod text_test;
+#[cfg(test)]
+mod rotate_test;
+#[cfg(test)]
+mod pipeline_test;
+#[cfg(test)]
+mod grayscale_test;
+#[cfg(test)]
+mod quality_test;
+#[cfg(test)]
+mod sticker_test;
+#[cfg(test)]
+mod alpha_test;
+#[cfg(test)]
+mod watermark_test;
+#[cfg(test)]
+mod selection_test;
+#[cfg(test)]
+mod naming_test;
+#[cfg(test)]
+mod dpi_test;
+#[cfg(test)]
+mod profile_test;
+#[cfg(test)]
+mod cover_test;
+#[cfg(test)]
+mod composition_test;
+#[cfg(test)]
+mod palette_test;
+#[cfg(test)]
+mod phash_test;
+#[cfg(test)]
+mod clipboard_test;
+#[cfg(test)]
+mod deskew_test;
+#[cfg(test)]
+mod thumbnail_cache_test;
+
 // Re-export commonly used types
-pub use types::{ImageData, ImageFormat, ConversionOptions, RGBColor, StickerData, TextData};
-pub use error::{AppError, AppResult};
+pub use types::{ImageData, CappedImageData, ImageProbe, QuickLook, ImageFormat, ConversionOptions, RGBColor, StickerData, TextData, FormatCapabilities, ResampleFilter, ResampleOptions, AlphaMode, MetadataPolicy, JpegSubsampling, TiffCompression, TextColorSuggestion, SrcsetEntry, GifFrame, ComparisonOrientation};
+pub use error::{AppError, AppResult, CommandError};
 pub use favorites::{FavoriteImage, FavoritesConfig};
+pub use pyramid::PyramidLevel;
+pub use pipeline::{PipelineStep, MacroFileResult, SidecarImage};
+pub use composition::GridType;
+pub use naming::CollisionStrategy;
+pub use quality::{QualityMetrics, ImageComparison, BandingReport, ChannelBitDepth};
+pub use histogram::{Histogram, ChannelClipping, ClippingReport};
+pub use camera::CameraInfo;
+pub use dpi::PrintSize;
 
 use base64::{Engine as _, engine::general_purpose};
 use image::{DynamicImage, GenericImageView, ImageReader, Rgba};
 // Note: imageproc is available for future use if needed
 use std::fs;
+use std::io::Read;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use limiter::{CancelRegistry, OperationLimiter};
+use thumbnail_cache::ThumbnailCache;
 
 // AppState to store opened image sources for macOS "Open With" functionality
 #[derive(Default)]
@@ -100,8 +189,22 @@ fn on_image_source_listener_ready(app: AppHandle) {
 /// - format: detected image format
 /// - data: Base64 encoded image data
 /// - hasAlpha: whether the image has transparency
+/// - displayData: Base64 PNG of the pixels converted to sRGB, if needed
+/// 
+/// If alpha_mode is "premultiplied", the file is treated as having premultiplied
+/// alpha and converted to straight alpha (re-encoded in the same format).
+/// 
+/// Images with an embedded color profile other than sRGB (e.g. Display-P3
+/// photos) also get displayData: their pixels converted to sRGB, so they
+/// display like in a color-managed app. data keeps the file's own bytes, with
+/// their profile and EXIF, so saving never degrades the original. Pass
+/// convert_to_srgb = false to skip the conversion.
 #[tauri::command]
-async fn load_image(path: String) -> Result<ImageData, String> {
+async fn load_image(
+    path: String,
+    alpha_mode: Option<AlphaMode>,
+    convert_to_srgb: Option<bool>,
+) -> Result<ImageData, CommandError> {
     // Decode URL encoding if present (for macOS "Open With" functionality)
     let decoded_path = match urlencoding::decode(&path) {
         Ok(decoded) => {
@@ -145,9 +248,11 @@ async fn load_image(path: String) -> Result<ImageData, String> {
     
     // Load image using the image crate with the decoded path
     let img = ImageReader::open(&decoded_path)
+        .map_err(AppError::IoError)?
+        .with_guessed_format()
         .map_err(AppError::IoError)?
         .decode()
-        .map_err(AppError::ImageError)?;
+        .map_err(|e| classify_decode_error(&decoded_path, e))?;
     
     // Extract metadata
     let (width, height) = img.dimensions();
@@ -156,21 +261,123 @@ async fn load_image(path: String) -> Result<ImageData, String> {
     // Detect format using the decoded path
     let format = detect_image_format(&decoded_path, &extension)?;
     
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&file_bytes);
+    // Premultiplied files are converted so the rest of the app sees straight alpha
+    let mut processed = None;
+    if alpha_mode == Some(AlphaMode::Premultiplied) && has_alpha {
+        let mut rgba_img = img.to_rgba8();
+        unpremultiply_alpha(&mut rgba_img);
+        processed = Some(DynamicImage::ImageRgba8(rgba_img));
+    }
     
-    Ok(ImageData {
-        path: decoded_path,
-        width,
-        height,
-        format,
-        data: base64_data,
-        has_alpha,
-    })
+    // The sRGB copy is a lossless PNG without a profile, so it's shown as sRGB
+    let mut display_data = None;
+    if convert_to_srgb.unwrap_or(true) {
+        if let Some(profile) = extract_icc_profile(&file_bytes) {
+            let source = processed.as_ref().unwrap_or(&img);
+            if let Some(converted) = color_management::convert_to_srgb(source, &profile)? {
+                display_data = Some(encode_image_data(&converted, decoded_path.clone(), ImageFormat::PNG)?.data);
+            }
+        }
+    }
+    
+    let image = match processed {
+        Some(processed) => encode_image_data(&processed, decoded_path, format)?,
+        None => ImageData {
+            path: decoded_path,
+            width,
+            height,
+            format,
+            data: general_purpose::STANDARD.encode(&file_bytes),
+            has_alpha,
+            display_data: None,
+        },
+    };
+    
+    Ok(ImageData { display_data, ..image })
+}
+
+/// Load an image from the system clipboard
+/// 
+/// Reads the clipboard's image (e.g. a screenshot) and returns it as PNG
+/// ImageData with the path "clipboard.png", so it can be edited and saved
+/// like an opened file.
+#[tauri::command]
+async fn load_image_from_clipboard(app: AppHandle) -> Result<ImageData, CommandError> {
+    let image = app.clipboard()
+        .read_image()
+        .map_err(|e| AppError::InvalidImageData(format!("Clipboard does not contain an image: {}", e)))?;
+    
+    Ok(image_data_from_rgba(image.rgba(), image.width(), image.height(), "clipboard.png".to_string())?)
+}
+
+/// Copy an image to the system clipboard
+/// 
+/// The image is decoded and handed to the clipboard as raw RGBA pixels, so it
+/// can be pasted into other applications without saving a file first.
+/// 
+/// @param image_data - The image to copy
+#[tauri::command]
+async fn copy_image_to_clipboard(app: AppHandle, image_data: ImageData) -> Result<(), CommandError> {
+    let (rgba, width, height) = image_data_to_rgba(&image_data)?;
+    
+    app.clipboard()
+        .write_image(&tauri::image::Image::new_owned(rgba, width, height))
+        .map_err(|e| AppError::OperationFailed(format!("Failed to copy image to clipboard: {}", e)))?;
+    
+    Ok(())
+}
+
+/// Decode ImageData into raw RGBA pixels (4 bytes per pixel, row by row)
+/// 
+/// Returns the pixels with the image's width and height.
+fn image_data_to_rgba(image_data: &ImageData) -> Result<(Vec<u8>, u32, u32), AppError> {
+    let rgba = decode_image_data(image_data)?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    
+    Ok((rgba.into_raw(), width, height))
+}
+
+/// Wrap raw RGBA pixels (4 bytes per pixel, row by row) as PNG ImageData
+fn image_data_from_rgba(rgba: &[u8], width: u32, height: u32, path: String) -> Result<ImageData, AppError> {
+    let img = image::RgbaImage::from_raw(width, height, rgba.to_vec())
+        .ok_or_else(|| AppError::InvalidImageData(format!(
+            "Expected {} bytes of RGBA data for a {}x{} image, got {}",
+            width as u64 * height as u64 * 4, width, height, rgba.len()
+        )))?;
+    
+    encode_image_data(&DynamicImage::ImageRgba8(img), path, ImageFormat::PNG)
+}
+
+/// Tell damaged files apart from other decoding failures
+/// 
+/// Malformed data and files that end early (e.g. an interrupted download)
+/// become CorruptImage; anything else, such as an unsupported feature of the
+/// format, stays an ImageError.
+fn classify_decode_error(path: &str, error: image::ImageError) -> AppError {
+    match error {
+        image::ImageError::Decoding(_) => AppError::CorruptImage(path.to_string()),
+        image::ImageError::IoError(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            AppError::CorruptImage(path.to_string())
+        }
+        other => AppError::ImageError(other),
+    }
+}
+
+/// Decode an image file, reporting damaged files as CorruptImage
+/// 
+/// The format is sniffed from the file's content, falling back to the
+/// extension, so a PNG named .jpg still decodes.
+fn open_image(path: &Path) -> AppResult<DynamicImage> {
+    ImageReader::open(path)
+        .map_err(AppError::IoError)?
+        .with_guessed_format()
+        .map_err(AppError::IoError)?
+        .decode()
+        .map_err(|e| classify_decode_error(&path.to_string_lossy(), e))
 }
 
 /// Load SVG image (special handling since image crate doesn't decode SVG)
-fn load_svg_image(path: String, file_bytes: Vec<u8>) -> Result<ImageData, String> {
+fn load_svg_image(path: String, file_bytes: Vec<u8>) -> Result<ImageData, CommandError> {
     // For SVG, we can't easily determine dimensions without a full SVG parser
     // We'll use placeholder dimensions and let the frontend handle rendering
     // SVG files are typically small and can be embedded directly
@@ -185,638 +392,3936 @@ fn load_svg_image(path: String, file_bytes: Vec<u8>) -> Result<ImageData, String
         format: ImageFormat::SVG,
         data: base64_data,
         has_alpha: true, // SVG can have transparency
+        display_data: None,
     })
 }
 
-/// Detect if an image has an alpha (transparency) channel
-fn detect_alpha_channel(img: &DynamicImage) -> bool {
-    use image::DynamicImage::*;
+/// Read an image file's dimensions, format and size without decoding its pixels
+/// 
+/// Only the file header is parsed, which makes this cheap enough for file-list
+/// previews. SVG files report 0x0 like load_image does.
+/// 
+/// @param path - File path to the image
+/// @returns Width, height, format and file size in bytes
+#[tauri::command]
+async fn probe_image(path: String) -> Result<ImageProbe, CommandError> {
+    error::utils::validate_file_exists(&path)?;
     
-    match img {
-        ImageLuma8(_) | ImageRgb8(_) | ImageRgb16(_) | ImageRgb32F(_) => false,
-        ImageLumaA8(img_data) => {
-            // Check if any pixel has alpha < 255
-            img_data.pixels().any(|p| p.0[1] < 255)
-        }
-        ImageRgba8(img_data) => {
-            // Check if any pixel has alpha < 255
-            img_data.pixels().any(|p| p.0[3] < 255)
-        }
-        ImageRgba16(img_data) => {
-            // Check if any pixel has alpha < 65535
-            img_data.pixels().any(|p| p.0[3] < 65535)
-        }
-        ImageRgba32F(img_data) => {
-            // Check if any pixel has alpha < 1.0
-            img_data.pixels().any(|p| p.0[3] < 1.0)
-        }
-        _ => false,
+    let file_size = fs::metadata(&path)
+        .map_err(AppError::IoError)?
+        .len();
+    
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    
+    if extension == "heic" || extension == "heif" {
+        return Err(AppError::UnsupportedFormat(
+            "HEIC format is not yet supported".to_string()
+        ).into());
     }
-}
-
-/// Detect image format from file path and extension
-fn detect_image_format(path: &str, extension: &str) -> Result<ImageFormat, AppError> {
-    let format = match extension {
-        "png" => ImageFormat::PNG,
-        "jpg" | "jpeg" => ImageFormat::JPEG,
-        "gif" => ImageFormat::GIF,
-        "bmp" => ImageFormat::BMP,
-        "webp" => ImageFormat::WEBP,
-        "svg" => ImageFormat::SVG,
-        "tiff" | "tif" => ImageFormat::TIFF,
-        "ico" => ImageFormat::ICO,
-        "heic" | "heif" => ImageFormat::HEIC,
-        "avif" => ImageFormat::AVIF,
-        _ => {
-            // Try to guess from image crate
-            let img_format = ImageReader::open(path)
-                .ok()
-                .and_then(|reader| reader.format())
-                .and_then(ImageFormat::from_image_format);
-            
-            img_format.ok_or_else(|| {
-                AppError::UnsupportedFormat(format!("Unknown format: {}", extension))
-            })?
-        }
+    
+    let format = detect_image_format(&path, &extension)?;
+    
+    let (width, height) = if format == ImageFormat::SVG {
+        (0, 0)
+    } else {
+        ImageReader::open(&path)
+            .map_err(AppError::IoError)?
+            .with_guessed_format()
+            .map_err(AppError::IoError)?
+            .into_dimensions()
+            .map_err(|e| classify_decode_error(&path, e))?
     };
     
-    Ok(format)
+    Ok(ImageProbe {
+        path,
+        width,
+        height,
+        format,
+        file_size,
+    })
 }
 
-/// Get list of image files in a directory
+/// Gather a thumbnail and key metadata of an image file for a hover preview
 /// 
-/// Returns a list of file paths for all supported image formats in the specified directory
+/// The thumbnail comes from the EXIF data when the file embeds one at least
+/// `size` pixels on its longest side; otherwise the image is decoded and
+/// scaled down. Thumbnails are encoded as JPEG, or PNG if they have alpha.
+/// 
+/// @param path - File path to the image
+/// @param size - Longest edge of the thumbnail in pixels (default 160)
+/// @returns Dimensions, format, file size, capture date and the thumbnail
 #[tauri::command]
-async fn get_directory_images(dir_path: String) -> Result<Vec<String>, String> {
-    let path = Path::new(&dir_path);
-    
-    // Validate directory exists
-    if !path.exists() {
-        return Err(AppError::FileNotFound(dir_path).into());
-    }
-    
-    if !path.is_dir() {
+async fn quick_look(
+    path: String,
+    size: Option<u32>,
+    thumbnails: State<'_, ThumbnailCache>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<QuickLook, CommandError> {
+    let size = size.unwrap_or(160);
+    if size == 0 {
         return Err(AppError::InvalidParameters(
-            "Path is not a directory".to_string()
+            "Thumbnail size must be positive".to_string()
         ).into());
     }
     
-    // Read directory entries
-    let entries = fs::read_dir(path)
-        .map_err(AppError::IoError)?;
+    let probe = probe_image(path.clone()).await?;
     
-    // Supported image extensions
-    let supported_extensions = [
-        "png", "jpg", "jpeg", "gif", "bmp", "webp", 
-        "svg", "tiff", "tif", "ico", "heic", "heif", "avif"
-    ];
+    let thumbnail = thumbnails
+        .get_or_insert_with(Path::new(&path), size, || generate_quick_look_thumbnail(&path, size, &limiter))
+        .await?;
     
-    // Filter and collect image files
-    let mut image_files: Vec<String> = entries
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.path().is_file()
-        })
-        .filter(|entry| {
-            entry.path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| supported_extensions.contains(&ext.to_lowercase().as_str()))
-                .unwrap_or(false)
-        })
-        .filter_map(|entry| {
-            entry.path().to_str().map(|s| s.to_string())
-        })
-        .collect();
+    let capture_date = camera::read_capture_date(Path::new(&path))
+        .map(|(year, month, day)| format!("{:04}-{:02}-{:02}", year, month, day));
     
-    // Sort alphabetically for consistent ordering
-    image_files.sort();
+    Ok(QuickLook {
+        probe,
+        thumbnail,
+        capture_date,
+    })
+}
+
+/// Decode and scale down the thumbnail for quick_look on a cache miss
+async fn generate_quick_look_thumbnail(
+    path: &str,
+    size: u32,
+    limiter: &OperationLimiter,
+) -> AppResult<ImageData> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let embedded = camera::read_exif_thumbnail(Path::new(path))
+        .and_then(|bytes| image::load_from_memory(&bytes).ok())
+        .filter(|thumbnail| thumbnail.width().max(thumbnail.height()) >= size);
+    let source = match embedded {
+        Some(thumbnail) => thumbnail,
+        None => open_image(Path::new(path))?,
+    };
+    // Small images are returned at their own size rather than enlarged
+    let longest_edge = size.min(source.width().max(source.height()));
+    let thumbnail = thumbnail_image(&source, longest_edge, &ResampleOptions::balanced());
+    drop(source);
     
-    Ok(image_files)
+    let format = if detect_alpha_channel(&thumbnail) { ImageFormat::PNG } else { ImageFormat::JPEG };
+    let thumbnail_path = update_file_extension(&path_with_suffix(path, "_thumb"), &format);
+    
+    encode_image_data(&thumbnail, thumbnail_path, format)
 }
 
-/// Open file dialog to select an image file
+/// Drop every thumbnail cached by quick_look
 /// 
-/// Returns the selected file path, or None if the user cancelled
+/// Thumbnails of files changed on disk are regenerated automatically; this
+/// frees the memory, e.g. after leaving a large directory.
 #[tauri::command]
-async fn open_file_dialog(app: tauri::AppHandle) -> Result<Option<String>, String> {
-    use tauri_plugin_dialog::DialogExt;
-    
-    let file_path = app.dialog()
-        .file()
-        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "tiff", "tif", "ico", "heic", "heif", "avif"])
-        .blocking_pick_file();
-    
-    Ok(file_path.and_then(|path| {
-        path.as_path().map(|p| p.to_string_lossy().to_string())
-    }))
+async fn clear_thumbnail_cache(thumbnails: State<'_, ThumbnailCache>) -> Result<(), CommandError> {
+    thumbnails.clear();
+    Ok(())
 }
 
-/// Open save file dialog
+/// Get the resolution levels stored in an image file
 /// 
-/// Returns the selected save path, or None if the user cancelled
+/// Multi-resolution (pyramid) TIFFs report one entry per stored level.
+/// Formats without pyramids report a single level with the full image dimensions.
 #[tauri::command]
-async fn save_file_dialog(app: tauri::AppHandle, default_name: String) -> Result<Option<String>, String> {
-    use tauri_plugin_dialog::DialogExt;
+async fn get_pyramid_levels(path: String) -> Result<Vec<PyramidLevel>, CommandError> {
+    error::utils::validate_file_exists(&path)?;
     
-    let file_path = app.dialog()
-        .file()
-        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "tiff", "tif", "ico", "avif"])
-        .set_file_name(&default_name)
-        .blocking_save_file();
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
     
-    Ok(file_path.and_then(|path| {
-        path.as_path().map(|p| p.to_string_lossy().to_string())
-    }))
+    if detect_image_format(&path, &extension)? == ImageFormat::TIFF {
+        return Ok(pyramid::read_tiff_levels(&path)?);
+    }
+    
+    // Single-level fallback: read the dimensions from the header only
+    let (width, height) = ImageReader::open(&path)
+        .map_err(AppError::IoError)?
+        .with_guessed_format()
+        .map_err(AppError::IoError)?
+        .into_dimensions()
+        .map_err(|e| classify_decode_error(&path, e))?;
+    
+    Ok(vec![PyramidLevel { level: 0, width, height }])
 }
 
-/// Save image data to a file
+/// Load a single resolution level of a multi-resolution image
+/// 
+/// Decoding a reduced level is much faster than decoding the full-resolution image,
+/// which makes it suitable for zoomed-out viewing of very large files.
+/// Formats without pyramids only provide level 0, which is the regular image.
 /// 
-/// Decodes the Base64 image data and writes it to the specified path
+/// @param path - File path to the image
+/// @param level - Index of the level as reported by get_pyramid_levels
+/// @returns ImageData containing the decoded level
 #[tauri::command]
-async fn save_image(image_data: ImageData, path: String) -> Result<(), String> {
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
-        .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
+async fn load_image_at_level(path: String, level: u32) -> Result<ImageData, CommandError> {
+    error::utils::validate_file_exists(&path)?;
     
-    // Validate the parent directory exists
-    let path_obj = Path::new(&path);
-    if let Some(parent) = path_obj.parent() {
-        if !parent.exists() {
-            return Err(AppError::FileNotFound(
-                format!("Directory does not exist: {}", parent.display())
-            ).into());
-        }
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    
+    if detect_image_format(&path, &extension)? == ImageFormat::TIFF {
+        let img = pyramid::decode_tiff_level(&path, level)?;
+        return Ok(encode_image_data(&img, path, ImageFormat::TIFF)?);
     }
     
-    // Write to file
-    fs::write(&path, decoded_data)
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                AppError::PermissionDenied(format!("Cannot write to: {}", path))
-            } else {
-                AppError::SaveFailed(format!("Failed to save image: {}", e))
-            }
-        })?;
+    if level != 0 {
+        return Err(AppError::InvalidParameters(
+            format!("Pyramid level {} does not exist", level)
+        ).into());
+    }
     
-    Ok(())
+    load_image(path, None, None).await
 }
 
-/// Resize an image to the specified dimensions
+/// Load an image, downscaling it if it exceeds a megapixel budget
 /// 
-/// If keep_aspect_ratio is true, the image will be resized to fit within the specified
-/// dimensions while maintaining the original aspect ratio. The actual dimensions may be
-/// smaller than requested to preserve the aspect ratio.
+/// The dimensions are read from the file header first, so images within the
+/// budget are loaded exactly like load_image. Larger images are decoded,
+/// downscaled to fit (keeping the aspect ratio) and re-encoded in their own
+/// format, so the full-resolution data never leaves the backend.
 /// 
-/// If keep_aspect_ratio is false, the image will be resized to exactly the specified dimensions.
+/// @param path - File path to the image
+/// @param max_megapixels - Largest allowed width * height in millions of pixels
+/// @returns The loaded image together with the original dimensions
 #[tauri::command]
-async fn resize_image(
-    image_data: ImageData,
-    width: u32,
-    height: u32,
-    keep_aspect_ratio: bool,
-) -> Result<ImageData, String> {
-    // Validate input parameters
-    if width == 0 || height == 0 {
+async fn load_image_capped(path: String, max_megapixels: f64) -> Result<CappedImageData, CommandError> {
+    if max_megapixels.is_nan() || max_megapixels <= 0.0 {
         return Err(AppError::InvalidParameters(
-            "Width and height must be positive integers".to_string()
+            "Megapixel budget must be positive".to_string()
         ).into());
     }
+    let max_pixels = max_megapixels * 1_000_000.0;
     
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
+    error::utils::validate_file_exists(&path)?;
+    
+    let (original_width, original_height) = match read_header_dimensions(&path) {
+        Some((width, height)) if (width as f64 * height as f64) > max_pixels => (width, height),
+        _ => return load_image_uncapped(path).await,
+    };
+    
+    // Scale both sides by the same factor, rounding down so the result stays within budget
+    let scale = (max_pixels / (original_width as f64 * original_height as f64)).sqrt();
+    let width = ((original_width as f64 * scale).floor() as u32).max(1);
+    let height = ((original_height as f64 * scale).floor() as u32).max(1);
+    
+    Ok(load_image_resized(path, (original_width, original_height), (width, height))?)
+}
+
+/// Load an image, downscaling it so neither side exceeds max_dim
+/// 
+/// Works like load_image_capped with a limit on the longest side instead of
+/// the pixel count, which bounds the size of the returned Base64 data. Huge
+/// files such as gigapixel scans are decoded and downscaled right away; the
+/// original dimensions are still reported.
+/// 
+/// @param path - File path to the image
+/// @param max_dim - Largest allowed width or height in pixels
+/// @returns The loaded image together with the original dimensions
+#[tauri::command]
+async fn load_image_downscaled(path: String, max_dim: u32) -> Result<CappedImageData, CommandError> {
+    if max_dim == 0 {
+        return Err(AppError::InvalidParameters(
+            "Maximum dimension must be positive".to_string()
+        ).into());
+    }
+    
+    error::utils::validate_file_exists(&path)?;
+    
+    let (original_width, original_height) = match read_header_dimensions(&path) {
+        Some((width, height)) if width.max(height) > max_dim => (width, height),
+        _ => return load_image_uncapped(path).await,
+    };
+    
+    let scale = max_dim as f64 / original_width.max(original_height) as f64;
+    let width = ((original_width as f64 * scale).round() as u32).clamp(1, max_dim);
+    let height = ((original_height as f64 * scale).round() as u32).clamp(1, max_dim);
+    
+    Ok(load_image_resized(path, (original_width, original_height), (width, height))?)
+}
+
+/// Read an image's dimensions from its file header
+/// 
+/// Returns None for files the image crate can't parse, such as SVG and HEIC.
+fn read_header_dimensions(path: &str) -> Option<(u32, u32)> {
+    ImageReader::open(path)
+        .ok()
+        .and_then(|reader| reader.with_guessed_format().ok())
+        .and_then(|reader| reader.into_dimensions().ok())
+}
+
+/// Load an image at full size for the capped loaders
+async fn load_image_uncapped(path: String) -> Result<CappedImageData, CommandError> {
+    let image = load_image(path, None, None).await?;
+    Ok(CappedImageData {
+        original_width: image.width,
+        original_height: image.height,
+        image,
+        downscaled: false,
+    })
+}
+
+/// Decode an image, downscale it to the given size and re-encode it in its own format
+fn load_image_resized(
+    path: String,
+    (original_width, original_height): (u32, u32),
+    (width, height): (u32, u32),
+) -> Result<CappedImageData, AppError> {
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let format = detect_image_format(&path, &extension)?;
+    
+    let img = ImageReader::open(&path)
+        .map_err(AppError::IoError)?
+        .with_guessed_format()
+        .map_err(AppError::IoError)?
+        .decode()
+        .map_err(|e| classify_decode_error(&path, e))?;
+    let resized = resample_image(&img, width, height, &ResampleOptions::balanced());
+    drop(img);
+    
+    println!(
+        "Downscaled {} from {}x{} to {}x{}",
+        path, original_width, original_height, width, height
+    );
+    
+    Ok(CappedImageData {
+        image: encode_image_data(&resized, path, format)?,
+        original_width,
+        original_height,
+        downscaled: true,
+    })
+}
+
+/// Detect if an image has an alpha (transparency) channel
+fn detect_alpha_channel(img: &DynamicImage) -> bool {
+    use image::DynamicImage::*;
+    
+    match img {
+        ImageLuma8(_) | ImageRgb8(_) | ImageRgb16(_) | ImageRgb32F(_) => false,
+        ImageLumaA8(img_data) => {
+            // Check if any pixel has alpha < 255
+            img_data.pixels().any(|p| p.0[1] < 255)
+        }
+        ImageLumaA16(img_data) => {
+            // Check if any pixel has alpha < 65535
+            img_data.pixels().any(|p| p.0[1] < 65535)
+        }
+        ImageRgba8(img_data) => {
+            // Check if any pixel has alpha < 255
+            img_data.pixels().any(|p| p.0[3] < 255)
+        }
+        ImageRgba16(img_data) => {
+            // Check if any pixel has alpha < 65535
+            img_data.pixels().any(|p| p.0[3] < 65535)
+        }
+        ImageRgba32F(img_data) => {
+            // Check if any pixel has alpha < 1.0
+            img_data.pixels().any(|p| p.0[3] < 1.0)
+        }
+        _ => false,
+    }
+}
+
+/// Detect image format from file content, falling back to the extension
+/// 
+/// The magic bytes win over a misleading extension (e.g. a PNG named .jpg),
+/// so the reported format matches what is actually in the file. The
+/// extension only decides when the header isn't recognised, as for SVG.
+fn detect_image_format(path: &str, extension: &str) -> Result<ImageFormat, AppError> {
+    if let Some(format) = sniff_image_format(path) {
+        return Ok(format);
+    }
+    
+    ImageFormat::from_extension(extension).ok_or_else(|| {
+        AppError::UnsupportedFormat(format!("Unknown format: {}", extension))
+    })
+}
+
+/// Guess an image's format from the magic bytes at the start of the file
+fn sniff_image_format(path: &str) -> Option<ImageFormat> {
+    // Enough for every signature image::guess_format checks
+    const HEADER_LEN: u64 = 64;
+    
+    let mut header = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(HEADER_LEN).read_to_end(&mut header))
+        .ok()?;
+    
+    image::guess_format(&header)
+        .ok()
+        .and_then(ImageFormat::from_image_format)
+}
+
+/// Decode the Base64 payload of an ImageData into a DynamicImage
+fn decode_image_data(image_data: &ImageData) -> Result<DynamicImage, AppError> {
+    let decoded_data = general_purpose::STANDARD.decode(&image_data.data)?;
+
+    image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)
+}
+
+/// Encode a processed image to the given format and wrap it in a new ImageData
+fn encode_image_data(
+    img: &DynamicImage,
+    path: String,
+    format: ImageFormat,
+) -> Result<ImageData, AppError> {
+    encode_image_data_with_profile(img, path, format, None)
+}
+
+/// Encode an image like encode_image_data, embedding an ICC color profile
+/// 
+/// The profile is written for PNG, JPEG, WEBP and TIFF; other formats can't
+/// carry one and are encoded without it.
+fn encode_image_data_with_profile(
+    img: &DynamicImage,
+    path: String,
+    format: ImageFormat,
+    icc_profile: Option<Vec<u8>>,
+) -> Result<ImageData, AppError> {
+    let img_format = format.to_image_format()
+        .ok_or_else(|| AppError::UnsupportedFormat(
+            format!("Cannot encode {} format", format)
+        ))?;
+
+    let mut output_buffer = Vec::new();
+    write_image_with_profile(img, &mut output_buffer, img_format, icc_profile)?;
+
+    Ok(ImageData {
+        path,
+        width: img.width(),
+        height: img.height(),
+        format,
+        data: general_purpose::STANDARD.encode(&output_buffer),
+        has_alpha: detect_alpha_channel(img),
+        display_data: None,
+    })
+}
+
+/// Write an image in the given format, embedding an ICC profile where supported
+fn write_image_with_profile(
+    img: &DynamicImage,
+    output_buffer: &mut Vec<u8>,
+    format: image::ImageFormat,
+    icc_profile: Option<Vec<u8>>,
+) -> Result<(), AppError> {
+    write_image_with_metadata(img, output_buffer, format, icc_profile, None)
+}
+
+/// Write an image in the given format, embedding an ICC profile and a raw EXIF
+/// block where supported
+///
+/// PNG, JPEG and WEBP carry both and TIFF carries the profile; other formats
+/// are written without them.
+fn write_image_with_metadata(
+    img: &DynamicImage,
+    output_buffer: &mut Vec<u8>,
+    format: image::ImageFormat,
+    icc_profile: Option<Vec<u8>>,
+    exif: Option<Vec<u8>>,
+) -> Result<(), AppError> {
+    use image::codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder};
+
+    if icc_profile.is_none() && exif.is_none() {
+        return img
+            .write_to(&mut std::io::Cursor::new(output_buffer), format)
+            .map_err(AppError::ImageError);
+    }
+
+    match format {
+        image::ImageFormat::Png => {
+            let mut encoder = PngEncoder::new(&mut *output_buffer);
+            attach_metadata(&mut encoder, icc_profile, exif)?;
+            img.write_with_encoder(encoder)
+        }
+        image::ImageFormat::Jpeg => {
+            let mut encoder = JpegEncoder::new(&mut *output_buffer);
+            attach_metadata(&mut encoder, icc_profile, exif)?;
+            img.write_with_encoder(encoder)
+        }
+        image::ImageFormat::WebP => {
+            let mut encoder = WebPEncoder::new_lossless(&mut *output_buffer);
+            attach_metadata(&mut encoder, icc_profile, exif)?;
+            img.write_with_encoder(encoder)
+        }
+        // Uncompressed, like the image crate's own TIFF encoder
+        image::ImageFormat::Tiff => {
+            return write_tiff(img, output_buffer, TiffCompression::None, icc_profile.as_deref(), exif.as_deref());
+        }
+        _ => img.write_to(&mut std::io::Cursor::new(output_buffer), format),
+    }
+    .map_err(AppError::ImageError)
+}
+
+/// Write an image as a TIFF with the given compression
+/// 
+/// The image crate's TIFF encoder only writes uncompressed files without
+/// metadata, so this uses the tiff crate directly. LZW and Deflate use the
+/// horizontal predictor, which makes smooth gradients compress much better.
+/// 8 and 16-bit gray, RGB and RGBA are kept as they are; other layouts are
+/// written as 8-bit RGB(A). An ICC profile and EXIF fields are stored in the
+/// image's tags.
+fn write_tiff(
+    img: &DynamicImage,
+    output_buffer: &mut Vec<u8>,
+    compression: TiffCompression,
+    icc_profile: Option<&[u8]>,
+    exif: Option<&[u8]>,
+) -> Result<(), AppError> {
+    use tiff::encoder::{colortype, Compression, DeflateLevel, Predictor, TiffEncoder};
+    
+    let (compression, predictor) = match compression {
+        TiffCompression::None => (Compression::Uncompressed, Predictor::None),
+        TiffCompression::Lzw => (Compression::Lzw, Predictor::Horizontal),
+        TiffCompression::Deflate => (Compression::Deflate(DeflateLevel::Balanced), Predictor::Horizontal),
+    };
+    let encode_error = |e: tiff::TiffError| AppError::OperationFailed(format!("Failed to encode TIFF: {}", e));
+    
+    let mut encoder = TiffEncoder::new(std::io::Cursor::new(output_buffer))
+        .map_err(encode_error)?
+        .with_compression(compression)
+        .with_predictor(predictor);
+    let mut metadata = tiff_metadata::TiffMetadata::new(icc_profile, exif);
+    metadata.write_sub_directories(&mut encoder).map_err(encode_error)?;
+    let size = img.dimensions();
+    
+    match img {
+        DynamicImage::ImageLuma8(gray) => write_tiff_image::<colortype::Gray8>(&mut encoder, size, gray.as_raw(), &metadata),
+        DynamicImage::ImageLuma16(gray) => write_tiff_image::<colortype::Gray16>(&mut encoder, size, gray.as_raw(), &metadata),
+        DynamicImage::ImageRgb8(rgb) => write_tiff_image::<colortype::RGB8>(&mut encoder, size, rgb.as_raw(), &metadata),
+        DynamicImage::ImageRgb16(rgb) => write_tiff_image::<colortype::RGB16>(&mut encoder, size, rgb.as_raw(), &metadata),
+        DynamicImage::ImageRgba16(rgba) => write_tiff_image::<colortype::RGBA16>(&mut encoder, size, rgba.as_raw(), &metadata),
+        _ if !img.color().has_alpha() => {
+            write_tiff_image::<colortype::RGB8>(&mut encoder, size, img.to_rgb8().as_raw(), &metadata)
+        }
+        _ => write_tiff_image::<colortype::RGBA8>(&mut encoder, size, img.to_rgba8().as_raw(), &metadata),
+    }
+    .map_err(encode_error)
+}
+
+/// Write one image directory of a TIFF, with its metadata tags
+fn write_tiff_image<C: tiff::encoder::colortype::ColorType>(
+    encoder: &mut tiff::encoder::TiffEncoder<std::io::Cursor<&mut Vec<u8>>>,
+    (width, height): (u32, u32),
+    data: &[C::Inner],
+    metadata: &tiff_metadata::TiffMetadata,
+) -> tiff::TiffResult<()>
+where
+    [C::Inner]: tiff::encoder::TiffValue,
+{
+    let mut image = encoder.new_image::<C>(width, height)?;
+    metadata.write_image_tags(image.encoder())?;
+    image.write_data(data)
+}
+
+/// Hand an ICC profile and a raw EXIF block to an encoder before it writes
+fn attach_metadata<E: image::ImageEncoder>(
+    encoder: &mut E,
+    icc_profile: Option<Vec<u8>>,
+    exif: Option<Vec<u8>>,
+) -> Result<(), AppError> {
+    let unsupported = |e| AppError::ImageError(image::ImageError::Unsupported(e));
+
+    if let Some(profile) = icc_profile {
+        encoder.set_icc_profile(profile).map_err(unsupported)?;
+    }
+    if let Some(exif) = exif {
+        encoder.set_exif_metadata(exif).map_err(unsupported)?;
+    }
+    Ok(())
+}
+
+/// Read the ICC color profile embedded in encoded image bytes, if any
+fn extract_icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    use image::ImageDecoder;
+
+    let reader = ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?;
+    // The image crate caps TIFF tag sizes by the pixel buffer size, which
+    // loses the profile of small images
+    if reader.format() == Some(image::ImageFormat::Tiff) {
+        return tiff_metadata::read_icc_profile(bytes);
+    }
+    reader
+        .into_decoder()
+        .ok()?
+        .icc_profile()
+        .ok()
+        .flatten()
+}
+
+/// Read the raw EXIF block embedded in encoded image bytes, if any
+///
+/// The block is the TIFF structure without the JPEG "Exif" header, which is
+/// the form the encoders expect.
+fn extract_exif(bytes: &[u8]) -> Option<Vec<u8>> {
+    use image::ImageDecoder;
+
+    ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?
+        .exif_metadata()
+        .ok()
+        .flatten()
+        .filter(|exif| !exif.is_empty())
+}
+
+/// Convert an RGBA8 processing result back to grayscale if the source was grayscale
+///
+/// Operations that work on RGBA8 internally would otherwise re-encode a
+/// grayscale+alpha image with four channels instead of two.
+fn match_grayscale_layout(result: image::RgbaImage, source: &DynamicImage) -> DynamicImage {
+    let result = DynamicImage::ImageRgba8(result);
+    
+    match source.color() {
+        image::ColorType::La8 => DynamicImage::ImageLumaA8(result.to_luma_alpha8()),
+        image::ColorType::L8 if detect_alpha_channel(&result) => {
+            DynamicImage::ImageLumaA8(result.to_luma_alpha8())
+        }
+        image::ColorType::L8 => DynamicImage::ImageLuma8(result.to_luma8()),
+        _ => result,
+    }
+}
+
+/// Get the operations supported for an image format
+/// 
+/// Lets the frontend enable or disable tools without duplicating the backend rules
+#[tauri::command]
+async fn get_format_capabilities(format: ImageFormat) -> Result<FormatCapabilities, CommandError> {
+    Ok(format.capabilities())
+}
+
+/// Set how many heavy operations may run at the same time
+/// 
+/// Operations already running are not interrupted; the new limit applies
+/// to operations started afterwards.
+#[tauri::command]
+async fn set_max_concurrent_operations(
+    max: usize,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<(), CommandError> {
+    limiter.set_max_concurrent(max)?;
+    Ok(())
+}
+
+/// Cancel the heavy operations in progress
+/// 
+/// Batch, compositing and filter loops check for the request between items or
+/// rows and stop with an OperationFailed("cancelled") error. Only operations
+/// already running are affected; a request while nothing runs is ignored.
+#[tauri::command]
+async fn cancel_operation(cancel_registry: State<'_, CancelRegistry>) -> Result<(), CommandError> {
+    let cancelled = cancel_registry.cancel_all();
+    println!("Cancelled {} running operation(s)", cancelled);
+    Ok(())
+}
+
+/// Get list of image files in a directory
+/// 
+/// Returns a list of file paths for all supported image formats in the specified directory
+#[tauri::command]
+async fn get_directory_images(dir_path: String) -> Result<Vec<String>, CommandError> {
+    let path = Path::new(&dir_path);
+    
+    // Validate directory exists
+    if !path.exists() {
+        return Err(AppError::FileNotFound(dir_path).into());
+    }
+    
+    if !path.is_dir() {
+        return Err(AppError::InvalidParameters(
+            "Path is not a directory".to_string()
+        ).into());
+    }
+    
+    // Read directory entries
+    let entries = fs::read_dir(path)
+        .map_err(AppError::IoError)?;
+    
+    // Supported image extensions
+    let supported_extensions = [
+        "png", "jpg", "jpeg", "gif", "bmp", "webp", 
+        "svg", "tiff", "tif", "ico", "heic", "heif", "avif"
+    ];
+    
+    // Filter and collect image files
+    let mut image_files: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.path().is_file()
+        })
+        .filter(|entry| {
+            entry.path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| supported_extensions.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| {
+            entry.path().to_str().map(|s| s.to_string())
+        })
+        .collect();
+    
+    // Sort alphabetically for consistent ordering
+    image_files.sort();
+    
+    Ok(image_files)
+}
+
+/// Open file dialog to select an image file
+/// 
+/// Returns the selected file path, or None if the user cancelled
+#[tauri::command]
+async fn open_file_dialog(app: tauri::AppHandle) -> Result<Option<String>, CommandError> {
+    use tauri_plugin_dialog::DialogExt;
+    
+    let file_path = app.dialog()
+        .file()
+        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "tiff", "tif", "ico", "heic", "heif", "avif"])
+        .blocking_pick_file();
+    
+    Ok(file_path.and_then(|path| {
+        path.as_path().map(|p| p.to_string_lossy().to_string())
+    }))
+}
+
+/// Open save file dialog
+/// 
+/// Returns the selected save path, or None if the user cancelled
+#[tauri::command]
+async fn save_file_dialog(app: tauri::AppHandle, default_name: String) -> Result<Option<String>, CommandError> {
+    use tauri_plugin_dialog::DialogExt;
+    
+    let file_path = app.dialog()
+        .file()
+        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "tiff", "tif", "ico", "avif"])
+        .set_file_name(&default_name)
+        .blocking_save_file();
+    
+    Ok(file_path.and_then(|path| {
+        path.as_path().map(|p| p.to_string_lossy().to_string())
+    }))
+}
+
+/// Save image data to a file
+/// 
+/// Decodes the Base64 image data and writes it to the specified path.
+/// If the path's extension names a different format than the image data
+/// (e.g. PNG data saved as "photo.jpg"), the image is converted to that format
+/// first; unknown extensions are written as they are.
+/// If alpha_mode is "premultiplied", color values are premultiplied by alpha
+/// before writing.
+/// If overwrite is false, an existing file at the path is an error, including
+/// one created while the image was being encoded. The file is written to a
+/// temporary file next to it and moved into place, so an interrupted save never
+/// leaves a truncated image behind.
+#[tauri::command]
+async fn save_image(
+    image_data: ImageData,
+    path: String,
+    alpha_mode: Option<AlphaMode>,
+    overwrite: Option<bool>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<(), CommandError> {
+    if !overwrite.unwrap_or(true) && Path::new(&path).exists() {
+        return Err(AppError::FileExists(path).into());
+    }
+    
+    // Decode Base64 data
+    let mut decoded_data = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    // Write premultiplied color values if the consumer expects them
+    if alpha_mode == Some(AlphaMode::Premultiplied) {
+        let mut rgba_img = image::load_from_memory(&decoded_data)
+            .map_err(AppError::ImageError)?
+            .to_rgba8();
+        premultiply_alpha(&mut rgba_img);
+        let encoded = encode_image_data(&DynamicImage::ImageRgba8(rgba_img), path.clone(), image_data.format.clone())?;
+        decoded_data = general_purpose::STANDARD
+            .decode(&encoded.data)
+            .map_err(AppError::Base64Error)?;
+    }
+    
+    // Transcode if the extension asks for another format
+    let target_format = Path::new(&path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| ImageFormat::from_extension(&ext.to_lowercase()));
+    if let Some(target_format) = target_format.filter(|format| *format != image_data.format) {
+        if target_format.to_image_format().is_none() {
+            return Err(AppError::UnsupportedFormat(
+                format!("Cannot save {} image data as {}", image_data.format, target_format)
+            ).into());
+        }
+        
+        let source = ImageData {
+            data: general_purpose::STANDARD.encode(&decoded_data),
+            ..image_data
+        };
+        let converted = convert_format(source, target_format.to_string(), None, None, limiter).await?;
+        decoded_data = general_purpose::STANDARD
+            .decode(&converted.data)
+            .map_err(AppError::Base64Error)?;
+    }
+    
+    // Validate the parent directory exists
+    let path_obj = Path::new(&path);
+    if let Some(parent) = path_obj.parent() {
+        if !parent.exists() {
+            return Err(AppError::FileNotFound(
+                format!("Directory does not exist: {}", parent.display())
+            ).into());
+        }
+    }
+    
+    // Write to a temporary file and move it to the target
+    write_file_atomically(path_obj, &decoded_data, overwrite.unwrap_or(true))
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                AppError::FileExists(path.clone())
+            } else if e.kind() == std::io::ErrorKind::PermissionDenied {
+                AppError::PermissionDenied(format!("Cannot write to: {}", path))
+            } else {
+                AppError::SaveFailed(format!("Failed to save image: {}", e))
+            }
+        })?;
+    
+    Ok(())
+}
+
+/// Write a file by writing a temporary file in the same directory and renaming
+/// it into place
+/// 
+/// The rename replaces the target in one step, so readers see either the old
+/// file or the complete new one. Every call gets its own temporary name, so
+/// concurrent writes to the same target don't share one. The temporary file is
+/// removed if anything fails.
+/// 
+/// Without overwrite, the temporary file is hard-linked to the target instead,
+/// which fails with AlreadyExists rather than replacing a file that appeared in
+/// the meantime. Filesystems without hard links get the target name reserved
+/// with create_new and the temporary file renamed over the empty reservation.
+fn write_file_atomically(path: &Path, data: &[u8], overwrite: bool) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    
+    static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+    
+    let file_name = path.file_name().ok_or_else(|| std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Not a file path: {}", path.display()),
+    ))?;
+    
+    // Leftovers from a crashed process with the same pid are skipped, not reused
+    let (temp_path, mut temp_file) = loop {
+        let temp_path = path.with_file_name(format!(
+            ".{}.{}.{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id(),
+            NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => break (temp_path, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    };
+    
+    let result = temp_file
+        .write_all(data)
+        .and_then(|_| temp_file.sync_all())
+        .and_then(|_| {
+            if overwrite {
+                return fs::rename(&temp_path, path);
+            }
+            match fs::hard_link(&temp_path, path) {
+                Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => {
+                    fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+                    fs::rename(&temp_path, path).inspect_err(|_| {
+                        let _ = fs::remove_file(path);
+                    })
+                }
+                linked => linked,
+            }
+        });
+    drop(temp_file);
+    
+    // After a hard link the temporary name is still there, so always clean up
+    if result.is_err() || !overwrite {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Export the decoded pixels of an image as a NumPy .npy file
+/// 
+/// Writes an array of shape [H, W, C] with dtype uint8, or uint16 for
+/// 16-bit sources, so images can be loaded with numpy.load directly.
+#[tauri::command]
+async fn export_npy(image_data: ImageData, path: String) -> Result<(), CommandError> {
+    let img = decode_image_data(&image_data)?;
+    
+    // Validate the parent directory exists
+    let path_obj = Path::new(&path);
+    if let Some(parent) = path_obj.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            return Err(AppError::FileNotFound(
+                format!("Directory does not exist: {}", parent.display())
+            ).into());
+        }
+    }
+    
+    fs::write(&path, npy::encode_npy(&img))
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                AppError::PermissionDenied(format!("Cannot write to: {}", path))
+            } else {
+                AppError::SaveFailed(format!("Failed to export NPY: {}", e))
+            }
+        })?;
+    
+    Ok(())
+}
+
+/// Resize an image to the specified dimensions
+/// 
+/// If keep_aspect_ratio is true, the image will be resized to fit within the specified
+/// dimensions while maintaining the original aspect ratio. The actual dimensions may be
+/// smaller than requested to preserve the aspect ratio.
+/// 
+/// If keep_aspect_ratio is false, the image will be resized to exactly the specified dimensions.
+/// 
+/// resample selects the filter ("nearest", "triangle", "catmullrom", "gaussian"
+/// or "lanczos3") and gamma handling (default Lanczos3 in sRGB).
+/// The embedded ICC color profile is kept unless strip_profile is true.
+#[tauri::command]
+async fn resize_image(
+    image_data: ImageData,
+    width: u32,
+    height: u32,
+    keep_aspect_ratio: bool,
+    resample: Option<ResampleOptions>,
+    strip_profile: Option<bool>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    // Validate input parameters
+    if width == 0 || height == 0 {
+        return Err(AppError::InvalidParameters(
+            "Width and height must be positive integers".to_string()
+        ).into());
+    }
+    
+    // Decode Base64 data
+    let decoded_data = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    // Load image from decoded data
+    let img = image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)?;
+    
+    // Calculate target dimensions
+    let (target_width, target_height) = if keep_aspect_ratio {
+        calculate_aspect_ratio_dimensions(
+            image_data.width,
+            image_data.height,
+            width,
+            height,
+        )
+    } else {
+        (width, height)
+    };
+    
+    // Resize the image with the requested filter (Lanczos3 by default)
+    let resized = resample_image(&img, target_width, target_height, &resample.unwrap_or_default());
+    
+    // Keep the source's color profile unless asked to drop it
+    let icc_profile = if strip_profile.unwrap_or(false) { None } else { extract_icc_profile(&decoded_data) };
+    
+    Ok(encode_image_data_with_profile(&resized, image_data.path, image_data.format, icc_profile)?)
+}
+
+/// Calculate dimensions that maintain aspect ratio
+/// 
+/// Given original dimensions and target dimensions, calculates the largest size
+/// that fits within the target while maintaining the original aspect ratio.
+fn calculate_aspect_ratio_dimensions(
+    original_width: u32,
+    original_height: u32,
+    target_width: u32,
+    target_height: u32,
+) -> (u32, u32) {
+    let original_ratio = original_width as f64 / original_height as f64;
+    let target_ratio = target_width as f64 / target_height as f64;
+    
+    if original_ratio > target_ratio {
+        // Width is the limiting factor
+        let new_width = target_width;
+        let new_height = (target_width as f64 / original_ratio).round() as u32;
+        (new_width, new_height.max(1))
+    } else {
+        // Height is the limiting factor
+        let new_height = target_height;
+        let new_width = (target_height as f64 * original_ratio).round() as u32;
+        (new_width.max(1), new_height)
+    }
+}
+
+/// Generate a full mipmap chain for a texture
+/// 
+/// Level 0 is the source image; every following level halves the previous
+/// dimensions (rounding down, never below 1) until a 1x1 level is reached.
+/// Downscaling is done in linear light so bright and dark details average
+/// correctly instead of darkening.
+/// 
+/// @param image_data - The source texture
+/// @returns One ImageData per mip level, largest first
+#[tauri::command]
+async fn generate_mipmaps(
+    image_data: ImageData,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<Vec<ImageData>, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let img = decode_image_data(&image_data)?;
+    
+    // Work in linear light with straight alpha
+    let mut linear = to_linear_rgba(&img);
+    
+    let mut levels = vec![encode_image_data(&img, image_data.path.clone(), image_data.format.clone())?];
+    
+    while linear.width() > 1 || linear.height() > 1 {
+        let width = (linear.width() / 2).max(1);
+        let height = (linear.height() / 2).max(1);
+        linear = image::imageops::resize(&linear, width, height, image::imageops::FilterType::Triangle);
+        
+        let level_img = from_linear_rgba(&linear);
+        
+        let path = path_with_suffix(&image_data.path, &format!("_mip{}", levels.len()));
+        levels.push(encode_image_data(&level_img, path, image_data.format.clone())?);
+    }
+    
+    println!("Generated {} mipmap levels for {}", levels.len(), image_data.path);
+    
+    Ok(levels)
+}
+
+/// Generate a responsive image set for an `<img srcset>`
+/// 
+/// Writes one file per requested width into output_dir, named
+/// "<stem>-<width>w.<ext>", keeping the aspect ratio. Images are only ever
+/// downscaled: widths larger than the source are skipped.
+/// 
+/// @param image_data - The source image
+/// @param widths - Target widths in pixels (e.g. [320, 640, 1280])
+/// @param format - Output format such as "webp" or "jpeg"
+/// @param quality - Quality for lossy formats (1-100)
+/// @param output_dir - Directory receiving the files (created if missing)
+/// @param collision_strategy - "skip", "overwrite" or "rename" when a file
+///   already exists (default "overwrite"); skipped widths are left out of the result
+/// @returns The written files with their dimensions, narrowest first
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn generate_srcset(
+    image_data: ImageData,
+    widths: Vec<u32>,
+    format: String,
+    quality: Option<u8>,
+    output_dir: String,
+    collision_strategy: Option<CollisionStrategy>,
+    limiter: State<'_, OperationLimiter>,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<Vec<SrcsetEntry>, CommandError> {
+    // Registered before waiting so a cancel while queued is seen
+    let cancel = cancel_registry.start();
+    // Heavy operation: wait for a free slot, held for every width
+    let _permit = limiter.acquire().await;
+    cancel.check()?;
+    
+    if widths.is_empty() || widths.contains(&0) {
+        return Err(AppError::InvalidParameters(
+            "Widths must be a non-empty list of positive numbers".to_string()
+        ).into());
+    }
+    
+    let img = decode_image_data(&image_data)?;
+    let (source_width, source_height) = img.dimensions();
+    
+    let mut widths: Vec<u32> = widths.into_iter().filter(|&width| width <= source_width).collect();
+    widths.sort_unstable();
+    widths.dedup();
+    
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| AppError::SaveFailed(format!("Failed to create {}: {}", output_dir, e)))?;
+    
+    let stem = Path::new(&image_data.path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image")
+        .to_string();
+    let options = ConversionOptions { quality, ..Default::default() };
+    let collision_strategy = collision_strategy.unwrap_or_default();
+    
+    let mut claimed = std::collections::HashSet::new();
+    let mut entries = Vec::with_capacity(widths.len());
+    for width in widths {
+        cancel.check()?;
+        let height = ((source_height as f64 * width as f64 / source_width as f64).round() as u32).max(1);
+        let resized = img.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
+        
+        // Encode losslessly first so convert_format applies the quality only once
+        let name = format!("{}-{}w.png", stem, width);
+        let resized_data = encode_image_data(&resized, name, ImageFormat::PNG)?;
+        let converted = convert_image_data(resized_data, &format, Some(options.clone()), None)?;
+        
+        // Paths written earlier in this call count as existing too
+        let output_path = Path::new(&output_dir).join(&converted.path);
+        let Some(output_path) = naming::resolve_collision_with(output_path, collision_strategy, |candidate| {
+            claimed.contains(candidate) || candidate.exists()
+        }) else {
+            continue;
+        };
+        claimed.insert(output_path.clone());
+        
+        let bytes = general_purpose::STANDARD
+            .decode(&converted.data)
+            .map_err(AppError::Base64Error)?;
+        let overwrite = collision_strategy == CollisionStrategy::Overwrite;
+        write_file_atomically(&output_path, &bytes, overwrite).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                AppError::FileExists(output_path.to_string_lossy().to_string())
+            } else {
+                AppError::SaveFailed(format!("Failed to write {}: {}", output_path.display(), e))
+            }
+        })?;
+        
+        entries.push(SrcsetEntry {
+            path: output_path.to_string_lossy().to_string(),
+            width,
+            height,
+        });
+    }
+    
+    println!("Generated {} srcset images for {}", entries.len(), image_data.path);
+    
+    Ok(entries)
+}
+
+/// Assemble frames into an animated GIF
+/// 
+/// Every frame must have the same dimensions. GIF stores delays in hundredths
+/// of a second, so delays are rounded to the nearest 10 ms.
+/// 
+/// @param frames - The frames in playback order, each with its delay
+/// @param loop_count - How many times the animation repeats (default: forever)
+/// @returns ImageData with the animation, named "animation.gif"
+#[tauri::command]
+async fn create_gif(
+    frames: Vec<GifFrame>,
+    loop_count: Option<u16>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    if frames.is_empty() {
+        return Err(AppError::InvalidParameters(
+            "An animation needs at least one frame".to_string()
+        ).into());
+    }
+    
+    let mut decoded = Vec::with_capacity(frames.len());
+    for frame in &frames {
+        let bytes = general_purpose::STANDARD
+            .decode(&frame.data)
+            .map_err(AppError::Base64Error)?;
+        let img = image::load_from_memory(&bytes).map_err(AppError::ImageError)?;
+        decoded.push((img.to_rgba8(), frame.delay_ms));
+    }
+    
+    let (width, height) = decoded[0].0.dimensions();
+    if decoded.iter().any(|(img, _)| img.dimensions() != (width, height)) {
+        return Err(AppError::InvalidParameters(
+            "All frames must have the same dimensions".to_string()
+        ).into());
+    }
+    
+    let has_alpha = decoded.iter().any(|(img, _)| img.pixels().any(|p| p.0[3] < 255));
+    
+    let mut output_buffer = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut output_buffer);
+        let repeat = loop_count.map(Repeat::Finite).unwrap_or(Repeat::Infinite);
+        encoder.set_repeat(repeat).map_err(AppError::ImageError)?;
+        
+        let gif_frames = decoded.into_iter().map(|(img, delay_ms)| {
+            image::Frame::from_parts(img, 0, 0, image::Delay::from_numer_denom_ms(delay_ms, 1))
+        });
+        encoder.encode_frames(gif_frames).map_err(AppError::ImageError)?;
+    }
+    
+    println!("Created {}x{} GIF with {} frames", width, height, frames.len());
+    
+    Ok(ImageData {
+        path: "animation.gif".to_string(),
+        width,
+        height,
+        format: ImageFormat::GIF,
+        data: general_purpose::STANDARD.encode(&output_buffer),
+        has_alpha,
+        display_data: None,
+    })
+}
+
+/// Create a multi-resolution ICO file, as used for application icons
+/// 
+/// The source is scaled to fit each requested size, centered on a transparent
+/// square canvas, and all sizes are packed into one ICO file as PNG images.
+/// Sizes are sorted and duplicates removed.
+/// 
+/// @param image_data - The source image, ideally square and at least 256 pixels
+/// @param sizes - Edge lengths of the icon images (1-256, e.g. [16, 32, 48, 256])
+/// @returns ImageData with the ICO file, sized like its largest image
+#[tauri::command]
+async fn create_icon(
+    image_data: ImageData,
+    sizes: Vec<u32>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    use image::codecs::ico::{IcoEncoder, IcoFrame};
+    
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    if sizes.is_empty() || sizes.iter().any(|size| !(1..=256).contains(size)) {
+        return Err(AppError::InvalidParameters(
+            "Icon sizes must be a non-empty list of values from 1 to 256".to_string()
+        ).into());
+    }
+    let mut sizes = sizes;
+    sizes.sort_unstable();
+    sizes.dedup();
+    
+    let img = decode_image_data(&image_data)?;
+    
+    let mut frames = Vec::with_capacity(sizes.len());
+    for &size in &sizes {
+        let scaled = thumbnail_image(&img, size, &ResampleOptions::balanced()).to_rgba8();
+        let mut canvas = image::RgbaImage::new(size, size);
+        image::imageops::overlay(
+            &mut canvas,
+            &scaled,
+            ((size - scaled.width()) / 2) as i64,
+            ((size - scaled.height()) / 2) as i64,
+        );
+        
+        let frame = IcoFrame::as_png(canvas.as_raw(), size, size, image::ExtendedColorType::Rgba8)
+            .map_err(AppError::ImageError)?;
+        frames.push(frame);
+    }
+    
+    let mut output_buffer = Vec::new();
+    IcoEncoder::new(&mut output_buffer)
+        .encode_images(&frames)
+        .map_err(AppError::ImageError)?;
+    
+    let largest = *sizes.last().unwrap_or(&1);
+    
+    println!("Created icon with sizes {:?}", sizes);
+    
+    Ok(ImageData {
+        path: update_file_extension(&image_data.path, &ImageFormat::ICO),
+        width: largest,
+        height: largest,
+        format: ImageFormat::ICO,
+        data: general_purpose::STANDARD.encode(&output_buffer),
+        has_alpha: true,
+        display_data: None,
+    })
+}
+
+/// Resize an image by a scale factor
+/// 
+/// Both dimensions are multiplied by scale (0.5 = half size, 2.0 = double) and
+/// rounded, with a minimum of 1 pixel.
+/// 
+/// @param image_data - The image to resize
+/// @param scale - Scale factor, must be greater than 0
+/// @param resample - Filter and gamma handling (default Lanczos3 in sRGB)
+/// @returns New ImageData with the scaled dimensions
+#[tauri::command]
+async fn resize_image_scale(
+    image_data: ImageData,
+    scale: f32,
+    resample: Option<ResampleOptions>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    if !scale.is_finite() || scale <= 0.0 {
+        return Err(AppError::InvalidParameters(
+            "Scale must be a positive number".to_string()
+        ).into());
+    }
+    
+    let scale_dimension = |size: u32| ((size as f64 * scale as f64).round() as u32).max(1);
+    let width = scale_dimension(image_data.width);
+    let height = scale_dimension(image_data.height);
+    
+    resize_image(image_data, width, height, false, resample, None, limiter).await
+}
+
+/// Resize an image to exact dimensions using the given resample options
+fn resample_image(
+    img: &DynamicImage,
+    width: u32,
+    height: u32,
+    options: &ResampleOptions,
+) -> DynamicImage {
+    let filter = options.filter.to_filter_type();
+    
+    if options.gamma_correct {
+        let linear = image::imageops::resize(&to_linear_rgba(img), width, height, filter);
+        from_linear_rgba(&linear)
+    } else {
+        img.resize_exact(width, height, filter)
+    }
+}
+
+/// Scale an image to fit within size x size, keeping its aspect ratio
+fn thumbnail_image(img: &DynamicImage, size: u32, options: &ResampleOptions) -> DynamicImage {
+    let (width, height) = calculate_aspect_ratio_dimensions(img.width(), img.height(), size, size);
+    resample_image(img, width, height, options)
+}
+
+/// Convert an image to linear light RGBA with straight alpha
+fn to_linear_rgba(img: &DynamicImage) -> image::Rgba32FImage {
+    image::ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+        let p = img.get_pixel(x, y).0;
+        Rgba([
+            srgb_to_linear(p[0]),
+            srgb_to_linear(p[1]),
+            srgb_to_linear(p[2]),
+            p[3] as f32 / 255.0,
+        ])
+    })
+}
+
+/// Convert linear light RGBA back to an 8-bit sRGB image
+fn from_linear_rgba(linear: &image::Rgba32FImage) -> DynamicImage {
+    DynamicImage::ImageRgba8(image::ImageBuffer::from_fn(linear.width(), linear.height(), |x, y| {
+        let p = linear.get_pixel(x, y).0;
+        Rgba([
+            linear_to_srgb(p[0]),
+            linear_to_srgb(p[1]),
+            linear_to_srgb(p[2]),
+            (p[3] * 255.0).round().clamp(0.0, 255.0) as u8,
+        ])
+    }))
+}
+
+/// Get the resample options for a named preset ("fast", "balanced" or "best")
+#[tauri::command]
+async fn get_resample_preset(name: String) -> Result<ResampleOptions, CommandError> {
+    ResampleOptions::preset(&name)
+        .ok_or_else(|| AppError::InvalidParameters(
+            format!("Unknown resample preset '{}'", name)
+        ).into())
+}
+
+/// Convert an 8-bit sRGB channel value to linear light (0.0-1.0)
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a linear light value (0.0-1.0) to an 8-bit sRGB channel value
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let encoded = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+/// Convert image to a different format
+/// 
+/// Supports conversion between all supported formats (PNG, JPEG, GIF, BMP, WEBP, TIFF, ICO, AVIF)
+/// For lossy formats (JPEG, WEBP, AVIF), quality parameter can be specified (1-100)
+/// 
+/// Note: SVG and HEIC formats are not supported for conversion
+/// 
+/// The returned path is output_path when given, used as-is; otherwise it is
+/// the source path with the extension swapped for the target format's.
+#[tauri::command]
+async fn convert_format(
+    image_data: ImageData,
+    target_format: String,
+    options: Option<ConversionOptions>,
+    output_path: Option<String>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    convert_image_data(image_data, &target_format, options, output_path)
+}
+
+/// The work of convert_format, for callers already holding a limiter permit
+fn convert_image_data(
+    image_data: ImageData,
+    target_format: &str,
+    options: Option<ConversionOptions>,
+    output_path: Option<String>,
+) -> Result<ImageData, CommandError> {
+    // Parse target format
+    let target_format_enum = match target_format.to_uppercase().as_str() {
+        "PNG" => ImageFormat::PNG,
+        "JPEG" | "JPG" => ImageFormat::JPEG,
+        "GIF" => ImageFormat::GIF,
+        "BMP" => ImageFormat::BMP,
+        "WEBP" => ImageFormat::WEBP,
+        "TIFF" | "TIF" => ImageFormat::TIFF,
+        "ICO" => ImageFormat::ICO,
+        "AVIF" => ImageFormat::AVIF,
+        _ => {
+            return Err(AppError::UnsupportedFormat(
+                format!("Unsupported target format: {}", target_format)
+            ).into());
+        }
+    };
+    
+    // Validate that we can convert to this format
+    if target_format_enum == ImageFormat::SVG || target_format_enum == ImageFormat::HEIC {
+        return Err(AppError::UnsupportedFormat(
+            format!("Cannot convert to {} format", target_format_enum)
+        ).into());
+    }
+    
+    // Validate quality parameter if provided
+    if let Some(ref opts) = options {
+        if let Some(quality) = opts.quality {
+            if quality < 1 || quality > 100 {
+                return Err(AppError::InvalidParameters(
+                    "Quality parameter must be between 1 and 100".to_string()
+                ).into());
+            }
+        }
+        // There is no lossy WebP encoder to honor an explicit request for one
+        if target_format_enum == ImageFormat::WEBP && opts.webp_lossless == Some(false) {
+            return Err(AppError::InvalidParameters(
+                "Lossy WEBP output is not supported; omit webp_lossless or set it to true".to_string()
+            ).into());
+        }
+        // The JPEG encoder always stores full-resolution chroma
+        let subsampled = matches!(opts.jpeg_subsampling, Some(JpegSubsampling::S422 | JpegSubsampling::S420));
+        if target_format_enum == ImageFormat::JPEG && subsampled {
+            return Err(AppError::InvalidParameters(
+                "JPEG output only supports 4:4:4 chroma subsampling".to_string()
+            ).into());
+        }
+    }
+    
+    // Decode Base64 data
+    let decoded_data = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    // Load image from decoded data
+    let mut img = image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)?;
+    
+    // Keep the source's color profile unless asked to drop it
+    let strip_profile = options.as_ref().and_then(|o| o.strip_profile).unwrap_or(false);
+    let icc_profile = if strip_profile { None } else { extract_icc_profile(&decoded_data) };
+    
+    // Carry EXIF over according to the metadata policy
+    let metadata_policy = options.as_ref().and_then(|o| o.metadata_policy).unwrap_or_default();
+    let exif = match metadata_policy {
+        MetadataPolicy::Preserve => extract_exif(&decoded_data),
+        MetadataPolicy::Strip => None,
+        MetadataPolicy::StripGpsOnly => extract_exif(&decoded_data)
+            .and_then(|exif| camera::strip_gps(&exif)),
+    };
+    
+    // Enforce the target format's dimension limits
+    if let Some(max_dimension) = target_format_enum.max_dimension() {
+        if img.width() > max_dimension || img.height() > max_dimension {
+            let downscale = options
+                .as_ref()
+                .and_then(|o| o.downscale_to_fit)
+                .unwrap_or(false);
+            
+            if !downscale {
+                return Err(AppError::InvalidParameters(
+                    format!(
+                        "{} images cannot exceed {}x{} pixels (image is {}x{})",
+                        target_format_enum, max_dimension, max_dimension, img.width(), img.height()
+                    )
+                ).into());
+            }
+            
+            println!(
+                "Warning: downscaling {}x{} image to fit the {}x{} {} limit",
+                img.width(), img.height(), max_dimension, max_dimension, target_format_enum
+            );
+            img = img.resize(max_dimension, max_dimension, image::imageops::FilterType::Lanczos3);
+        }
+    }
+    
+    // Convert to target format
+    let mut output_buffer = Vec::new();
+    let img_format = target_format_enum.to_image_format()
+        .ok_or_else(|| AppError::UnsupportedFormat(
+            format!("Cannot encode to {} format", target_format_enum)
+        ))?;
+    
+    // Handle quality parameter for lossy formats
+    match target_format_enum {
+        ImageFormat::JPEG => {
+            let quality = options
+                .as_ref()
+                .and_then(|o| o.quality)
+                .unwrap_or(90); // Default quality for JPEG
+            
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
+                &mut output_buffer,
+                quality,
+            );
+            attach_metadata(&mut encoder, icc_profile, exif)?;
+            encoder.encode_image(&img)
+                .map_err(AppError::ImageError)?;
+        }
+        ImageFormat::TIFF => {
+            match options.as_ref().and_then(|o| o.tiff_compression) {
+                Some(compression) => {
+                    write_tiff(&img, &mut output_buffer, compression, icc_profile.as_deref(), exif.as_deref())?
+                }
+                None => write_image_with_metadata(&img, &mut output_buffer, img_format, icc_profile, exif)?,
+            }
+        }
+        ImageFormat::WEBP => {
+            // The image crate's WebP encoder is lossless only, so the quality
+            // parameter has no effect (webp_lossless: false is rejected above)
+            write_image_with_metadata(&img, &mut output_buffer, img_format, icc_profile, exif)?;
+        }
+        ImageFormat::AVIF => {
+            // Note: AVIF encoding with quality parameter may not be fully supported
+            // We'll use the default encoding
+            write_image_with_metadata(&img, &mut output_buffer, img_format, icc_profile, exif)?;
+        }
+        _ => {
+            // For lossless formats, just encode normally
+            write_image_with_metadata(&img, &mut output_buffer, img_format, icc_profile, exif)?;
+        }
+    }
+    
+    // Encode to Base64
+    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    
+    // Detect alpha channel in converted image
+    let has_alpha = detect_alpha_channel(&img);
+    
+    // Use the caller's destination, or swap the extension to match the new format
+    let new_path = output_path
+        .unwrap_or_else(|| update_file_extension(&image_data.path, &target_format_enum));
+    
+    // Return new ImageData with updated format
+    Ok(ImageData {
+        path: new_path,
+        width: img.width(),
+        height: img.height(),
+        format: target_format_enum,
+        data: base64_data,
+        has_alpha,
+        display_data: None,
+    })
+}
+
+/// Update file path extension to match the new format
+fn update_file_extension(path: &str, format: &ImageFormat) -> String {
+    let path_obj = Path::new(path);
+    let stem = path_obj.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+    
+    let extension = match format {
+        ImageFormat::PNG => "png",
+        ImageFormat::JPEG => "jpg",
+        ImageFormat::GIF => "gif",
+        ImageFormat::BMP => "bmp",
+        ImageFormat::WEBP => "webp",
+        ImageFormat::TIFF => "tiff",
+        ImageFormat::ICO => "ico",
+        ImageFormat::AVIF => "avif",
+        ImageFormat::SVG => "svg",
+        ImageFormat::HEIC => "heic",
+    };
+    
+    if let Some(parent) = path_obj.parent() {
+        parent.join(format!("{}.{}", stem, extension))
+            .to_string_lossy()
+            .to_string()
+    } else {
+        format!("{}.{}", stem, extension)
+    }
+}
+
+/// Append a suffix to the file stem of a path, keeping its directory and extension
+fn path_with_suffix(path: &str, suffix: &str) -> String {
+    let path_obj = Path::new(path);
+    let stem = path_obj.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("image");
+    
+    let file_name = match path_obj.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}{}.{}", stem, suffix, extension),
+        None => format!("{}{}", stem, suffix),
+    };
+    
+    match path_obj.parent() {
+        Some(parent) => parent.join(file_name).to_string_lossy().to_string(),
+        None => file_name,
+    }
+}
+
+/// Constrain a crop region to the image boundaries
+/// 
+/// The top-left corner is moved inside the image and the size is reduced to
+/// what remains from there, never below 1x1. Returns (x, y, width, height).
+fn constrain_crop_region(
+    image_width: u32,
+    image_height: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> (u32, u32, u32, u32) {
+    let constrained_x = x.min(image_width.saturating_sub(1));
+    let constrained_y = y.min(image_height.saturating_sub(1));
+    
+    // Calculate maximum available width and height from the constrained position
+    let max_width = image_width.saturating_sub(constrained_x);
+    let max_height = image_height.saturating_sub(constrained_y);
+    
+    (
+        constrained_x,
+        constrained_y,
+        width.min(max_width).max(1),
+        height.min(max_height).max(1),
+    )
+}
+
+/// Crop an image to the specified region
+/// 
+/// Extracts a rectangular region from the image. If the crop region extends beyond
+/// the image boundaries, it will be automatically constrained to fit within the image.
+/// 
+/// @param image_data - The image to crop
+/// @param x - X coordinate of the top-left corner of the crop region
+/// @param y - Y coordinate of the top-left corner of the crop region
+/// @param width - Width of the crop region
+/// @param height - Height of the crop region
+/// @param strip_profile - Drop the embedded ICC color profile instead of keeping it
+/// @returns New ImageData containing only the cropped region
+#[tauri::command]
+async fn crop_image(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    strip_profile: Option<bool>,
+) -> Result<ImageData, CommandError> {
+    // Validate input parameters
+    if width == 0 || height == 0 {
+        return Err(AppError::InvalidParameters(
+            "Width and height must be positive integers".to_string()
+        ).into());
+    }
+    
+    // Constrain crop region to image boundaries
+    let (constrained_x, constrained_y, constrained_width, constrained_height) =
+        constrain_crop_region(image_data.width, image_data.height, x, y, width, height);
+    
+    // Decode Base64 data
+    let decoded_data = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    // Load image from decoded data
+    let img = image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)?;
+    
+    // Crop the image
+    let cropped = img.crop_imm(constrained_x, constrained_y, constrained_width, constrained_height);
+    
+    // Keep the source's color profile unless asked to drop it
+    let icc_profile = if strip_profile.unwrap_or(false) { None } else { extract_icc_profile(&decoded_data) };
+    
+    Ok(encode_image_data_with_profile(&cropped, image_data.path, image_data.format, icc_profile)?)
+}
+
+/// Crop an image to a region given as fractions of its size
+/// 
+/// Useful when the selection was made on a preview of a different scale. The
+/// fractions are multiplied by the real image dimensions and the resulting
+/// region is constrained the same way as in crop_image.
+/// 
+/// @param image_data - The image to crop
+/// @param x - Left edge of the crop region (0.0-1.0)
+/// @param y - Top edge of the crop region (0.0-1.0)
+/// @param width - Width of the crop region (0.0-1.0)
+/// @param height - Height of the crop region (0.0-1.0)
+/// @returns New ImageData containing only the cropped region
+#[tauri::command]
+async fn crop_image_normalized(
+    image_data: ImageData,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<ImageData, CommandError> {
+    if [x, y, width, height].iter().any(|value| !(0.0..=1.0).contains(value)) {
+        return Err(AppError::InvalidParameters(
+            "Normalized crop values must be between 0.0 and 1.0".to_string()
+        ).into());
+    }
+    
+    let to_pixels = |fraction: f32, size: u32| (fraction * size as f32).round() as u32;
+    let pixel_x = to_pixels(x, image_data.width);
+    let pixel_y = to_pixels(y, image_data.height);
+    let pixel_width = to_pixels(width, image_data.width);
+    let pixel_height = to_pixels(height, image_data.height);
+    
+    crop_image(image_data, pixel_x, pixel_y, pixel_width, pixel_height, None).await
+}
+
+/// Crop an image to a region locked to an aspect ratio
+/// 
+/// The height is derived from the requested width so that width:height equals
+/// aspect_w:aspect_h exactly; the width is rounded down to the nearest size for
+/// which that is possible with whole pixels. If the region would extend beyond
+/// the image, it is shrunk (keeping the ratio) to fit from the given position.
+/// 
+/// @param image_data - The image to crop
+/// @param x - X coordinate of the top-left corner of the crop region
+/// @param y - Y coordinate of the top-left corner of the crop region
+/// @param target_width - Requested width of the crop region
+/// @param aspect_w - Width part of the aspect ratio (e.g. 16)
+/// @param aspect_h - Height part of the aspect ratio (e.g. 9)
+/// @returns New ImageData containing only the cropped region
+#[tauri::command]
+async fn crop_image_aspect(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    target_width: u32,
+    aspect_w: u32,
+    aspect_h: u32,
+) -> Result<ImageData, CommandError> {
+    if aspect_w == 0 || aspect_h == 0 {
+        return Err(AppError::InvalidParameters(
+            "Aspect ratio values must be positive integers".to_string()
+        ).into());
+    }
+    
+    // Reduce the ratio so the region can be as close to the requested width as possible
+    let (mut a, mut b) = (aspect_w, aspect_h);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let (unit_w, unit_h) = (aspect_w / a, aspect_h / a);
+    
+    // Constrain the position like crop_image, then fit the region in the space left
+    let constrained_x = x.min(image_data.width.saturating_sub(1));
+    let constrained_y = y.min(image_data.height.saturating_sub(1));
+    let max_width = image_data.width.saturating_sub(constrained_x);
+    let max_height = image_data.height.saturating_sub(constrained_y);
+    
+    let units = (target_width / unit_w)
+        .min(max_width / unit_w)
+        .min(max_height / unit_h);
+    if units == 0 {
+        return Err(AppError::InvalidParameters(
+            format!(
+                "No {}:{} region of width {} fits at {},{} in a {}x{} image",
+                aspect_w, aspect_h, target_width, x, y, image_data.width, image_data.height
+            )
+        ).into());
+    }
+    
+    crop_image(image_data, constrained_x, constrained_y, units * unit_w, units * unit_h, None).await
+}
+
+/// Make pixels outside a rounded rectangle transparent
+/// 
+/// Each corner is cut along a quarter ellipse with radii (radius_x, radius_y).
+/// Edge pixels keep partial alpha according to their distance from the curve
+/// so the outline is anti-aliased. Radii of half the image size give an ellipse.
+fn apply_rounded_mask(img: &mut image::RgbaImage, radius_x: f32, radius_y: f32) {
+    if radius_x <= 0.0 || radius_y <= 0.0 {
+        return;
+    }
+    
+    let width = img.width() as f32;
+    let height = img.height() as f32;
+    
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        // Distance of the pixel center past the straight edges, in each corner region
+        let px = x as f32 + 0.5;
+        let py = y as f32 + 0.5;
+        let dx = (radius_x - px).max(px - (width - radius_x)).max(0.0);
+        let dy = (radius_y - py).max(py - (height - radius_y)).max(0.0);
+        if dx == 0.0 || dy == 0.0 {
+            continue;
+        }
+        
+        // Normalized distance from the corner's ellipse center, 1.0 on the curve
+        let distance = ((dx / radius_x).powi(2) + (dy / radius_y).powi(2)).sqrt();
+        let coverage = ((1.0 - distance) * radius_x.min(radius_y) + 0.5).clamp(0.0, 1.0);
+        pixel.0[3] = (pixel.0[3] as f32 * coverage).round() as u8;
+    }
+}
+
+/// Crop a region and mask it to a rounded shape
+/// 
+/// The source format is kept if it supports alpha, otherwise the result is PNG.
+fn crop_with_rounded_mask(
+    image_data: ImageData,
+    region: (u32, u32, u32, u32),
+    radius: impl Fn(u32, u32) -> (f32, f32),
+) -> Result<ImageData, AppError> {
+    let (x, y, width, height) = region;
+    if width == 0 || height == 0 {
+        return Err(AppError::InvalidParameters(
+            "Width and height must be positive integers".to_string()
+        ));
+    }
+    
+    let (x, y, width, height) =
+        constrain_crop_region(image_data.width, image_data.height, x, y, width, height);
+    
+    let img = decode_image_data(&image_data)?;
+    let mut cropped = img.crop_imm(x, y, width, height).to_rgba8();
+    
+    let (radius_x, radius_y) = radius(width, height);
+    apply_rounded_mask(&mut cropped, radius_x, radius_y);
+    
+    encode_with_alpha(&match_grayscale_layout(cropped, &img), image_data.path, image_data.format)
+}
+
+/// Encode a result that relies on transparency
+/// 
+/// Keeps the requested format if it supports alpha, otherwise encodes as PNG
+/// and updates the path's extension to match.
+fn encode_with_alpha(img: &DynamicImage, path: String, format: ImageFormat) -> Result<ImageData, AppError> {
+    if format.capabilities().supports_alpha {
+        encode_image_data(img, path, format)
+    } else {
+        let path = update_file_extension(&path, &ImageFormat::PNG);
+        encode_image_data(img, path, ImageFormat::PNG)
+    }
+}
+
+/// Fraction of each pixel covered by a polygon, using 4x4 samples per pixel
+/// 
+/// Sample rows are filled between pairs of edge crossings (even-odd rule), so
+/// self-intersecting polygons leave their overlapping parts empty.
+fn polygon_coverage(vertices: &[(f32, f32)], width: u32, height: u32) -> Vec<f32> {
+    const SAMPLES: usize = 4;
+    let mut hits = vec![0u32; width as usize * height as usize];
+    
+    for sample_row in 0..height as usize * SAMPLES {
+        let sy = (sample_row as f32 + 0.5) / SAMPLES as f32;
+        
+        // x coordinates where this sample row crosses a polygon edge
+        let mut crossings: Vec<f32> = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .filter(|((_, y1), (_, y2))| (*y1 <= sy) != (*y2 <= sy))
+            .map(|((x1, y1), (x2, y2))| x1 + (sy - y1) / (y2 - y1) * (x2 - x1))
+            .collect();
+        crossings.sort_by(f32::total_cmp);
+        
+        let row_start = sample_row / SAMPLES * width as usize;
+        for span in crossings.chunks_exact(2) {
+            let (start, end) = (span[0], span[1]);
+            let first = (start.floor().max(0.0) as u32).min(width);
+            let last = (end.ceil().max(0.0) as u32).min(width);
+            
+            for x in first..last {
+                for i in 0..SAMPLES {
+                    let sx = x as f32 + (i as f32 + 0.5) / SAMPLES as f32;
+                    if sx >= start && sx < end {
+                        hits[row_start + x as usize] += 1;
+                    }
+                }
+            }
+        }
+    }
+    
+    hits.iter().map(|&count| count as f32 / (SAMPLES * SAMPLES) as f32).collect()
+}
+
+/// Cut out a polygon from an image
+/// 
+/// Pixels outside the polygon become transparent and pixels on its edges keep
+/// partial alpha for anti-aliasing. Sources without alpha support (JPEG) are
+/// returned as PNG.
+/// 
+/// @param image_data - The image to cut from
+/// @param vertices - Polygon corners as [x, y] pixel coordinates, in order
+/// @param crop_to_bounds - Crop the result to the polygon's bounding box (default false)
+/// @returns New ImageData with everything outside the polygon transparent
+#[tauri::command]
+async fn crop_polygon(
+    image_data: ImageData,
+    vertices: Vec<(f32, f32)>,
+    crop_to_bounds: Option<bool>,
+) -> Result<ImageData, CommandError> {
+    if vertices.len() < 3 {
+        return Err(AppError::InvalidParameters(
+            "A polygon needs at least 3 vertices".to_string()
+        ).into());
+    }
+    
+    if vertices.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+        return Err(AppError::InvalidParameters(
+            "Polygon vertices must be finite numbers".to_string()
+        ).into());
+    }
+    
+    let img = decode_image_data(&image_data)?;
+    let mut rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    
+    let coverage = polygon_coverage(&vertices, width, height);
+    for (pixel, amount) in rgba.pixels_mut().zip(&coverage) {
+        pixel.0[3] = (pixel.0[3] as f32 * amount).round() as u8;
+    }
+    
+    if crop_to_bounds.unwrap_or(false) {
+        let min_x = vertices.iter().map(|v| v.0).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let min_y = vertices.iter().map(|v| v.1).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let max_x = (vertices.iter().map(|v| v.0).fold(f32::MIN, f32::max).ceil().max(0.0) as u32).min(width);
+        let max_y = (vertices.iter().map(|v| v.1).fold(f32::MIN, f32::max).ceil().max(0.0) as u32).min(height);
+        
+        if min_x >= max_x || min_y >= max_y {
+            return Err(AppError::InvalidParameters(
+                "Polygon lies outside the image".to_string()
+            ).into());
+        }
+        
+        rgba = image::imageops::crop_imm(&rgba, min_x, min_y, max_x - min_x, max_y - min_y).to_image();
+    }
+    
+    Ok(encode_with_alpha(&match_grayscale_layout(rgba, &img), image_data.path, image_data.format)?)
+}
+
+/// Crop an image to a rectangle with rounded corners
+/// 
+/// Pixels outside the rounded corners become transparent. Sources without alpha
+/// support (JPEG) are returned as PNG.
+/// 
+/// @param image_data - The image to crop
+/// @param x - X coordinate of the top-left corner of the crop region
+/// @param y - Y coordinate of the top-left corner of the crop region
+/// @param width - Width of the crop region
+/// @param height - Height of the crop region
+/// @param radius - Corner radius in pixels, limited to half the shorter side
+/// @returns New ImageData containing the rounded crop
+#[tauri::command]
+async fn crop_image_rounded(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    radius: u32,
+) -> Result<ImageData, CommandError> {
+    Ok(crop_with_rounded_mask(image_data, (x, y, width, height), |width, height| {
+        let radius = radius.min(width / 2).min(height / 2) as f32;
+        (radius, radius)
+    })?)
+}
+
+/// Crop an image to the circle or ellipse inscribed in a region
+/// 
+/// Pixels outside the ellipse become transparent; a square region gives a
+/// circle. Sources without alpha support (JPEG) are returned as PNG.
+/// 
+/// @param image_data - The image to crop
+/// @param x - X coordinate of the top-left corner of the crop region
+/// @param y - Y coordinate of the top-left corner of the crop region
+/// @param width - Width of the crop region
+/// @param height - Height of the crop region
+/// @returns New ImageData containing the elliptical crop
+#[tauri::command]
+async fn crop_image_circle(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<ImageData, CommandError> {
+    Ok(crop_with_rounded_mask(image_data, (x, y, width, height), |width, height| {
+        (width as f32 / 2.0, height as f32 / 2.0)
+    })?)
+}
+
+/// Pixelate a region of an image, e.g. to redact faces or text
+///
+/// The region is constrained to the image like in crop_image and divided into
+/// block_size x block_size blocks starting at its top-left corner, each filled
+/// with its average color. Blocks at the right and bottom edges are smaller
+/// if the region doesn't divide evenly.
+///
+/// @param image_data - The image to edit
+/// @param x - X coordinate of the top-left corner of the region
+/// @param y - Y coordinate of the top-left corner of the region
+/// @param width - Width of the region
+/// @param height - Height of the region
+/// @param block_size - Side of each mosaic block in pixels
+/// @returns New ImageData with the region pixelated
+#[tauri::command]
+async fn pixelate_region(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    block_size: u32,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<ImageData, CommandError> {
+    if width == 0 || height == 0 {
+        return Err(AppError::InvalidParameters(
+            "Width and height must be positive integers".to_string()
+        ).into());
+    }
+    if block_size == 0 {
+        return Err(AppError::InvalidParameters("Block size must be at least 1".to_string()).into());
+    }
+
+    let img = decode_image_data(&image_data)?;
+    let mut rgba = img.to_rgba8();
+    let (x, y, width, height) = constrain_crop_region(rgba.width(), rgba.height(), x, y, width, height);
+
+    let cancel = cancel_registry.start();
+    for block_y in (y..y + height).step_by(block_size as usize) {
+        cancel.check()?;
+        for block_x in (x..x + width).step_by(block_size as usize) {
+            let block_right = (block_x + block_size).min(x + width);
+            let block_bottom = (block_y + block_size).min(y + height);
+
+            let mut sums = [0u64; 4];
+            for py in block_y..block_bottom {
+                for px in block_x..block_right {
+                    for (sum, &value) in sums.iter_mut().zip(rgba.get_pixel(px, py).0.iter()) {
+                        *sum += value as u64;
+                    }
+                }
+            }
+            let count = ((block_right - block_x) * (block_bottom - block_y)) as u64;
+            let average = Rgba(sums.map(|sum| ((sum + count / 2) / count) as u8));
+
+            for py in block_y..block_bottom {
+                for px in block_x..block_right {
+                    rgba.put_pixel(px, py, average);
+                }
+            }
+        }
+    }
+
+    let result_img = match_grayscale_layout(rgba, &img);
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Split a wide panorama into overlapping vertical strips
+/// 
+/// Tiles span the full height and are tile_width wide, each starting
+/// tile_width - overlap pixels after the previous one. The last tile is
+/// narrower if the remaining width doesn't fill a whole tile.
+/// 
+/// @param image_data - The panorama to split
+/// @param tile_width - Width of each tile in pixels
+/// @param overlap - Pixels each tile shares with the next (less than tile_width)
+/// @returns Tiles from left to right, named `<name>_tile<index>`
+#[tauri::command]
+async fn split_panorama(
+    image_data: ImageData,
+    tile_width: u32,
+    overlap: u32,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<Vec<ImageData>, CommandError> {
+    if tile_width == 0 || overlap >= tile_width {
+        return Err(AppError::InvalidParameters(
+            "Tile width must be positive and larger than the overlap".to_string()
+        ).into());
+    }
+    
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let img = decode_image_data(&image_data)?;
+    let (width, height) = img.dimensions();
+    let step = tile_width - overlap;
+    
+    let mut tiles = Vec::new();
+    let mut x = 0;
+    loop {
+        let tile = img.crop_imm(x, 0, tile_width.min(width - x), height);
+        let path = path_with_suffix(&image_data.path, &format!("_tile{}", tiles.len()));
+        tiles.push(encode_image_data(&tile, path, image_data.format.clone())?);
+        
+        if x + tile_width >= width {
+            break;
+        }
+        x += step;
+    }
+    
+    Ok(tiles)
+}
+
+/// Automatically crop an image to its opaque content
+/// 
+/// Finds the tight bounding box of pixels whose alpha is above alpha_threshold and
+/// crops to it. If border_tolerance is provided, pixels matching the top-left corner
+/// color within that per-channel tolerance are also treated as border, which trims
+/// uniform-color margins on opaque images.
+/// 
+/// @param image_data - The image to crop
+/// @param alpha_threshold - Pixels with alpha at or below this value are border (default 0)
+/// @param border_tolerance - Also trim a uniform-color border with this tolerance (0-255)
+/// @returns New ImageData cropped to the content
+#[tauri::command]
+async fn autocrop_opaque(
+    image_data: ImageData,
+    alpha_threshold: Option<u8>,
+    border_tolerance: Option<u8>,
+) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    let rgba_img = img.to_rgba8();
+    let alpha_threshold = alpha_threshold.unwrap_or(0);
+    let border_color = *rgba_img.get_pixel(0, 0);
+    
+    let bounds = content_bounds(&rgba_img, |pixel| {
+        if pixel.0[3] <= alpha_threshold {
+            return false;
+        }
+        match border_tolerance {
+            Some(tolerance) => !colors_within_tolerance(pixel, &border_color, tolerance),
+            None => true,
+        }
+    });
+    
+    let (x, y, width, height) = bounds.ok_or_else(|| AppError::InvalidParameters(
+        "Image has no content to crop to".to_string()
+    ))?;
+    
+    let cropped = img.crop_imm(x, y, width, height);
+    
+    Ok(encode_image_data(&cropped, image_data.path, image_data.format)?)
+}
+
+/// Crop away black letterbox and pillarbox bars
+/// 
+/// Counts the near-black rows at the top and bottom and the near-black columns
+/// at the left and right. Bars are removed symmetrically: only as many rows
+/// (or columns) as both opposite sides have are trimmed, so dark content that
+/// touches a single edge is kept.
+/// 
+/// @param image_data - The image to trim
+/// @param tolerance - Highest channel value still counted as black (default 16)
+/// @returns New ImageData without the bars
+#[tauri::command]
+async fn remove_letterbox(
+    image_data: ImageData,
+    tolerance: Option<u8>,
+) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    let rgba_img = img.to_rgba8();
+    let (width, height) = rgba_img.dimensions();
+    let tolerance = tolerance.unwrap_or(16);
+    let black = Rgba([0, 0, 0, 255]);
+    
+    let dark_row = |y: u32| (0..width).all(|x| colors_within_tolerance(rgba_img.get_pixel(x, y), &black, tolerance));
+    let dark_column = |x: u32| (0..height).all(|y| colors_within_tolerance(rgba_img.get_pixel(x, y), &black, tolerance));
+    
+    let top = (0..height).take_while(|&y| dark_row(y)).count() as u32;
+    if top == height {
+        return Err(AppError::InvalidParameters(
+            "Image has no content outside the black bars".to_string()
+        ).into());
+    }
+    let bottom = (0..height).rev().take_while(|&y| dark_row(y)).count() as u32;
+    let left = (0..width).take_while(|&x| dark_column(x)).count() as u32;
+    let right = (0..width).rev().take_while(|&x| dark_column(x)).count() as u32;
+    
+    let bar_height = top.min(bottom);
+    let bar_width = left.min(right);
+    
+    let cropped = img.crop_imm(bar_width, bar_height, width - 2 * bar_width, height - 2 * bar_height);
+    
+    Ok(encode_image_data(&cropped, image_data.path, image_data.format)?)
+}
+
+/// Combine a before and an after image into one comparison image
+/// 
+/// The images are placed side by side ("horizontal") or stacked ("vertical")
+/// with a white divider of gap pixels between them. If their heights (side by
+/// side) or widths (stacked) differ, the smaller image is scaled up to match,
+/// keeping its aspect ratio.
+/// 
+/// @param before - The original image, placed left or on top
+/// @param after - The edited image, placed right or below
+/// @param orientation - "horizontal" or "vertical"
+/// @param gap - Width of the divider in pixels
+/// @returns New ImageData in the after image's format
+#[tauri::command]
+async fn create_comparison(
+    before: ImageData,
+    after: ImageData,
+    orientation: ComparisonOrientation,
+    gap: u32,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let before_img = decode_image_data(&before)?;
+    let after_img = decode_image_data(&after)?;
+    let horizontal = orientation == ComparisonOrientation::Horizontal;
+    
+    // Scale the smaller image so both share the height (or width when stacked)
+    let shared = |img: &DynamicImage| if horizontal { img.height() } else { img.width() };
+    let target = shared(&before_img).max(shared(&after_img));
+    let match_size = |img: DynamicImage| -> image::RgbaImage {
+        if shared(&img) == target {
+            return img.to_rgba8();
+        }
+        let scale = target as f64 / shared(&img) as f64;
+        let (width, height) = if horizontal {
+            (((img.width() as f64 * scale).round() as u32).max(1), target)
+        } else {
+            (target, ((img.height() as f64 * scale).round() as u32).max(1))
+        };
+        img.resize_exact(width, height, image::imageops::FilterType::Lanczos3).to_rgba8()
+    };
+    let before_rgba = match_size(before_img);
+    let after_rgba = match_size(after_img);
+    
+    let (width, height, after_x, after_y) = if horizontal {
+        let after_x = before_rgba.width() + gap;
+        (after_x + after_rgba.width(), target, after_x, 0)
+    } else {
+        let after_y = before_rgba.height() + gap;
+        (target, after_y + after_rgba.height(), 0, after_y)
+    };
+    
+    let mut canvas = image::RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    image::imageops::replace(&mut canvas, &before_rgba, 0, 0);
+    image::imageops::replace(&mut canvas, &after_rgba, after_x as i64, after_y as i64);
+    
+    let path = path_with_suffix(&after.path, "_comparison");
+    Ok(encode_image_data(&DynamicImage::ImageRgba8(canvas), path, after.format)?)
+}
+
+/// Add a solid border around an image
+/// 
+/// The canvas grows by `thickness` on every side, or by the per-side values
+/// where given, and the new area is filled with the border color. The image
+/// itself is copied unchanged, so it stays centered when all sides are equal.
+/// 
+/// @param image_data - The image to frame
+/// @param thickness - Border width in pixels on every side
+/// @param color - Border color as #RRGGBB
+/// @param top - Border above the image, overriding thickness
+/// @param right - Border right of the image, overriding thickness
+/// @param bottom - Border below the image, overriding thickness
+/// @param left - Border left of the image, overriding thickness
+/// @returns New ImageData with the enlarged canvas
+#[tauri::command]
+async fn add_border(
+    image_data: ImageData,
+    thickness: u32,
+    color: String,
+    top: Option<u32>,
+    right: Option<u32>,
+    bottom: Option<u32>,
+    left: Option<u32>,
+) -> Result<ImageData, CommandError> {
+    let (r, g, b) = parse_hex_color(&color).map_err(AppError::InvalidParameters)?;
+    let [top, right, bottom, left] = [top, right, bottom, left].map(|side| side.unwrap_or(thickness));
+    
+    let img = decode_image_data(&image_data)?;
+    let padded = pad_image(&img, [top, right, bottom, left], Rgba([r, g, b, 255]))?;
+    
+    Ok(encode_image_data(&padded, image_data.path, image_data.format)?)
+}
+
+/// Pad an image to a target aspect ratio with solid bars
+/// 
+/// Only one dimension grows: images wider than the ratio get bars above and
+/// below, narrower images get bars at the sides. Nothing is cropped and the
+/// image stays centered.
+/// 
+/// @param image_data - The image to pad
+/// @param aspect_w - Width part of the ratio (e.g. 16)
+/// @param aspect_h - Height part of the ratio (e.g. 9)
+/// @param color - Bar color as #RRGGBB
+/// @returns New ImageData with the padded canvas
+#[tauri::command]
+async fn pad_to_aspect(
+    image_data: ImageData,
+    aspect_w: u32,
+    aspect_h: u32,
+    color: String,
+) -> Result<ImageData, CommandError> {
+    if aspect_w == 0 || aspect_h == 0 {
+        return Err(AppError::InvalidParameters(
+            "Aspect ratio parts must be positive".to_string()
+        ).into());
+    }
+    let (r, g, b) = parse_hex_color(&color).map_err(AppError::InvalidParameters)?;
+    
+    let img = decode_image_data(&image_data)?;
+    let (width, height) = img.dimensions();
+    
+    // Grow whichever side is too short for the ratio
+    let canvas_width = ((height as f64 * aspect_w as f64 / aspect_h as f64).round() as u32).max(width);
+    let canvas_height = ((width as f64 * aspect_h as f64 / aspect_w as f64).round() as u32).max(height);
+    let (canvas_width, canvas_height) = if canvas_width > width {
+        (canvas_width, height)
+    } else {
+        (width, canvas_height)
+    };
+    
+    let left = (canvas_width - width) / 2;
+    let top = (canvas_height - height) / 2;
+    let sides = [top, canvas_width - width - left, canvas_height - height - top, left];
+    let padded = pad_image(&img, sides, Rgba([r, g, b, 255]))?;
+    
+    Ok(encode_image_data(&padded, image_data.path, image_data.format)?)
+}
+
+/// Place an image on a larger canvas filled with a color
+/// 
+/// `sides` gives the padding as [top, right, bottom, left]. The image pixels
+/// are copied unchanged, including their alpha.
+fn pad_image(img: &DynamicImage, sides: [u32; 4], fill: Rgba<u8>) -> Result<DynamicImage, AppError> {
+    let [top, right, bottom, left] = sides;
+    let (width, height) = img.dimensions();
+    
+    let canvas_size = |size: u32, before: u32, after: u32| {
+        size.checked_add(before).and_then(|s| s.checked_add(after))
+    };
+    let (Some(canvas_width), Some(canvas_height)) = (
+        canvas_size(width, left, right),
+        canvas_size(height, top, bottom),
+    ) else {
+        return Err(AppError::InvalidParameters("Padding is too large".to_string()));
+    };
+    
+    let mut canvas = image::RgbaImage::from_pixel(canvas_width, canvas_height, fill);
+    image::imageops::replace(&mut canvas, &img.to_rgba8(), left as i64, top as i64);
+    
+    Ok(DynamicImage::ImageRgba8(canvas))
+}
+
+/// Find the bounding box (x, y, width, height) of all pixels matching is_content
+/// 
+/// Returns None if no pixel matches.
+fn content_bounds<F>(img: &image::RgbaImage, is_content: F) -> Option<(u32, u32, u32, u32)>
+where
+    F: Fn(&Rgba<u8>) -> bool,
+{
+    let mut min_x = u32::MAX;
+    let mut min_y = u32::MAX;
+    let mut max_x = 0;
+    let mut max_y = 0;
+    
+    for (x, y, pixel) in img.enumerate_pixels() {
+        if is_content(pixel) {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    
+    if min_x == u32::MAX {
+        return None;
+    }
+    
+    Some((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+/// Check whether two pixels' RGB channels all differ by at most tolerance
+fn colors_within_tolerance(a: &Rgba<u8>, b: &Rgba<u8>, tolerance: u8) -> bool {
+    (0..3).all(|i| a.0[i].abs_diff(b.0[i]) <= tolerance)
+}
+
+/// Replace a color throughout an image, or key it out to transparency
+/// 
+/// Every pixel whose RGB channels are all within tolerance of the target color
+/// is recolored to the replacement, keeping its alpha. Without a replacement
+/// the matching pixels become fully transparent, and formats without alpha
+/// support are encoded as PNG instead.
+/// 
+/// @param image_data - The image to edit
+/// @param target - Color to replace
+/// @param replacement - New color, or None to make matching pixels transparent
+/// @param tolerance - Maximum per-channel difference from the target (0-255)
+/// @returns New ImageData with the color replaced
+#[tauri::command]
+async fn replace_color(
+    image_data: ImageData,
+    target: RGBColor,
+    replacement: Option<RGBColor>,
+    tolerance: u8,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    let mut rgba = img.to_rgba8();
+    let target = Rgba([target.r, target.g, target.b, 255]);
+    
+    let cancel = cancel_registry.start();
+    let mut replaced = 0usize;
+    for row in rgba.rows_mut() {
+        cancel.check()?;
+        for pixel in row {
+            if !colors_within_tolerance(pixel, &target, tolerance) {
+                continue;
+            }
+            *pixel = match &replacement {
+                Some(color) => Rgba([color.r, color.g, color.b, pixel.0[3]]),
+                None => Rgba([pixel.0[0], pixel.0[1], pixel.0[2], 0]),
+            };
+            replaced += 1;
+        }
+    }
+    
+    println!("Replaced {} pixels", replaced);
+    
+    // A colored replacement must not be folded back into a grayscale source
+    let result_img = match &replacement {
+        Some(color) if !(color.r == color.g && color.g == color.b) => DynamicImage::ImageRgba8(rgba),
+        _ => match_grayscale_layout(rgba, &img),
+    };
+    if replacement.is_some() {
+        Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+    } else {
+        Ok(encode_with_alpha(&result_img, image_data.path, image_data.format)?)
+    }
+}
+
+/// Select the connected region of similar color around a seed pixel
+/// 
+/// Works like a magic wand: starting from (x, y), neighbouring pixels whose RGB
+/// channels are all within tolerance of the seed color are added to the region.
+/// 
+/// @param image_data - The image to select from
+/// @param x - X coordinate of the seed pixel
+/// @param y - Y coordinate of the seed pixel
+/// @param tolerance - Maximum per-channel difference from the seed color (0-255)
+/// @param eight_connected - Also spread to diagonal neighbours (default false)
+/// @returns Grayscale PNG mask, 255 inside the region and 0 elsewhere
+#[tauri::command]
+async fn flood_fill_mask(
+    image_data: ImageData,
+    x: u32,
+    y: u32,
+    tolerance: u8,
+    eight_connected: Option<bool>,
+) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    
+    if x >= width || y >= height {
+        return Err(AppError::InvalidParameters(
+            format!("Seed {},{} is outside the image ({}x{})", x, y, width, height)
+        ).into());
+    }
+    
+    let seed = *rgba.get_pixel(x, y);
+    let offsets: &[(i64, i64)] = if eight_connected.unwrap_or(false) {
+        &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)]
+    } else {
+        &[(0, -1), (-1, 0), (1, 0), (0, 1)]
+    };
+    
+    let mut mask = image::GrayImage::new(width, height);
+    mask.put_pixel(x, y, image::Luma([255]));
+    let mut stack = vec![(x, y)];
+    
+    while let Some((px, py)) = stack.pop() {
+        for &(dx, dy) in offsets {
+            let nx = px as i64 + dx;
+            let ny = py as i64 + dy;
+            if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                continue;
+            }
+            
+            let (nx, ny) = (nx as u32, ny as u32);
+            if mask.get_pixel(nx, ny).0[0] == 0
+                && colors_within_tolerance(rgba.get_pixel(nx, ny), &seed, tolerance)
+            {
+                mask.put_pixel(nx, ny, image::Luma([255]));
+                stack.push((nx, ny));
+            }
+        }
+    }
+    
+    let path = update_file_extension(&path_with_suffix(&image_data.path, "_mask"), &ImageFormat::PNG);
+    
+    Ok(encode_image_data(&DynamicImage::ImageLuma8(mask), path, ImageFormat::PNG)?)
+}
+
+/// Crop a sub-pixel region of an image with supersampling
+/// 
+/// Instead of a hard pixel crop, every output pixel averages several bilinear
+/// samples taken from the source, including pixels just outside the region.
+/// This gives smooth results for tiny, magnified crops such as loupe views.
+/// 
+/// @param image_data - The image to crop
+/// @param x - X coordinate of the region (may be fractional)
+/// @param y - Y coordinate of the region (may be fractional)
+/// @param width - Width of the region in source pixels (may be fractional)
+/// @param height - Height of the region in source pixels (may be fractional)
+/// @param output_width - Width of the returned image
+/// @param output_height - Height of the returned image
+/// @returns New ImageData of output_width x output_height
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn crop_supersampled(
+    image_data: ImageData,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    output_width: u32,
+    output_height: u32,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    // Number of samples per output pixel along each axis
+    const SAMPLES: u32 = 4;
+    
+    error::utils::validate_dimensions(output_width, output_height)?;
+    
+    if !(width > 0.0 && height > 0.0) {
+        return Err(AppError::InvalidParameters(
+            "Crop width and height must be positive".to_string()
+        ).into());
+    }
+    
+    let img = decode_image_data(&image_data)?;
+    let source = img.to_rgba8();
+    
+    if x < 0.0 || y < 0.0
+        || x + width > source.width() as f32
+        || y + height > source.height() as f32
+    {
+        return Err(AppError::InvalidParameters(
+            format!(
+                "Crop region ({}x{} at {},{}) exceeds image bounds ({}x{})",
+                width, height, x, y, source.width(), source.height()
+            )
+        ).into());
+    }
+    
+    let step_x = width / output_width as f32;
+    let step_y = height / output_height as f32;
+    let sample_count = (SAMPLES * SAMPLES) as f32;
+    
+    let output = image::RgbaImage::from_fn(output_width, output_height, |ox, oy| {
+        let mut sum = [0.0f32; 4];
+        
+        for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+                // Sample position in source pixel space (pixel centers at +0.5)
+                let src_x = x + (ox as f32 + (sx as f32 + 0.5) / SAMPLES as f32) * step_x - 0.5;
+                let src_y = y + (oy as f32 + (sy as f32 + 0.5) / SAMPLES as f32) * step_y - 0.5;
+                let sample = sample_bilinear(&source, src_x, src_y);
+                for (total, value) in sum.iter_mut().zip(sample) {
+                    *total += value;
+                }
+            }
+        }
+        
+        Rgba(sum.map(|v| (v / sample_count).round().clamp(0.0, 255.0) as u8))
+    });
+    
+    let result_img = match_grayscale_layout(output, &img);
+    
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Sample an image at a fractional position using bilinear interpolation
+/// 
+/// Coordinates outside the image are clamped to the nearest edge pixel.
+fn sample_bilinear(img: &image::RgbaImage, x: f32, y: f32) -> [f32; 4] {
+    let max_x = (img.width() - 1) as f32;
+    let max_y = (img.height() - 1) as f32;
+    let x = x.clamp(0.0, max_x);
+    let y = y.clamp(0.0, max_y);
+    
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let x1 = (x0 + 1.0).min(max_x);
+    let y1 = (y0 + 1.0).min(max_y);
+    let fx = x - x0;
+    let fy = y - y0;
+    
+    let p00 = img.get_pixel(x0 as u32, y0 as u32);
+    let p10 = img.get_pixel(x1 as u32, y0 as u32);
+    let p01 = img.get_pixel(x0 as u32, y1 as u32);
+    let p11 = img.get_pixel(x1 as u32, y1 as u32);
+    
+    std::array::from_fn(|channel| {
+        let top = p00.0[channel] as f32 * (1.0 - fx) + p10.0[channel] as f32 * fx;
+        let bottom = p01.0[channel] as f32 * (1.0 - fx) + p11.0[channel] as f32 * fx;
+        top * (1.0 - fy) + bottom * fy
+    })
+}
+
+/// Set background color for transparent images
+/// 
+/// Replaces transparent pixels with the specified RGB color.
+/// Only works on images with an alpha channel (hasAlpha = true).
+/// 
+/// @param image_data - The image to process (must have alpha channel)
+/// @param r - Red component (0-255)
+/// @param g - Green component (0-255)
+/// @param b - Blue component (0-255)
+/// @param linear_blend - Blend in linear light instead of gamma-encoded sRGB,
+///   which avoids dark fringes between contrasting colors (default false)
+/// @returns New ImageData with background applied to transparent areas
+#[tauri::command]
+async fn set_background(
+    image_data: ImageData,
+    r: u8,
+    g: u8,
+    b: u8,
+    linear_blend: Option<bool>,
+) -> Result<ImageData, CommandError> {
+    // Validate that the image has an alpha channel
+    if !image_data.has_alpha {
+        return Err(AppError::NoAlpha(
+            "background setting is only applicable to transparent images".to_string()
+        ).into());
+    }
+    
+    // Decode Base64 data
+    let decoded_data = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    // Load image from decoded data
+    let img = image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)?;
+    
+    // Convert to RGBA8 for processing
+    let mut rgba_img = img.to_rgba8();
+    
+    let linear_blend = linear_blend.unwrap_or(false);
+    let background = [r, g, b];
+    
+    // Apply background color to transparent pixels
+    for pixel in rgba_img.pixels_mut() {
+        let alpha = pixel.0[3];
+        
+        if alpha < 255 {
+            // Blend the background color with the existing pixel based on alpha
+            let alpha_f = alpha as f32 / 255.0;
+            let inv_alpha = 1.0 - alpha_f;
+            
+            // Alpha blending: result = foreground * alpha + background * (1 - alpha)
+            for (value, &background) in pixel.0.iter_mut().zip(&background) {
+                *value = if linear_blend {
+                    linear_to_srgb(
+                        srgb_to_linear(*value) * alpha_f + srgb_to_linear(background) * inv_alpha,
+                    )
+                } else {
+                    ((*value as f32 * alpha_f) + (background as f32 * inv_alpha)) as u8
+                };
+            }
+            pixel.0[3] = 255; // Set alpha to fully opaque
+        }
+    }
+    
+    // Convert back to DynamicImage
+    let result_img = DynamicImage::ImageRgba8(rgba_img);
+    
+    // Encode to the same format as the original
+    let mut output_buffer = Vec::new();
+    let format = image_data.format.to_image_format()
+        .ok_or_else(|| AppError::UnsupportedFormat(
+            format!("Cannot process {} format", image_data.format)
+        ))?;
+    
+    result_img.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
+        .map_err(AppError::ImageError)?;
+    
+    // Encode to Base64
+    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    
+    // After applying background, the image no longer has transparency
+    let has_alpha = false;
+    
+    // Return new ImageData with background applied
+    Ok(ImageData {
+        path: image_data.path,
+        width: image_data.width,
+        height: image_data.height,
+        format: image_data.format,
+        data: base64_data,
+        has_alpha,
+        display_data: None,
+    })
+}
+
+/// Rotate an image by 90 degrees clockwise or counter-clockwise
+/// 
+/// @param image_data - ImageData object containing the image to rotate
+/// @param clockwise - If true, rotate 90° clockwise; if false, rotate 90° counter-clockwise
+/// @returns New ImageData with rotated image (width and height are swapped)
+#[tauri::command]
+async fn rotate_image(
+    image_data: ImageData,
+    clockwise: bool,
+) -> Result<ImageData, CommandError> {
+    // Decode Base64 data
+    let decoded_data = general_purpose::STANDARD
         .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
+        .map_err(AppError::Base64Error)?;
+    
+    // Load image from decoded data
+    let img = image::load_from_memory(&decoded_data)
+        .map_err(AppError::ImageError)?;
+    
+    // Rotate the image
+    let rotated = if clockwise {
+        img.rotate90()
+    } else {
+        img.rotate270()
+    };
+    
+    // Encode to the same format as the original
+    let mut output_buffer = Vec::new();
+    let format = image_data.format.to_image_format()
+        .ok_or_else(|| AppError::UnsupportedFormat(
+            format!("Cannot rotate {} format", image_data.format)
+        ))?;
+    
+    rotated.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
+        .map_err(AppError::ImageError)?;
+    
+    // Encode to Base64
+    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    
+    // Detect alpha channel in rotated image
+    let has_alpha = detect_alpha_channel(&rotated);
+    
+    // Return new ImageData with swapped dimensions
+    Ok(ImageData {
+        path: image_data.path,
+        width: rotated.width(),
+        height: rotated.height(),
+        format: image_data.format,
+        data: base64_data,
+        has_alpha,
+        display_data: None,
+    })
+}
+
+/// Rotate an image by an arbitrary angle around its center
+///
+/// Positive angles rotate clockwise. The canvas grows to fit the rotated image
+/// and the uncovered corners are filled with `fill`.
+fn rotate_by_angle(img: &image::RgbaImage, degrees: f32, fill: Rgba<u8>) -> image::RgbaImage {
+    let radians = degrees.to_radians();
+    let cos_angle = radians.cos();
+    let sin_angle = radians.sin();
+    
+    let width = img.width() as f32;
+    let height = img.height() as f32;
+    let new_width = (width * cos_angle.abs() + height * sin_angle.abs()).round().max(1.0) as u32;
+    let new_height = (width * sin_angle.abs() + height * cos_angle.abs()).round().max(1.0) as u32;
+    
+    let center_x = (width - 1.0) / 2.0;
+    let center_y = (height - 1.0) / 2.0;
+    let new_center_x = (new_width as f32 - 1.0) / 2.0;
+    let new_center_y = (new_height as f32 - 1.0) / 2.0;
+    
+    image::RgbaImage::from_fn(new_width, new_height, |x, y| {
+        // Apply inverse rotation to find the source pixel
+        let dx = x as f32 - new_center_x;
+        let dy = y as f32 - new_center_y;
+        let src_x = dx * cos_angle + dy * sin_angle + center_x;
+        let src_y = -dx * sin_angle + dy * cos_angle + center_y;
+        
+        if src_x < -0.5 || src_y < -0.5 || src_x > width - 0.5 || src_y > height - 0.5 {
+            return fill;
+        }
+        
+        Rgba(sample_bilinear(img, src_x, src_y).map(|v| v.round().clamp(0.0, 255.0) as u8))
+    })
+}
+
+/// Rotate an image so that a drawn line becomes horizontal
+///
+/// Computes the angle of the line from (x1, y1) to (x2, y2) relative to the
+/// horizontal and rotates the image by the opposite angle ("level the horizon").
+/// The direction the line was drawn in does not matter.
+///
+/// @param image_data - The image to straighten
+/// @param x1 - X coordinate of the line start
+/// @param y1 - Y coordinate of the line start
+/// @param x2 - X coordinate of the line end
+/// @param y2 - Y coordinate of the line end
+/// @param fill - Color for the corners uncovered by the rotation (default transparent)
+/// @returns New ImageData with the rotated image, enlarged to fit
+#[tauri::command]
+async fn straighten_by_line(
+    image_data: ImageData,
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+    fill: Option<RGBColor>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    if x1 == x2 && y1 == y2 {
+        return Err(AppError::InvalidParameters(
+            "Line start and end points must differ".to_string()
+        ).into());
+    }
+    
+    // Angle of the line in image coordinates (positive = clockwise), folded into -90..90
+    let mut angle = (y2 - y1).atan2(x2 - x1).to_degrees();
+    if angle > 90.0 {
+        angle -= 180.0;
+    } else if angle <= -90.0 {
+        angle += 180.0;
+    }
+    
+    let fill = fill
+        .map(|c| Rgba([c.r, c.g, c.b, 255]))
+        .unwrap_or(Rgba([0, 0, 0, 0]));
+    
+    let img = decode_image_data(&image_data)?;
+    let rotated = rotate_by_angle(&img.to_rgba8(), -angle, fill);
+    
+    println!("Straightening image by {:.2} degrees", -angle);
+    
+    let result_img = match_grayscale_layout(rotated, &img);
+    
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Straighten a scanned document in one step
+///
+/// Detects the skew of the text lines and rotates the image by the opposite
+/// angle, filling the uncovered corners with white. With auto_crop the result
+/// is then trimmed to the bounding box of its non-white content.
+///
+/// @param image_data - The scan to straighten
+/// @param auto_crop - Crop to the content after rotating (default false)
+/// @returns New ImageData with the straightened image
+#[tauri::command]
+async fn auto_deskew(
+    image_data: ImageData,
+    auto_crop: Option<bool>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // Load image from decoded data
-    let img = image::load_from_memory(&decoded_data)
-        .map_err(AppError::ImageError)?;
+    let img = decode_image_data(&image_data)?;
+    let angle = deskew::detect_skew(&img.to_luma8());
     
-    // Calculate target dimensions
-    let (target_width, target_height) = if keep_aspect_ratio {
-        calculate_aspect_ratio_dimensions(
-            image_data.width,
-            image_data.height,
-            width,
-            height,
-        )
-    } else {
-        (width, height)
-    };
+    println!("Deskewing image by {:.2} degrees", -angle);
     
-    // Resize the image using Lanczos3 filter for high quality
-    let resized = img.resize(target_width, target_height, image::imageops::FilterType::Lanczos3);
+    let white = Rgba([255, 255, 255, 255]);
+    let mut rotated = rotate_by_angle(&img.to_rgba8(), -angle, white);
     
-    // Encode to the same format as the original
-    let mut output_buffer = Vec::new();
-    let format = image_data.format.to_image_format()
-        .ok_or_else(|| AppError::UnsupportedFormat(
-            format!("Cannot resize {} format", image_data.format)
-        ))?;
+    if auto_crop.unwrap_or(false) {
+        let bounds = content_bounds(&rotated, |pixel| !colors_within_tolerance(pixel, &white, 32));
+        if let Some((x, y, width, height)) = bounds {
+            rotated = image::imageops::crop_imm(&rotated, x, y, width, height).to_image();
+        }
+    }
     
-    resized.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
-        .map_err(AppError::ImageError)?;
+    let result_img = match_grayscale_layout(rotated, &img);
     
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Convert an image to grayscale
+/// 
+/// Transparency is kept: images with an alpha channel become 2-channel
+/// grayscale+alpha rather than being promoted to RGBA.
+/// 
+/// @param image_data - The image to convert
+/// @returns New ImageData with the grayscale image
+#[tauri::command]
+async fn grayscale_image(image_data: ImageData) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
     
-    // Detect alpha channel in resized image
-    let has_alpha = detect_alpha_channel(&resized);
+    // Keeps the bit depth and maps alpha images to grayscale+alpha
+    let result_img = img.grayscale();
     
-    // Return new ImageData with updated dimensions
-    Ok(ImageData {
-        path: image_data.path,
-        width: target_width,
-        height: target_height,
-        format: image_data.format,
-        data: base64_data,
-        has_alpha,
-    })
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
 }
 
-/// Calculate dimensions that maintain aspect ratio
+/// Multiply each color channel by its pixel's alpha
+fn premultiply_alpha(img: &mut image::RgbaImage) {
+    for pixel in img.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+        for channel in pixel.0.iter_mut().take(3) {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Divide each color channel by its pixel's alpha, undoing premultiplication
+///
+/// Fully transparent pixels carry no color information and become black.
+fn unpremultiply_alpha(img: &mut image::RgbaImage) {
+    for pixel in img.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+        for channel in pixel.0.iter_mut().take(3) {
+            *channel = (*channel as u32 * 255 + alpha / 2)
+                .checked_div(alpha)
+                .map_or(0, |value| value.min(255) as u8);
+        }
+    }
+}
+
+/// Convert an image with premultiplied alpha to straight alpha
 /// 
-/// Given original dimensions and target dimensions, calculates the largest size
-/// that fits within the target while maintaining the original aspect ratio.
-fn calculate_aspect_ratio_dimensions(
-    original_width: u32,
-    original_height: u32,
-    target_width: u32,
-    target_height: u32,
-) -> (u32, u32) {
-    let original_ratio = original_width as f64 / original_height as f64;
-    let target_ratio = target_width as f64 / target_height as f64;
+/// Use on assets whose semi-transparent edges look too dark. Premultiplication
+/// is not recorded in the file, so this must be chosen explicitly.
+/// 
+/// @param image_data - The premultiplied image
+/// @returns New ImageData with straight alpha
+#[tauri::command]
+async fn premultiplied_to_straight(image_data: ImageData) -> Result<ImageData, CommandError> {
+    let mut rgba_img = decode_image_data(&image_data)?.to_rgba8();
+    unpremultiply_alpha(&mut rgba_img);
     
-    if original_ratio > target_ratio {
-        // Width is the limiting factor
-        let new_width = target_width;
-        let new_height = (target_width as f64 / original_ratio).round() as u32;
-        (new_width, new_height.max(1))
+    let result_img = DynamicImage::ImageRgba8(rgba_img);
+    
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Convert an image with straight alpha to premultiplied alpha
+/// 
+/// @param image_data - The straight-alpha image
+/// @returns New ImageData with color channels multiplied by alpha
+#[tauri::command]
+async fn straight_to_premultiplied(image_data: ImageData) -> Result<ImageData, CommandError> {
+    let mut rgba_img = decode_image_data(&image_data)?.to_rgba8();
+    premultiply_alpha(&mut rgba_img);
+    
+    let result_img = DynamicImage::ImageRgba8(rgba_img);
+    
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Equalize the histogram of an image to improve contrast
+///
+/// By default the lightness channel is equalized and hue/saturation are kept via HSL,
+/// so colors are preserved. If per_channel is true, red, green and blue are equalized
+/// independently instead. The alpha channel is always preserved.
+///
+/// @param image_data - The image to equalize
+/// @param per_channel - Equalize R, G and B separately instead of lightness (default false)
+/// @returns New ImageData with the equalized image
+#[tauri::command]
+async fn equalize_histogram_image(
+    image_data: ImageData,
+    per_channel: Option<bool>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    use imageproc::contrast::equalize_histogram;
+
+    let img = decode_image_data(&image_data)?;
+    let mut rgba_img = img.to_rgba8();
+    let (width, height) = rgba_img.dimensions();
+
+    if per_channel.unwrap_or(false) {
+        // Equalize each color channel as its own grayscale image
+        for channel in 0..3 {
+            let plane = image::GrayImage::from_fn(width, height, |x, y| {
+                image::Luma([rgba_img.get_pixel(x, y).0[channel]])
+            });
+            let equalized = equalize_histogram(&plane);
+            for (x, y, pixel) in rgba_img.enumerate_pixels_mut() {
+                pixel.0[channel] = equalized.get_pixel(x, y).0[0];
+            }
+        }
     } else {
-        // Height is the limiting factor
-        let new_height = target_height;
-        let new_width = (target_height as f64 * original_ratio).round() as u32;
-        (new_width.max(1), new_height)
+        // Equalize lightness only, keeping hue and saturation
+        let hsl: Vec<(f32, f32, f32)> = rgba_img
+            .pixels()
+            .map(|p| rgb_to_hsl(p.0[0], p.0[1], p.0[2]))
+            .collect();
+        let lightness = image::GrayImage::from_fn(width, height, |x, y| {
+            let l = hsl[(y * width + x) as usize].2;
+            image::Luma([(l * 255.0).round() as u8])
+        });
+        let equalized = equalize_histogram(&lightness);
+        for (x, y, pixel) in rgba_img.enumerate_pixels_mut() {
+            let (h, s, _) = hsl[(y * width + x) as usize];
+            let l = equalized.get_pixel(x, y).0[0] as f32 / 255.0;
+            let (r, g, b) = hsl_to_rgb(h, s, l);
+            pixel.0[0] = r;
+            pixel.0[1] = g;
+            pixel.0[2] = b;
+        }
     }
+
+    let result_img = match_grayscale_layout(rgba_img, &img);
+
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
 }
 
-/// Convert image to a different format
+/// Stretch each color channel to the full 0-255 range
 /// 
-/// Supports conversion between all supported formats (PNG, JPEG, GIF, BMP, WEBP, TIFF, ICO, AVIF)
-/// For lossy formats (JPEG, WEBP, AVIF), quality parameter can be specified (1-100)
+/// Finds the darkest and brightest value of red, green and blue and maps them
+/// linearly to 0 and 255, which brings back contrast in washed-out photos.
+/// Fully transparent pixels are ignored and alpha is preserved.
 /// 
-/// Note: SVG and HEIC formats are not supported for conversion
+/// @param image_data - The image to adjust
+/// @param clip_percent - Percentage of pixels to ignore at each end of every
+///   channel so outliers don't limit the stretch (0.0-50.0, default 0.0)
+/// @returns New ImageData with the stretched image
 #[tauri::command]
-async fn convert_format(
+async fn auto_levels(
     image_data: ImageData,
-    target_format: String,
-    options: Option<ConversionOptions>,
-) -> Result<ImageData, String> {
-    // Parse target format
-    let target_format_enum = match target_format.to_uppercase().as_str() {
-        "PNG" => ImageFormat::PNG,
-        "JPEG" | "JPG" => ImageFormat::JPEG,
-        "GIF" => ImageFormat::GIF,
-        "BMP" => ImageFormat::BMP,
-        "WEBP" => ImageFormat::WEBP,
-        "TIFF" | "TIF" => ImageFormat::TIFF,
-        "ICO" => ImageFormat::ICO,
-        "AVIF" => ImageFormat::AVIF,
-        _ => {
-            return Err(AppError::UnsupportedFormat(
-                format!("Unsupported target format: {}", target_format)
-            ).into());
+    clip_percent: Option<f32>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    let clip_percent = clip_percent.unwrap_or(0.0);
+    if !(0.0..50.0).contains(&clip_percent) {
+        return Err(AppError::InvalidParameters(
+            "Clip percent must be between 0 and 50".to_string()
+        ).into());
+    }
+    
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let img = decode_image_data(&image_data)?;
+    let histogram = histogram::compute_histogram(&img);
+    
+    // One lookup table per color channel; flat channels are left unchanged
+    let tables: Vec<[u8; 256]> = [&histogram.red, &histogram.green, &histogram.blue]
+        .into_iter()
+        .map(|counts| {
+            let bounds = histogram::channel_bounds(counts, clip_percent);
+            let mut table = [0u8; 256];
+            for (value, entry) in table.iter_mut().enumerate() {
+                *entry = match bounds {
+                    Some((low, high)) if high > low => {
+                        let t = (value as f32 - low as f32) / (high - low) as f32;
+                        (t * 255.0).round().clamp(0.0, 255.0) as u8
+                    }
+                    _ => value as u8,
+                };
+            }
+            table
+        })
+        .collect();
+    
+    let mut rgba_img = img.to_rgba8();
+    for pixel in rgba_img.pixels_mut() {
+        for (channel, table) in tables.iter().enumerate() {
+            pixel.0[channel] = table[pixel.0[channel] as usize];
         }
-    };
+    }
     
-    // Validate that we can convert to this format
-    if target_format_enum == ImageFormat::SVG || target_format_enum == ImageFormat::HEIC {
-        return Err(AppError::UnsupportedFormat(
-            format!("Cannot convert to {} format", target_format_enum)
+    let result_img = match_grayscale_layout(rgba_img, &img);
+    
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Reduce every color channel to a few discrete levels (poster-art effect)
+/// 
+/// Each channel value is snapped to the nearest of `levels` evenly spaced
+/// values from 0 to 255. Alpha is preserved.
+/// 
+/// @param image_data - The image to posterize
+/// @param levels - Number of values per channel (2-256; 256 leaves the image unchanged)
+/// @returns New ImageData with the posterized image
+#[tauri::command]
+async fn posterize_image(image_data: ImageData, levels: u16) -> Result<ImageData, CommandError> {
+    if !(2..=256).contains(&levels) {
+        return Err(AppError::InvalidParameters(
+            "Posterize levels must be between 2 and 256".to_string()
         ).into());
     }
     
-    // Validate quality parameter if provided
-    if let Some(ref opts) = options {
-        if let Some(quality) = opts.quality {
-            if quality < 1 || quality > 100 {
-                return Err(AppError::InvalidParameters(
-                    "Quality parameter must be between 1 and 100".to_string()
-                ).into());
-            }
+    let steps = (levels - 1) as f32;
+    let mut table = [0u8; 256];
+    for (value, entry) in table.iter_mut().enumerate() {
+        let level = (value as f32 * steps / 255.0).round();
+        *entry = (level * 255.0 / steps).round() as u8;
+    }
+    
+    let img = decode_image_data(&image_data)?;
+    let mut rgba_img = img.to_rgba8();
+    for pixel in rgba_img.pixels_mut() {
+        for channel in 0..3 {
+            pixel.0[channel] = table[pixel.0[channel] as usize];
         }
     }
     
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
+    let result_img = match_grayscale_layout(rgba_img, &img);
+
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Convert an image to pure black and white
+///
+/// Pixels whose luminance is at least the threshold become white and the rest
+/// black. In adaptive mode each pixel is instead compared with the mean of the
+/// block around it (a sixteenth of the shorter side), which copes with uneven
+/// lighting across a scanned page; the threshold is ignored then.
+///
+/// @param image_data - The image to binarize
+/// @param threshold - Luminance from which pixels become white (0-255)
+/// @param adaptive - Compare against the local mean instead of a fixed threshold
+/// @returns New grayscale ImageData containing only black and white
+#[tauri::command]
+async fn threshold_image(
+    image_data: ImageData,
+    threshold: u8,
+    adaptive: Option<bool>,
+) -> Result<ImageData, CommandError> {
+    let gray = decode_image_data(&image_data)?.to_luma8();
+
+    let binary = if adaptive.unwrap_or(false) {
+        let block_radius = (gray.width().min(gray.height()) / 16).max(1);
+        imageproc::contrast::adaptive_threshold(&gray, block_radius)
+    } else {
+        image::GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+            image::Luma([if gray.get_pixel(x, y).0[0] >= threshold { 255 } else { 0 }])
+        })
+    };
+
+    Ok(encode_image_data(&DynamicImage::ImageLuma8(binary), image_data.path, image_data.format)?)
+}
+
+/// Set the pixel density used when the image is printed
+/// 
+/// The density is written into the file's metadata (a pHYs chunk for PNG, the
+/// JFIF header for JPEG) without re-encoding the pixels.
+/// 
+/// @param image_data - A PNG or JPEG image
+/// @param dpi - Dots per inch, from 1 to 65535
+/// @returns New ImageData carrying the density
+#[tauri::command]
+async fn set_image_dpi(image_data: ImageData, dpi: u32) -> Result<ImageData, CommandError> {
+    let bytes = general_purpose::STANDARD
         .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
+        .map_err(AppError::Base64Error)?;
+    let updated = dpi::set_dpi(&bytes, &image_data.format, dpi)?;
     
-    // Load image from decoded data
-    let img = image::load_from_memory(&decoded_data)
-        .map_err(AppError::ImageError)?;
+    Ok(ImageData {
+        data: general_purpose::STANDARD.encode(&updated),
+        ..image_data
+    })
+}
+
+/// Read the pixel density recorded in an image
+/// 
+/// @param image_data - The image to inspect
+/// @returns Dots per inch, or None if the file doesn't record an absolute density
+#[tauri::command]
+async fn get_image_dpi(image_data: ImageData) -> Result<Option<u32>, CommandError> {
+    let bytes = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    Ok(dpi::read_dpi(&bytes, &image_data.format))
+}
+
+/// Get the physical size of an image when printed
+/// 
+/// Uses the density recorded in the file, or 96 DPI if there is none.
+/// 
+/// @param image_data - The image to measure
+/// @returns Width and height in inches and millimeters, with the DPI used
+#[tauri::command]
+async fn get_print_size(image_data: ImageData) -> Result<PrintSize, CommandError> {
+    let bytes = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
     
-    // Convert to target format
-    let mut output_buffer = Vec::new();
-    let img_format = target_format_enum.to_image_format()
-        .ok_or_else(|| AppError::UnsupportedFormat(
-            format!("Cannot encode to {} format", target_format_enum)
-        ))?;
+    let embedded = dpi::read_dpi(&bytes, &image_data.format).filter(|&dpi| dpi > 0);
     
-    // Handle quality parameter for lossy formats
-    match target_format_enum {
-        ImageFormat::JPEG => {
-            let quality = options
-                .as_ref()
-                .and_then(|o| o.quality)
-                .unwrap_or(90); // Default quality for JPEG
-            
-            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(
-                &mut output_buffer,
-                quality,
-            );
-            encoder.encode_image(&img)
-                .map_err(AppError::ImageError)?;
-        }
-        ImageFormat::WEBP => {
-            // Note: The image crate's WebP encoder doesn't support quality parameter directly
-            // We'll use the default encoding
-            img.write_to(&mut std::io::Cursor::new(&mut output_buffer), img_format)
-                .map_err(AppError::ImageError)?;
-        }
-        ImageFormat::AVIF => {
-            // Note: AVIF encoding with quality parameter may not be fully supported
-            // We'll use the default encoding
-            img.write_to(&mut std::io::Cursor::new(&mut output_buffer), img_format)
-                .map_err(AppError::ImageError)?;
-        }
-        _ => {
-            // For lossless formats, just encode normally
-            img.write_to(&mut std::io::Cursor::new(&mut output_buffer), img_format)
-                .map_err(AppError::ImageError)?;
+    Ok(PrintSize::new(
+        image_data.width,
+        image_data.height,
+        embedded.unwrap_or(dpi::DEFAULT_DPI),
+        embedded.is_some(),
+    ))
+}
+
+/// Read the pixel aspect ratio recorded in an image
+/// 
+/// @param image_data - The image to inspect
+/// @returns Pixel width divided by pixel height, or None if the file doesn't record it
+#[tauri::command]
+async fn get_pixel_aspect(image_data: ImageData) -> Result<Option<f64>, CommandError> {
+    let bytes = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    Ok(dpi::read_pixel_aspect(&bytes, &image_data.format))
+}
+
+/// Resample an image with non-square (anamorphic) pixels to square pixels
+/// 
+/// The height is kept and the width is multiplied by the pixel aspect ratio,
+/// so a 2:1 ratio doubles the width. The ratio is read from the file unless
+/// given explicitly, and is assumed to be 1:1 when the file doesn't record one.
+/// 
+/// @param image_data - The image to correct
+/// @param pixel_aspect - Pixel width divided by pixel height (0.1-10), overriding the file
+/// @returns New ImageData with square pixels
+#[tauri::command]
+async fn correct_pixel_aspect(
+    image_data: ImageData,
+    pixel_aspect: Option<f64>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let bytes = general_purpose::STANDARD
+        .decode(&image_data.data)
+        .map_err(AppError::Base64Error)?;
+    
+    let pixel_aspect = match pixel_aspect {
+        Some(ratio) if !(0.1..=10.0).contains(&ratio) => {
+            return Err(AppError::InvalidParameters(
+                "Pixel aspect ratio must be between 0.1 and 10".to_string()
+            ).into());
         }
+        Some(ratio) => ratio,
+        None => dpi::read_pixel_aspect(&bytes, &image_data.format).unwrap_or(1.0),
+    };
+    
+    let img = image::load_from_memory(&bytes).map_err(AppError::ImageError)?;
+    let width = ((img.width() as f64 * pixel_aspect).round() as u32).max(1);
+    
+    println!("Correcting pixel aspect {:.3}: {}x{} -> {}x{}", pixel_aspect, img.width(), img.height(), width, img.height());
+    
+    let resized = img.resize_exact(width, img.height(), image::imageops::FilterType::Lanczos3);
+    
+    Ok(encode_image_data(&resized, image_data.path, image_data.format)?)
+}
+
+/// Compute the red, green, blue and luminance histograms of an image
+/// 
+/// Each channel holds 256 bins of pixel counts. Fully transparent pixels are
+/// skipped. The image itself is left unchanged.
+/// 
+/// @param image_data - The image to analyze
+/// @returns Pixel counts per value for every channel
+#[tauri::command]
+async fn compute_histogram(image_data: ImageData) -> Result<Histogram, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    
+    Ok(histogram::compute_histogram(&img))
+}
+
+/// Draw composition guides over an image for review exports
+/// 
+/// Burns rule-of-thirds, golden spiral, diagonal or phi grid lines into a copy
+/// of the image. The guides are drawn at full strength and then blended over
+/// the image with the given opacity.
+/// 
+/// @param image_data - The image to draw on
+/// @param grid_type - "thirds", "golden", "diagonal" or "phi_grid"
+/// @param color - Line color as #RRGGBB
+/// @param opacity - Line opacity (0.0-1.0)
+/// @returns New ImageData with the guides drawn in
+#[tauri::command]
+async fn draw_composition_grid(
+    image_data: ImageData,
+    grid_type: GridType,
+    color: String,
+    opacity: f32,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<ImageData, CommandError> {
+    use imageproc::drawing::draw_line_segment_mut;
+    
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(AppError::InvalidParameters(
+            "Grid opacity must be between 0.0 and 1.0".to_string()
+        ).into());
+    }
+    let (r, g, b) = parse_hex_color(&color).map_err(AppError::InvalidParameters)?;
+    
+    let img = decode_image_data(&image_data)?;
+    let mut base_rgba = img.to_rgba8();
+    
+    let mut guides = image::RgbaImage::new(base_rgba.width(), base_rgba.height());
+    for (start, end) in composition::guide_lines(grid_type, guides.width(), guides.height()) {
+        draw_line_segment_mut(&mut guides, start, end, Rgba([r, g, b, 255]));
+    }
+    let cancel = cancel_registry.start();
+    composite_overlay(&mut base_rgba, &guides, (0, 0), 0.0, opacity, cancel.flag())?;
+    
+    Ok(encode_image_data(
+        &DynamicImage::ImageRgba8(base_rgba),
+        image_data.path,
+        image_data.format,
+    )?)
+}
+
+/// Extract the dominant colors of an image
+/// 
+/// Runs median cut over a downsampled copy of the image. Images with fewer
+/// distinct colors than requested return fewer entries.
+/// 
+/// @param image_data - The image to analyze
+/// @param count - Number of colors to return (1-256)
+/// @returns Dominant colors ordered by the share of pixels they cover
+#[tauri::command]
+async fn extract_palette(
+    image_data: ImageData,
+    count: u32,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<Vec<RGBColor>, CommandError> {
+    if !(1..=256).contains(&count) {
+        return Err(AppError::InvalidParameters(
+            "Palette size must be between 1 and 256".to_string()
+        ).into());
     }
     
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // Detect alpha channel in converted image
-    let has_alpha = detect_alpha_channel(&img);
+    let img = decode_image_data(&image_data)?;
     
-    // Update file path extension to match new format
-    let new_path = update_file_extension(&image_data.path, &target_format_enum);
+    Ok(palette::extract_palette(&img, count as usize))
+}
+
+/// Render an image's histogram as a chart
+/// 
+/// Draws the luminance distribution as a filled area and the red, green and
+/// blue channels as lines, with axes, on a dark canvas.
+/// 
+/// @param image_data - The image to chart
+/// @param width - Chart width in pixels (default 512)
+/// @param height - Chart height in pixels (default 256)
+/// @returns PNG ImageData of the chart
+#[tauri::command]
+async fn render_histogram(
+    image_data: ImageData,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<ImageData, CommandError> {
+    let img = decode_image_data(&image_data)?;
     
-    // Return new ImageData with updated format
-    Ok(ImageData {
-        path: new_path,
-        width: image_data.width,
-        height: image_data.height,
-        format: target_format_enum,
-        data: base64_data,
-        has_alpha,
-    })
+    let histogram = histogram::compute_histogram(&img);
+    let chart = histogram::render_histogram(&histogram, width.unwrap_or(512), height.unwrap_or(256))?;
+    
+    let path = update_file_extension(&path_with_suffix(&image_data.path, "_histogram"), &ImageFormat::PNG);
+    
+    Ok(encode_image_data(&DynamicImage::ImageRgba8(chart), path, ImageFormat::PNG)?)
 }
 
-/// Update file path extension to match the new format
-fn update_file_extension(path: &str, format: &ImageFormat) -> String {
-    let path_obj = Path::new(path);
-    let stem = path_obj.file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("image");
+/// Detect clipped highlights and shadows
+/// 
+/// Reports the percentage of pixels at pure 0 and pure 255 in each channel,
+/// ignoring fully transparent pixels.
+/// 
+/// @param image_data - The image to check
+/// @param include_mask - Also return a PNG overlay marking clipped highlights in
+///   red and clipped shadows in blue (default false)
+/// @returns Clipping percentages and the optional mask
+#[tauri::command]
+async fn detect_clipping(
+    image_data: ImageData,
+    include_mask: Option<bool>,
+) -> Result<ClippingReport, CommandError> {
+    let img = decode_image_data(&image_data)?;
     
-    let extension = match format {
-        ImageFormat::PNG => "png",
-        ImageFormat::JPEG => "jpg",
-        ImageFormat::GIF => "gif",
-        ImageFormat::BMP => "bmp",
-        ImageFormat::WEBP => "webp",
-        ImageFormat::TIFF => "tiff",
-        ImageFormat::ICO => "ico",
-        ImageFormat::AVIF => "avif",
-        ImageFormat::SVG => "svg",
-        ImageFormat::HEIC => "heic",
-    };
+    let mut report = histogram::compute_clipping(&img);
     
-    if let Some(parent) = path_obj.parent() {
-        parent.join(format!("{}.{}", stem, extension))
-            .to_string_lossy()
-            .to_string()
-    } else {
-        format!("{}.{}", stem, extension)
+    if include_mask.unwrap_or(false) {
+        let mask = histogram::render_clipping_mask(&img);
+        let path = update_file_extension(&path_with_suffix(&image_data.path, "_clipping"), &ImageFormat::PNG);
+        report.mask = Some(encode_image_data(&DynamicImage::ImageRgba8(mask), path, ImageFormat::PNG)?);
     }
+    
+    Ok(report)
 }
 
-/// Crop an image to the specified region
+/// Suggest a readable text color for a region of an image
 /// 
-/// Extracts a rectangular region from the image. If the crop region extends beyond
-/// the image boundaries, it will be automatically constrained to fit within the image.
+/// Averages the region's color and picks black or white by WCAG contrast
+/// ratio. The inverse of the average color is returned as well for callers
+/// that prefer a colored caption.
 /// 
-/// @param image_data - The image to crop
-/// @param x - X coordinate of the top-left corner of the crop region
-/// @param y - Y coordinate of the top-left corner of the crop region
-/// @param width - Width of the crop region
-/// @param height - Height of the crop region
-/// @returns New ImageData containing only the cropped region
+/// @param image_data - The image the text will be placed on
+/// @param x - X coordinate of the region's top-left corner
+/// @param y - Y coordinate of the region's top-left corner
+/// @param width - Width of the region (clamped to the image)
+/// @param height - Height of the region (clamped to the image)
+/// @returns The suggested colors and the region's luminance
 #[tauri::command]
-async fn crop_image(
+async fn suggest_text_color(
     image_data: ImageData,
     x: u32,
     y: u32,
     width: u32,
     height: u32,
-) -> Result<ImageData, String> {
-    // Validate input parameters
-    if width == 0 || height == 0 {
+) -> Result<TextColorSuggestion, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    let (x, y, width, height) = constrain_crop_region(img.width(), img.height(), x, y, width, height);
+    let region = img.crop_imm(x, y, width, height).to_rgb8();
+    
+    // Average in sRGB for the displayed color, in linear light for luminance
+    let pixel_count = region.pixels().len() as f64;
+    let mut srgb_sums = [0.0f64; 3];
+    let mut linear_sums = [0.0f64; 3];
+    for pixel in region.pixels() {
+        for channel in 0..3 {
+            srgb_sums[channel] += pixel.0[channel] as f64;
+            linear_sums[channel] += srgb_to_linear(pixel.0[channel]) as f64;
+        }
+    }
+    let average = srgb_sums.map(|sum| (sum / pixel_count).round() as u8);
+    let [r, g, b] = linear_sums.map(|sum| sum / pixel_count);
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    
+    let white_contrast = 1.05 / (luminance + 0.05);
+    let black_contrast = (luminance + 0.05) / 0.05;
+    let (color, contrast_ratio) = if white_contrast > black_contrast {
+        (RGBColor { r: 255, g: 255, b: 255 }, white_contrast)
+    } else {
+        (RGBColor { r: 0, g: 0, b: 0 }, black_contrast)
+    };
+    
+    Ok(TextColorSuggestion {
+        color,
+        complementary: RGBColor {
+            r: 255 - average[0],
+            g: 255 - average[1],
+            b: 255 - average[2],
+        },
+        luminance,
+        contrast_ratio,
+    })
+}
+
+/// Convert an RGB color to HSL (hue in degrees, saturation and lightness in 0.0-1.0)
+fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let r = r as f32 / 255.0;
+    let g = g as f32 / 255.0;
+    let b = b as f32 / 255.0;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = if l > 0.5 {
+        delta / (2.0 - max - min)
+    } else {
+        delta / (max + min)
+    };
+
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    } * 60.0;
+
+    (h, s, l)
+}
+
+/// Convert an HSL color (hue in degrees, saturation and lightness in 0.0-1.0) to RGB
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h_prime = h / 60.0;
+    let x = c * (1.0 - (h_prime.rem_euclid(2.0) - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r, g, b) = match h_prime as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// Render an edit history as a labeled filmstrip
+/// 
+/// Each snapshot of the undo stack is thumbnailed into a square cell laid out left
+/// to right, with its step number and operation name drawn underneath.
+/// 
+/// @param snapshots - Images of each editing step, oldest first
+/// @param labels - Operation name for each snapshot (same length as snapshots)
+/// @param thumbnail_size - Size of each thumbnail cell in pixels (default 160)
+/// @returns New PNG ImageData containing the filmstrip
+#[tauri::command]
+async fn render_edit_timeline(
+    snapshots: Vec<ImageData>,
+    labels: Vec<String>,
+    thumbnail_size: Option<u32>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    use ab_glyph::PxScale;
+    use imageproc::drawing::{draw_text_mut, text_size};
+    
+    const GAP: u32 = 8;
+    
+    if snapshots.is_empty() {
         return Err(AppError::InvalidParameters(
-            "Width and height must be positive integers".to_string()
+            "No snapshots provided".to_string()
         ).into());
     }
     
-    // Constrain crop region to image boundaries
-    let constrained_x = x.min(image_data.width.saturating_sub(1));
-    let constrained_y = y.min(image_data.height.saturating_sub(1));
+    if snapshots.len() != labels.len() {
+        return Err(AppError::InvalidParameters(
+            format!("Expected {} labels, got {}", snapshots.len(), labels.len())
+        ).into());
+    }
     
-    // Calculate maximum available width and height from the constrained position
-    let max_width = image_data.width.saturating_sub(constrained_x);
-    let max_height = image_data.height.saturating_sub(constrained_y);
+    let cell = thumbnail_size.unwrap_or(160);
+    if cell == 0 {
+        return Err(AppError::InvalidParameters(
+            "Thumbnail size must be a positive integer".to_string()
+        ).into());
+    }
     
-    let constrained_width = width.min(max_width).max(1);
-    let constrained_height = height.min(max_height).max(1);
+    let font_size = (cell / 10).max(12);
+    let caption_height = font_size * 2;
+    let count = snapshots.len() as u32;
+    let width = count * cell + (count + 1) * GAP;
+    let height = cell + caption_height + 2 * GAP;
     
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
-        .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
+    let mut strip = image::RgbaImage::from_pixel(width, height, Rgba([32, 32, 32, 255]));
     
-    // Load image from decoded data
-    let img = image::load_from_memory(&decoded_data)
-        .map_err(AppError::ImageError)?;
+    // Captions are best effort: without a font the thumbnails are still useful
+    let font = match load_font_from_directory() {
+        Ok(font) => Some(font),
+        Err(e) => {
+            println!("Rendering edit timeline without captions: {}", e);
+            None
+        }
+    };
+    let scale = PxScale::from(font_size as f32);
     
-    // Crop the image
-    let cropped = img.crop_imm(constrained_x, constrained_y, constrained_width, constrained_height);
+    for (index, (snapshot, label)) in snapshots.iter().zip(&labels).enumerate() {
+        let thumbnail = thumbnail_image(&decode_image_data(snapshot)?, cell, &ResampleOptions::balanced())
+            .to_rgba8();
+        
+        let cell_x = GAP + index as u32 * (cell + GAP);
+        
+        // Center the thumbnail inside its cell
+        let thumb_x = cell_x + (cell - thumbnail.width()) / 2;
+        let thumb_y = GAP + (cell - thumbnail.height()) / 2;
+        image::imageops::overlay(&mut strip, &thumbnail, thumb_x as i64, thumb_y as i64);
+        
+        if let Some(font) = &font {
+            let caption = format!("{}. {}", index + 1, label);
+            let (text_width, _) = text_size(scale, font, &caption);
+            let text_x = cell_x as i32 + (cell as i32 - text_width as i32).max(0) / 2;
+            let text_y = (GAP + cell + font_size / 2) as i32;
+            draw_text_mut(
+                &mut strip,
+                Rgba([230, 230, 230, 255]),
+                text_x,
+                text_y,
+                scale,
+                font,
+                &caption,
+            );
+        }
+    }
     
-    // Encode to the same format as the original
-    let mut output_buffer = Vec::new();
-    let format = image_data.format.to_image_format()
-        .ok_or_else(|| AppError::UnsupportedFormat(
-            format!("Cannot crop {} format", image_data.format)
-        ))?;
+    let result_img = DynamicImage::ImageRgba8(strip);
     
-    cropped.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
-        .map_err(AppError::ImageError)?;
+    Ok(encode_image_data(&result_img, "edit_timeline.png".to_string(), ImageFormat::PNG)?)
+}
+
+/// Compute objective quality metrics between an original and a processed image
+/// 
+/// Useful for tuning lossy encoder settings. PSNR is infinite (null in JSON)
+/// when the images are identical.
+/// 
+/// @param original - The reference image
+/// @param compressed - The processed image, with the same dimensions
+/// @returns PSNR in dB and mean SSIM
+#[tauri::command]
+async fn compute_quality_metrics(
+    original: ImageData,
+    compressed: ImageData,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<QualityMetrics, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let original_img = decode_image_data(&original)?;
+    let compressed_img = decode_image_data(&compressed)?;
+    
+    Ok(quality::compute_quality_metrics(&original_img, &compressed_img)?)
+}
+
+/// Compare two images pixel by pixel
+/// 
+/// Useful to confirm that a lossless conversion didn't change any pixels.
+/// 
+/// @param a - First image
+/// @param b - Second image, with the same dimensions as the first
+/// @returns Mean absolute error, largest channel difference and the
+///   percentage of identical pixels
+#[tauri::command]
+async fn compare_images(
+    a: ImageData,
+    b: ImageData,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageComparison, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let img_a = decode_image_data(&a)?;
+    let img_b = decode_image_data(&b)?;
+    
+    Ok(quality::compare_images(&img_a, &img_b)?)
+}
+
+/// Render a heatmap of where two images differ
+/// 
+/// Each pixel's brightness is the largest channel difference between the
+/// inputs at that position; identical areas are black.
+/// 
+/// @param a - First image
+/// @param b - Second image, with the same dimensions as the first
+/// @returns Grayscale PNG ImageData of the differences
+#[tauri::command]
+async fn diff_heatmap(
+    a: ImageData,
+    b: ImageData,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    let img_a = decode_image_data(&a)?;
+    let img_b = decode_image_data(&b)?;
     
-    // Detect alpha channel in cropped image
-    let has_alpha = detect_alpha_channel(&cropped);
+    let heatmap = quality::diff_heatmap(&img_a, &img_b)?;
+    let path = update_file_extension(&path_with_suffix(&a.path, "_diff"), &ImageFormat::PNG);
     
-    // Return new ImageData with updated dimensions
-    Ok(ImageData {
-        path: image_data.path,
-        width: constrained_width,
-        height: constrained_height,
-        format: image_data.format,
-        data: base64_data,
-        has_alpha,
-    })
+    Ok(encode_image_data(&DynamicImage::ImageLuma8(heatmap), path, ImageFormat::PNG)?)
 }
 
-/// Set background color for transparent images
+/// Compute a perceptual hash of an image for finding near-duplicates
 /// 
-/// Replaces transparent pixels with the specified RGB color.
-/// Only works on images with an alpha channel (hasAlpha = true).
+/// The 64-bit difference hash barely changes when an image is resized or
+/// recompressed. Compare hashes with hamming_distance.
 /// 
-/// @param image_data - The image to process (must have alpha channel)
-/// @param r - Red component (0-255)
-/// @param g - Green component (0-255)
-/// @param b - Blue component (0-255)
-/// @returns New ImageData with background applied to transparent areas
+/// @param image_data - The image to hash
+/// @returns The hash as 16 hex digits
 #[tauri::command]
-async fn set_background(
+async fn perceptual_hash(image_data: ImageData) -> Result<String, CommandError> {
+    let img = decode_image_data(&image_data)?;
+    
+    Ok(phash::to_hex(phash::difference_hash(&img)))
+}
+
+/// Count the bits that differ between two perceptual hashes
+/// 
+/// Near-duplicates usually differ in no more than about 10 of the 64 bits.
+/// 
+/// @param hash_a - Hash returned by perceptual_hash
+/// @param hash_b - Hash returned by perceptual_hash
+/// @returns Hamming distance (0-64)
+#[tauri::command]
+async fn hamming_distance(hash_a: String, hash_b: String) -> Result<u32, CommandError> {
+    Ok(phash::hamming_distance(phash::from_hex(&hash_a)?, phash::from_hex(&hash_b)?))
+}
+
+/// Detect visible banding from over-quantization
+/// 
+/// Scores how much of the change in smooth regions happens as hard steps
+/// between flat bands, and reports how many bits of precision each color
+/// channel really uses.
+/// 
+/// @param image_data - The image to analyze
+/// @returns Banding score (0.0-1.0) and effective bit depth per channel
+#[tauri::command]
+async fn detect_banding(
     image_data: ImageData,
-    r: u8,
-    g: u8,
-    b: u8,
-) -> Result<ImageData, String> {
-    // Validate that the image has an alpha channel
-    if !image_data.has_alpha {
-        return Err(AppError::InvalidParameters(
-            "Image does not have transparency. Background setting is only applicable to transparent images.".to_string()
-        ).into());
-    }
+    limiter: State<'_, OperationLimiter>,
+) -> Result<BandingReport, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
-        .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
+    let img = decode_image_data(&image_data)?;
     
-    // Load image from decoded data
-    let img = image::load_from_memory(&decoded_data)
-        .map_err(AppError::ImageError)?;
+    Ok(quality::detect_banding(&img))
+}
+
+// ============================================================================
+// Pipeline and Macro Commands
+// ============================================================================
+
+/// Progress of a macro replay, emitted as the "macro-progress" event
+#[derive(Clone, serde::Serialize)]
+struct MacroProgress {
+    current: usize,
+    total: usize,
+    path: String,
+}
+
+/// Apply a sequence of editing steps to an image
+/// 
+/// @param image_data - The image to process
+/// @param steps - Steps to apply in order
+/// @returns New ImageData with every step applied
+#[tauri::command]
+async fn apply_pipeline(
+    image_data: ImageData,
+    steps: Vec<PipelineStep>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // Convert to RGBA8 for processing
-    let mut rgba_img = img.to_rgba8();
+    let img = decode_image_data(&image_data)?;
+    let result_img = pipeline::apply_pipeline(img, &steps)?;
     
-    // Apply background color to transparent pixels
-    for pixel in rgba_img.pixels_mut() {
-        let alpha = pixel.0[3];
-        
-        if alpha < 255 {
-            // Blend the background color with the existing pixel based on alpha
-            let alpha_f = alpha as f32 / 255.0;
-            let inv_alpha = 1.0 - alpha_f;
-            
-            // Alpha blending: result = foreground * alpha + background * (1 - alpha)
-            pixel.0[0] = ((pixel.0[0] as f32 * alpha_f) + (r as f32 * inv_alpha)) as u8;
-            pixel.0[1] = ((pixel.0[1] as f32 * alpha_f) + (g as f32 * inv_alpha)) as u8;
-            pixel.0[2] = ((pixel.0[2] as f32 * alpha_f) + (b as f32 * inv_alpha)) as u8;
-            pixel.0[3] = 255; // Set alpha to fully opaque
-        }
+    Ok(encode_image_data(&result_img, image_data.path, image_data.format)?)
+}
+
+/// Save a pipeline as a JSON macro file
+#[tauri::command]
+async fn save_macro(path: String, steps: Vec<PipelineStep>) -> Result<(), CommandError> {
+    pipeline::save_macro(Path::new(&path), &steps)?;
+    println!("Saved macro with {} step(s) to {}", steps.len(), path);
+    Ok(())
+}
+
+/// Load a pipeline from a JSON macro file
+#[tauri::command]
+async fn load_macro(path: String) -> Result<Vec<PipelineStep>, CommandError> {
+    Ok(pipeline::load_macro(Path::new(&path))?)
+}
+
+/// Load an image and apply the edits recorded in its sidecar
+/// 
+/// The sidecar is the `<name>.edits.json` file next to the image, written by
+/// save_sidecar. The file on disk is never modified; the recorded steps are
+/// returned alongside the edited image so editing can continue
+/// non-destructively. Without a sidecar the image is returned unchanged.
+/// 
+/// @param path - Path of the image file
+/// @returns The edited image and the recorded steps
+#[tauri::command]
+async fn load_image_with_sidecar(
+    path: String,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<SidecarImage, CommandError> {
+    let image = load_image(path, None, None).await?;
+    let steps = pipeline::load_sidecar(Path::new(&image.path))?;
+    
+    if steps.is_empty() {
+        return Ok(SidecarImage { image, steps });
     }
     
-    // Convert back to DynamicImage
-    let result_img = DynamicImage::ImageRgba8(rgba_img);
-    
-    // Encode to the same format as the original
-    let mut output_buffer = Vec::new();
-    let format = image_data.format.to_image_format()
-        .ok_or_else(|| AppError::UnsupportedFormat(
-            format!("Cannot process {} format", image_data.format)
-        ))?;
-    
-    result_img.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
-        .map_err(AppError::ImageError)?;
-    
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
     
-    // After applying background, the image no longer has transparency
-    let has_alpha = false;
+    let img = decode_image_data(&image)?;
+    let edited = pipeline::apply_pipeline(img, &steps)?;
+    let image = encode_image_data(&edited, image.path, image.format)?;
     
-    // Return new ImageData with background applied
-    Ok(ImageData {
-        path: image_data.path,
-        width: image_data.width,
-        height: image_data.height,
-        format: image_data.format,
-        data: base64_data,
-        has_alpha,
-    })
+    Ok(SidecarImage { image, steps })
+}
+
+/// Record the edits of an image in its `<name>.edits.json` sidecar
+/// 
+/// Saving an empty list removes the sidecar.
+/// 
+/// @param path - Path of the image file
+/// @param steps - Steps to record, in order
+/// @returns Path of the sidecar file
+#[tauri::command]
+async fn save_sidecar(path: String, steps: Vec<PipelineStep>) -> Result<String, CommandError> {
+    let sidecar = pipeline::save_sidecar(Path::new(&path), &steps)?;
+    Ok(sidecar.to_string_lossy().to_string())
 }
 
-/// Rotate an image by 90 degrees clockwise or counter-clockwise
+/// Replay a saved macro over every image in a directory
 /// 
-/// @param image_data - ImageData object containing the image to rotate
-/// @param clockwise - If true, rotate 90° clockwise; if false, rotate 90° counter-clockwise
-/// @returns New ImageData with rotated image (width and height are swapped)
+/// Emits a "macro-progress" event after each file. Results are written to
+/// output_dir; files that fail are reported without stopping the batch.
+/// 
+/// @param macro_path - Path of the macro file to replay
+/// @param dir_path - Directory containing the images to process
+/// @param output_dir - Directory receiving the processed images
+/// @param name_template - Output path template such as "{year}/{month}/{camera}_{index}"
+///   (default: keep the original file names)
+/// @param collision_strategy - "skip", "overwrite" or "rename" when an output file
+///   already exists (default "overwrite")
+/// @returns Per-file results in processing order
 #[tauri::command]
-async fn rotate_image(
-    image_data: ImageData,
-    clockwise: bool,
-) -> Result<ImageData, String> {
-    // Decode Base64 data
-    let decoded_data = general_purpose::STANDARD
-        .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode Base64: {}", e)))?;
-    
-    // Load image from decoded data
-    let img = image::load_from_memory(&decoded_data)
-        .map_err(AppError::ImageError)?;
-    
-    // Rotate the image
-    let rotated = if clockwise {
-        img.rotate90()
-    } else {
-        img.rotate270()
-    };
-    
-    // Encode to the same format as the original
-    let mut output_buffer = Vec::new();
-    let format = image_data.format.to_image_format()
-        .ok_or_else(|| AppError::UnsupportedFormat(
-            format!("Cannot rotate {} format", image_data.format)
-        ))?;
-    
-    rotated.write_to(&mut std::io::Cursor::new(&mut output_buffer), format)
-        .map_err(AppError::ImageError)?;
-    
-    // Encode to Base64
-    let base64_data = general_purpose::STANDARD.encode(&output_buffer);
+#[allow(clippy::too_many_arguments)]
+async fn apply_macro_to_directory(
+    app: AppHandle,
+    macro_path: String,
+    dir_path: String,
+    output_dir: String,
+    name_template: Option<String>,
+    collision_strategy: Option<CollisionStrategy>,
+    limiter: State<'_, OperationLimiter>,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<Vec<MacroFileResult>, CommandError> {
+    // Registered before waiting so a cancel while queued is seen
+    let cancel = cancel_registry.start();
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    cancel.check()?;
+    
+    let steps = pipeline::load_macro(Path::new(&macro_path))?;
+    
+    let results = pipeline::apply_macro_to_directory(
+        &steps,
+        Path::new(&dir_path),
+        Path::new(&output_dir),
+        name_template.as_deref(),
+        collision_strategy.unwrap_or_default(),
+        cancel.flag(),
+        |current, total, path| {
+            let progress = MacroProgress { current, total, path: path.to_string() };
+            if let Err(e) = app.emit("macro-progress", progress) {
+                eprintln!("Failed to emit macro progress: {}", e);
+            }
+        },
+    )?;
     
-    // Detect alpha channel in rotated image
-    let has_alpha = detect_alpha_channel(&rotated);
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    println!("Macro processed {} file(s), {} failed", results.len(), failed);
     
-    // Return new ImageData with swapped dimensions
-    Ok(ImageData {
-        path: image_data.path,
-        width: rotated.width(),
-        height: rotated.height(),
-        format: image_data.format,
-        data: base64_data,
-        has_alpha,
-    })
+    Ok(results)
+}
+
+/// Generate a cover image for a collection
+/// 
+/// Lays out the first four images in a 2x2 grid (a single image fills the
+/// cover). Empty collections get a gradient placeholder.
+/// 
+/// @param paths - Paths of the images in the collection, in display order
+/// @param size - Side length of the square cover in pixels (default 256)
+/// @returns PNG ImageData of the cover
+#[tauri::command]
+async fn generate_collection_cover(
+    paths: Vec<String>,
+    size: Option<u32>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let member_paths: Vec<&Path> = paths.iter().map(Path::new).collect();
+    let cover = cover::render_collection_cover(&member_paths, size.unwrap_or(256))?;
+    
+    Ok(encode_image_data(&DynamicImage::ImageRgba8(cover), "cover.png".to_string(), ImageFormat::PNG)?)
+}
+
+/// Create a contact sheet: many images laid out in a grid
+/// 
+/// Every image is scaled to fit a square cell, centered in it, and the cells
+/// are separated by gap pixels of background. The sheet is
+/// columns * cell_size + (columns + 1) * gap pixels wide, with as many rows as
+/// needed; a final partial row is left-aligned.
+/// 
+/// @param paths - Paths of the images, in reading order
+/// @param columns - Number of cells per row
+/// @param cell_size - Side length of each cell in pixels
+/// @param gap - Space between cells and around the edge in pixels
+/// @param background - Background color as #RRGGBB
+/// @returns PNG ImageData of the sheet
+#[tauri::command]
+async fn create_contact_sheet(
+    paths: Vec<String>,
+    columns: u32,
+    cell_size: u32,
+    gap: u32,
+    background: String,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let (r, g, b) = parse_hex_color(&background).map_err(AppError::InvalidParameters)?;
+    
+    let member_paths: Vec<&Path> = paths.iter().map(Path::new).collect();
+    let sheet = cover::render_contact_sheet(&member_paths, columns, cell_size, gap, Rgba([r, g, b, 255]))?;
+    
+    Ok(encode_image_data(&DynamicImage::ImageRgba8(sheet), "contact_sheet.png".to_string(), ImageFormat::PNG)?)
 }
 
 // ============================================================================
@@ -825,7 +4330,7 @@ async fn rotate_image(
 
 /// Get all favorite images
 #[tauri::command]
-async fn get_all_favorites() -> Result<Vec<FavoriteImage>, String> {
+async fn get_all_favorites() -> Result<Vec<FavoriteImage>, CommandError> {
     let config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
     Ok(config.get_all())
@@ -833,7 +4338,7 @@ async fn get_all_favorites() -> Result<Vec<FavoriteImage>, String> {
 
 /// Add an image to favorites with tags
 #[tauri::command]
-async fn add_favorite(path: String, tags: Vec<String>) -> Result<(), String> {
+async fn add_favorite(path: String, tags: Vec<String>) -> Result<(), CommandError> {
     let mut config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
     
@@ -846,7 +4351,7 @@ async fn add_favorite(path: String, tags: Vec<String>) -> Result<(), String> {
 
 /// Remove an image from favorites
 #[tauri::command]
-async fn remove_favorite(path: String) -> Result<bool, String> {
+async fn remove_favorite(path: String) -> Result<bool, CommandError> {
     let mut config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
     
@@ -860,36 +4365,301 @@ async fn remove_favorite(path: String) -> Result<bool, String> {
     Ok(removed)
 }
 
+/// Set or clear the note of a favorite image
+/// 
+/// Returns false if the image is not a favorite
+#[tauri::command]
+async fn update_favorite_note(path: String, note: Option<String>) -> Result<bool, CommandError> {
+    let mut config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    
+    let updated = config.update_note(&path, note);
+    
+    if updated {
+        config.save()
+            .map_err(|e| e.to_string())?;
+    }
+    
+    Ok(updated)
+}
+
+/// Record that a favorite image was just viewed
+/// 
+/// Returns false if the image is not a favorite
+#[tauri::command]
+async fn touch_favorite(path: String) -> Result<bool, CommandError> {
+    let mut config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    
+    let touched = config.touch(&path);
+    
+    if touched {
+        config.save()
+            .map_err(|e| e.to_string())?;
+    }
+    
+    Ok(touched)
+}
+
+/// List favorites whose file has been moved or deleted
+#[tauri::command]
+async fn list_missing_favorites() -> Result<Vec<String>, CommandError> {
+    let config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    Ok(config.list_missing())
+}
+
+/// Remove favorites whose file has been moved or deleted
+/// 
+/// Returns the removed paths
+#[tauri::command]
+async fn prune_missing_favorites() -> Result<Vec<String>, CommandError> {
+    let mut config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    
+    let removed = config.prune_missing();
+    
+    if !removed.is_empty() {
+        config.save()
+            .map_err(|e| e.to_string())?;
+    }
+    
+    Ok(removed)
+}
+
 /// Check if an image is favorited
 #[tauri::command]
-async fn is_favorite(path: String) -> Result<bool, String> {
+async fn is_favorite(path: String) -> Result<bool, CommandError> {
     let config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
     Ok(config.is_favorite(&path))
 }
 
 /// Search favorites by tags
+/// 
+/// match_all requires every tag to match (AND) instead of any (OR);
+/// exact disables substring matching. Both default to false.
+#[tauri::command]
+async fn search_favorites_by_tags(
+    tags: Vec<String>,
+    match_all: Option<bool>,
+    exact: Option<bool>,
+) -> Result<Vec<FavoriteImage>, CommandError> {
+    let config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    Ok(config.search_by_tags(&tags, match_all.unwrap_or(false), exact.unwrap_or(false)))
+}
+
+/// Search favorites by camera or lens
+/// 
+/// Matches the camera make, camera model and lens model read from EXIF
+/// (case-insensitive substring)
 #[tauri::command]
-async fn search_favorites_by_tags(tags: Vec<String>) -> Result<Vec<FavoriteImage>, String> {
+async fn search_favorites_by_camera(query: String) -> Result<Vec<FavoriteImage>, CommandError> {
     let config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
-    Ok(config.search_by_tags(&tags))
+    Ok(config.search_by_camera(&query))
+}
+
+/// Read camera, lens and exposure information from an image's EXIF data
+/// 
+/// @param path - Path to a JPEG or TIFF file
+/// @returns The camera information, or None if the file has no EXIF data
+#[tauri::command]
+async fn get_camera_info(path: String) -> Result<Option<CameraInfo>, CommandError> {
+    Ok(camera::read_camera_info(Path::new(&path)))
 }
 
 /// Get all unique tags from favorites
 #[tauri::command]
-async fn get_all_tags() -> Result<Vec<String>, String> {
+async fn get_all_tags() -> Result<Vec<String>, CommandError> {
     let config = FavoritesConfig::load()
         .map_err(|e| e.to_string())?;
     Ok(config.get_all_tags())
 }
 
+/// Rename a tag across all favorites
+/// 
+/// Returns the number of favorites that were changed
+#[tauri::command]
+async fn rename_tag(old_tag: String, new_tag: String) -> Result<usize, CommandError> {
+    let new_tag = new_tag.trim().to_string();
+    if new_tag.is_empty() {
+        return Err(AppError::InvalidParameters(
+            "New tag name cannot be empty".to_string()
+        ).into());
+    }
+    
+    let mut config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    
+    let affected = config.rename_tag(&old_tag, &new_tag);
+    
+    if affected > 0 {
+        config.save()
+            .map_err(|e| e.to_string())?;
+    }
+    
+    Ok(affected)
+}
+
+/// Export images as a static HTML gallery
+/// 
+/// Writes an index.html together with copies and thumbnails of the images into
+/// output_dir. Tags and notes are taken from favorites; images that are not
+/// favorites are exported without them. collision_strategy ("skip", "overwrite"
+/// or "rename", default "overwrite") decides what happens to files left over
+/// from an earlier export.
+/// Returns the path of the generated index.html
+#[tauri::command]
+async fn export_gallery_html(
+    paths: Vec<String>,
+    output_dir: String,
+    collision_strategy: Option<CollisionStrategy>,
+    limiter: State<'_, OperationLimiter>,
+) -> Result<String, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
+    let config = FavoritesConfig::load()
+        .map_err(|e| e.to_string())?;
+    
+    let entries: Vec<FavoriteImage> = paths
+        .into_iter()
+        .map(|path| {
+            config.favorites
+                .get(&path)
+                .cloned()
+                .unwrap_or(FavoriteImage { path, ..Default::default() })
+        })
+        .collect();
+    
+    let index_path = gallery::export_gallery(&entries, Path::new(&output_dir), collision_strategy.unwrap_or_default())?;
+    
+    Ok(index_path.to_string_lossy().to_string())
+}
+
 /// Check if a file exists
 #[tauri::command]
-async fn file_exists(path: String) -> Result<bool, String> {
+async fn file_exists(path: String) -> Result<bool, CommandError> {
     Ok(Path::new(&path).exists())
 }
 
+/// Alpha-blend an overlay onto a base image, rotated around its own center
+///
+/// (x, y) is the overlay's top-left corner before rotation. The overlay's alpha
+/// is multiplied by `opacity` and fully transparent samples are skipped.
+/// `cancelled` is checked before every row.
+fn composite_overlay(
+    base_rgba: &mut image::RgbaImage,
+    overlay: &image::RgbaImage,
+    (x, y): (u32, u32),
+    rotation: f32,
+    opacity: f32,
+    cancelled: &AtomicBool,
+) -> AppResult<()> {
+    let (width, height) = overlay.dimensions();
+    
+    // Calculate rotation parameters
+    let rotation_radians = rotation * std::f32::consts::PI / 180.0;
+    let cos_angle = rotation_radians.cos();
+    let sin_angle = rotation_radians.sin();
+    
+    // Calculate the center of the overlay in the base image
+    let center_x = x as f32 + (width as f32 / 2.0);
+    let center_y = y as f32 + (height as f32 / 2.0);
+    
+    // Calculate the bounds of the rotated overlay
+    let half_width = width as f32 / 2.0;
+    let half_height = height as f32 / 2.0;
+    
+    // Only base pixels inside the rotated overlay's bounding box can receive an
+    // overlay pixel, so skip the rest of the (possibly much larger) base image
+    let corners = [
+        (-half_width, -half_height),
+        (half_width, -half_height),
+        (-half_width, half_height),
+        (half_width, half_height),
+    ].map(|(dx, dy)| (
+        center_x + dx * cos_angle - dy * sin_angle,
+        center_y + dx * sin_angle + dy * cos_angle,
+    ));
+    let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let max_x = ((corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil() + 1.0).max(0.0) as u32)
+        .min(base_rgba.width());
+    let max_y = ((corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil() + 1.0).max(0.0) as u32)
+        .min(base_rgba.height());
+    
+    // For each pixel in the bounding box, check if it should receive a rotated overlay pixel
+    for base_y in min_y..max_y {
+        limiter::check_cancelled(cancelled)?;
+        for base_x in min_x..max_x {
+            // Translate to sticker center coordinates
+            let dx = base_x as f32 - center_x;
+            let dy = base_y as f32 - center_y;
+            
+            // Apply inverse rotation to find source pixel in original overlay
+            let src_x = dx * cos_angle + dy * sin_angle + half_width;
+            let src_y = -dx * sin_angle + dy * cos_angle + half_height;
+            
+            // Check if the source coordinates are within the overlay bounds
+            if src_x >= 0.0 && src_x < width as f32 && 
+               src_y >= 0.0 && src_y < height as f32 {
+                
+                // Use bilinear interpolation for smooth rotation; every neighbour
+                // is clamped so sampling never leaves the overlay
+                let x0 = (src_x.floor() as u32).min(width - 1);
+                let y0 = (src_y.floor() as u32).min(height - 1);
+                let x1 = (x0 + 1).min(width - 1);
+                let y1 = (y0 + 1).min(height - 1);
+                
+                let fx = src_x - x0 as f32;
+                let fy = src_y - y0 as f32;
+                
+                // Get the four surrounding pixels
+                let p00 = overlay.get_pixel(x0, y0);
+                let p10 = overlay.get_pixel(x1, y0);
+                let p01 = overlay.get_pixel(x0, y1);
+                let p11 = overlay.get_pixel(x1, y1);
+                
+                // Bilinear interpolation
+                let interpolated_pixel = Rgba([
+                    ((p00.0[0] as f32 * (1.0 - fx) + p10.0[0] as f32 * fx) * (1.0 - fy) +
+                     (p01.0[0] as f32 * (1.0 - fx) + p11.0[0] as f32 * fx) * fy) as u8,
+                    ((p00.0[1] as f32 * (1.0 - fx) + p10.0[1] as f32 * fx) * (1.0 - fy) +
+                     (p01.0[1] as f32 * (1.0 - fx) + p11.0[1] as f32 * fx) * fy) as u8,
+                    ((p00.0[2] as f32 * (1.0 - fx) + p10.0[2] as f32 * fx) * (1.0 - fy) +
+                     (p01.0[2] as f32 * (1.0 - fx) + p11.0[2] as f32 * fx) * fy) as u8,
+                    ((p00.0[3] as f32 * (1.0 - fx) + p10.0[3] as f32 * fx) * (1.0 - fy) +
+                     (p01.0[3] as f32 * (1.0 - fx) + p11.0[3] as f32 * fx) * fy) as u8,
+                ]);
+                
+                // Fully transparent samples leave the base untouched, so skip the blending work
+                let overlay_alpha = interpolated_pixel.0[3] as f32 / 255.0 * opacity;
+                if overlay_alpha <= 0.0 {
+                    continue;
+                }
+                
+                // Apply alpha blending
+                let base_pixel = base_rgba.get_pixel_mut(base_x, base_y);
+                let inv_alpha = 1.0 - overlay_alpha;
+                
+                // Blend RGB channels
+                base_pixel.0[0] = ((base_pixel.0[0] as f32 * inv_alpha) + (interpolated_pixel.0[0] as f32 * overlay_alpha)) as u8;
+                base_pixel.0[1] = ((base_pixel.0[1] as f32 * inv_alpha) + (interpolated_pixel.0[1] as f32 * overlay_alpha)) as u8;
+                base_pixel.0[2] = ((base_pixel.0[2] as f32 * inv_alpha) + (interpolated_pixel.0[2] as f32 * overlay_alpha)) as u8;
+                
+                // Combine alpha channels
+                let combined_alpha = (base_pixel.0[3] as f32 / 255.0) * inv_alpha + overlay_alpha;
+                base_pixel.0[3] = (combined_alpha * 255.0) as u8;
+            }
+        }
+    }
+    
+    Ok(())
+}
+
 /// Apply stickers to an image
 /// 
 /// Composites multiple sticker images onto a base image at specified positions,
@@ -903,7 +4673,15 @@ async fn file_exists(path: String) -> Result<bool, String> {
 async fn apply_stickers(
     image_data: ImageData,
     stickers: Vec<StickerData>,
-) -> Result<ImageData, String> {
+    limiter: State<'_, OperationLimiter>,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<ImageData, CommandError> {
+    // Registered before waiting so a cancel while queued is seen
+    let cancel = cancel_registry.start();
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    cancel.check()?;
+    
     if stickers.is_empty() {
         return Err(AppError::InvalidParameters(
             "No stickers provided".to_string()
@@ -913,7 +4691,7 @@ async fn apply_stickers(
     // Decode Base64 data for the base image
     let decoded_data = general_purpose::STANDARD
         .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode base image Base64: {}", e)))?;
+        .map_err(AppError::Base64Error)?;
     
     // Load base image from decoded data
     let base_img = image::load_from_memory(&decoded_data)
@@ -924,6 +4702,8 @@ async fn apply_stickers(
     
     // Apply each sticker
     for (index, sticker) in stickers.iter().enumerate() {
+        cancel.check()?;
+        
         // Validate sticker parameters
         if sticker.width == 0 || sticker.height == 0 {
             return Err(AppError::InvalidParameters(
@@ -931,12 +4711,16 @@ async fn apply_stickers(
             ).into());
         }
         
+        if !(0.0..=1.0).contains(&sticker.opacity) {
+            return Err(AppError::InvalidParameters(
+                format!("Sticker {} opacity must be between 0.0 and 1.0", index)
+            ).into());
+        }
+        
         // Decode sticker image data
         let sticker_decoded = general_purpose::STANDARD
             .decode(&sticker.image_data)
-            .map_err(|e| AppError::InvalidImageData(
-                format!("Failed to decode sticker {} Base64: {}", index, e)
-            ))?;
+            .map_err(AppError::Base64Error)?;
         
         // Load sticker image
         let sticker_img = image::load_from_memory(&sticker_decoded)
@@ -957,77 +4741,15 @@ async fn apply_stickers(
         // Convert sticker to RGBA8 for processing
         let sticker_rgba = resized_sticker.to_rgba8();
         
-        // Calculate rotation parameters
-        let rotation_radians = sticker.rotation * std::f32::consts::PI / 180.0;
-        let cos_angle = rotation_radians.cos();
-        let sin_angle = rotation_radians.sin();
-        
-        // Calculate the center of the sticker in the base image
-        let center_x = sticker.x as f32 + (sticker.width as f32 / 2.0);
-        let center_y = sticker.y as f32 + (sticker.height as f32 / 2.0);
-        
-        // Calculate the bounds of the rotated sticker
-        let half_width = sticker.width as f32 / 2.0;
-        let half_height = sticker.height as f32 / 2.0;
-        
-        // For each pixel in the base image, check if it should receive a rotated sticker pixel
-        for base_y in 0..base_rgba.height() {
-            for base_x in 0..base_rgba.width() {
-                // Translate to sticker center coordinates
-                let dx = base_x as f32 - center_x;
-                let dy = base_y as f32 - center_y;
-                
-                // Apply inverse rotation to find source pixel in original sticker
-                let src_x = dx * cos_angle + dy * sin_angle + half_width;
-                let src_y = -dx * sin_angle + dy * cos_angle + half_height;
-                
-                // Check if the source coordinates are within the sticker bounds
-                if src_x >= 0.0 && src_x < sticker.width as f32 && 
-                   src_y >= 0.0 && src_y < sticker.height as f32 {
-                    
-                    // Use bilinear interpolation for smooth rotation
-                    let x0 = src_x.floor() as u32;
-                    let y0 = src_y.floor() as u32;
-                    let x1 = (x0 + 1).min(sticker.width - 1);
-                    let y1 = (y0 + 1).min(sticker.height - 1);
-                    
-                    let fx = src_x - x0 as f32;
-                    let fy = src_y - y0 as f32;
-                    
-                    // Get the four surrounding pixels
-                    let p00 = sticker_rgba.get_pixel(x0, y0);
-                    let p10 = sticker_rgba.get_pixel(x1, y0);
-                    let p01 = sticker_rgba.get_pixel(x0, y1);
-                    let p11 = sticker_rgba.get_pixel(x1, y1);
-                    
-                    // Bilinear interpolation
-                    let interpolated_pixel = Rgba([
-                        ((p00.0[0] as f32 * (1.0 - fx) + p10.0[0] as f32 * fx) * (1.0 - fy) +
-                         (p01.0[0] as f32 * (1.0 - fx) + p11.0[0] as f32 * fx) * fy) as u8,
-                        ((p00.0[1] as f32 * (1.0 - fx) + p10.0[1] as f32 * fx) * (1.0 - fy) +
-                         (p01.0[1] as f32 * (1.0 - fx) + p11.0[1] as f32 * fx) * fy) as u8,
-                        ((p00.0[2] as f32 * (1.0 - fx) + p10.0[2] as f32 * fx) * (1.0 - fy) +
-                         (p01.0[2] as f32 * (1.0 - fx) + p11.0[2] as f32 * fx) * fy) as u8,
-                        ((p00.0[3] as f32 * (1.0 - fx) + p10.0[3] as f32 * fx) * (1.0 - fy) +
-                         (p01.0[3] as f32 * (1.0 - fx) + p11.0[3] as f32 * fx) * fy) as u8,
-                    ]);
-                    
-                    // Apply alpha blending
-                    let base_pixel = base_rgba.get_pixel_mut(base_x, base_y);
-                    let sticker_alpha = interpolated_pixel.0[3] as f32 / 255.0;
-                    let inv_alpha = 1.0 - sticker_alpha;
-                    
-                    // Blend RGB channels
-                    base_pixel.0[0] = ((base_pixel.0[0] as f32 * inv_alpha) + (interpolated_pixel.0[0] as f32 * sticker_alpha)) as u8;
-                    base_pixel.0[1] = ((base_pixel.0[1] as f32 * inv_alpha) + (interpolated_pixel.0[1] as f32 * sticker_alpha)) as u8;
-                    base_pixel.0[2] = ((base_pixel.0[2] as f32 * inv_alpha) + (interpolated_pixel.0[2] as f32 * sticker_alpha)) as u8;
-                    
-                    // Combine alpha channels
-                    let combined_alpha = (base_pixel.0[3] as f32 / 255.0) * inv_alpha + sticker_alpha;
-                    base_pixel.0[3] = (combined_alpha * 255.0) as u8;
-                }
-            }
-        }
+        // Rotate and blend the sticker onto the base image
+        composite_overlay(
+            &mut base_rgba,
+            &sticker_rgba,
+            (sticker.x, sticker.y),
+            sticker.rotation,
+            sticker.opacity,
+            cancel.flag(),
+        )?;
     }
     
     // Convert back to DynamicImage
@@ -1057,9 +4779,138 @@ async fn apply_stickers(
         format: image_data.format,
         data: base64_data,
         has_alpha,
+        display_data: None,
     })
 }
 
+/// Top-left corners at which a watermark is drawn
+///
+/// Tiled watermarks start at the top-left corner and repeat every
+/// `spacing` pixels; the last row and column may be cut off by the image edge.
+/// A single watermark is placed at a named position ("top-left", "top",
+/// "top-right", "left", "center", "right", "bottom-left", "bottom" or
+/// "bottom-right"), kept `spacing` pixels away from the edges it touches.
+fn watermark_positions(
+    base_width: u32,
+    base_height: u32,
+    mark_width: u32,
+    mark_height: u32,
+    tile: bool,
+    spacing: u32,
+    position: &str,
+) -> Result<Vec<(u32, u32)>, AppError> {
+    if spacing > base_width.max(base_height) {
+        return Err(AppError::InvalidParameters(
+            format!("Watermark spacing {} is larger than the {}x{} image", spacing, base_width, base_height)
+        ));
+    }
+
+    if tile {
+        // An empty watermark would otherwise give a step of zero
+        let step = |mark: u32| {
+            mark.checked_add(spacing).map(|step| step.max(1) as usize).ok_or_else(|| {
+                AppError::InvalidParameters("Watermark size plus spacing is too large".to_string())
+            })
+        };
+        let step_x = step(mark_width)?;
+        let step_y = step(mark_height)?;
+        return Ok((0..base_height)
+            .step_by(step_y)
+            .flat_map(|y| (0..base_width).step_by(step_x).map(move |x| (x, y)))
+            .collect());
+    }
+
+    // Alignment along each axis: -1 = start edge, 0 = centered, 1 = end edge
+    let (align_x, align_y) = match position.to_lowercase().as_str() {
+        "top-left" => (-1, -1),
+        "top" => (0, -1),
+        "top-right" => (1, -1),
+        "left" => (-1, 0),
+        "center" => (0, 0),
+        "right" => (1, 0),
+        "bottom-left" => (-1, 1),
+        "bottom" => (0, 1),
+        "bottom-right" => (1, 1),
+        other => {
+            return Err(AppError::InvalidParameters(
+                format!("Unknown watermark position: {}", other)
+            ));
+        }
+    };
+
+    let place = |align: i8, base: u32, mark: u32| match align {
+        -1 => spacing,
+        0 => base.saturating_sub(mark) / 2,
+        _ => base.saturating_sub(mark).saturating_sub(spacing),
+    };
+
+    Ok(vec![(
+        place(align_x, base_width, mark_width),
+        place(align_y, base_height, mark_height),
+    )])
+}
+
+/// Apply a watermark to an image
+/// 
+/// The watermark is either tiled across the whole image or placed once at a
+/// named position, and blended the same way as stickers.
+/// 
+/// @param image_data - The base image to watermark
+/// @param watermark_data - The watermark image, drawn at its own size
+/// @param opacity - Multiplier for the watermark's alpha from 0.0 to 1.0
+/// @param tile - Repeat the watermark across the whole image
+/// @param spacing - Gap between tiles, or the margin from the edges when not tiling; at most the longer image side
+/// @param position - Named position used when not tiling (e.g. "center", "bottom-right")
+/// @returns New ImageData with the watermark applied
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn apply_watermark(
+    image_data: ImageData,
+    watermark_data: ImageData,
+    opacity: f32,
+    tile: bool,
+    spacing: u32,
+    position: String,
+    limiter: State<'_, OperationLimiter>,
+    cancel_registry: State<'_, CancelRegistry>,
+) -> Result<ImageData, CommandError> {
+    // Registered before waiting so a cancel while queued is seen
+    let cancel = cancel_registry.start();
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    cancel.check()?;
+    
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(AppError::InvalidParameters(
+            "Watermark opacity must be between 0.0 and 1.0".to_string()
+        ).into());
+    }
+    
+    let base_img = decode_image_data(&image_data)?;
+    let watermark = decode_image_data(&watermark_data)?.to_rgba8();
+    
+    let mut base_rgba = base_img.to_rgba8();
+    let positions = watermark_positions(
+        base_rgba.width(),
+        base_rgba.height(),
+        watermark.width(),
+        watermark.height(),
+        tile,
+        spacing,
+        &position,
+    )?;
+    
+    for position in positions {
+        composite_overlay(&mut base_rgba, &watermark, position, 0.0, opacity, cancel.flag())?;
+    }
+    
+    Ok(encode_image_data(
+        &DynamicImage::ImageRgba8(base_rgba),
+        image_data.path,
+        image_data.format,
+    )?)
+}
+
 /// Apply text overlays to an image
 /// 
 /// Renders text onto the image at specified positions with customizable styling.
@@ -1072,7 +4923,11 @@ async fn apply_stickers(
 async fn apply_texts(
     image_data: ImageData,
     texts: Vec<TextData>,
-) -> Result<ImageData, String> {
+    limiter: State<'_, OperationLimiter>,
+) -> Result<ImageData, CommandError> {
+    // Heavy operation: wait for a free slot
+    let _permit = limiter.acquire().await;
+    
     if texts.is_empty() {
         return Err(AppError::InvalidParameters(
             "No texts provided".to_string()
@@ -1082,7 +4937,7 @@ async fn apply_texts(
     // Decode Base64 data for the base image
     let decoded_data = general_purpose::STANDARD
         .decode(&image_data.data)
-        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode base image Base64: {}", e)))?;
+        .map_err(AppError::Base64Error)?;
     
     // Load base image from decoded data
     let base_img = image::load_from_memory(&decoded_data)
@@ -1104,23 +4959,27 @@ async fn apply_texts(
             ).into());
         }
         
+        if !(0.0..=1.0).contains(&text_data.opacity) {
+            return Err(AppError::InvalidParameters(
+                format!("Text {} opacity must be between 0.0 and 1.0", index)
+            ).into());
+        }
+        
         // Parse color
         let color = parse_hex_color(&text_data.color)
             .map_err(|e| AppError::InvalidParameters(
                 format!("Text {} has invalid color '{}': {}", index, text_data.color, e)
             ))?;
+        let background = text_data.background_color
+            .as_deref()
+            .map(parse_hex_color_with_alpha)
+            .transpose()
+            .map_err(|e| AppError::InvalidParameters(
+                format!("Text {} has invalid background color: {}", index, e)
+            ))?;
         
         // Render text using font files
-        render_text_on_image(
-            &mut base_rgba,
-            &text_data.text,
-            text_data.x,
-            text_data.y,
-            text_data.font_size,
-            color,
-            &text_data.font_family,
-            text_data.rotation,
-        )?;
+        render_text_on_image(&mut base_rgba, text_data, color, background)?;
     }
     
     // Convert back to DynamicImage
@@ -1150,9 +5009,26 @@ async fn apply_texts(
         format: image_data.format,
         data: base64_data,
         has_alpha,
+        display_data: None,
     })
 }
 
+/// Parse a #RRGGBB or #RRGGBBAA color string to RGBA values
+fn parse_hex_color_with_alpha(hex: &str) -> Result<(u8, u8, u8, u8), String> {
+    let digits = hex.trim_start_matches('#');
+    
+    match digits.len() {
+        6 => parse_hex_color(digits).map(|(r, g, b)| (r, g, b, 255)),
+        8 => {
+            let (r, g, b) = parse_hex_color(&digits[0..6])?;
+            let a = u8::from_str_radix(&digits[6..8], 16)
+                .map_err(|_| "Invalid alpha component")?;
+            Ok((r, g, b, a))
+        }
+        _ => Err("Color must be in #RRGGBB or #RRGGBBAA format".to_string()),
+    }
+}
+
 /// Parse hex color string to RGB values
 fn parse_hex_color(hex: &str) -> Result<(u8, u8, u8), String> {
     let hex = hex.trim_start_matches('#');
@@ -1172,41 +5048,202 @@ fn parse_hex_color(hex: &str) -> Result<(u8, u8, u8), String> {
 }
 
 /// Render text on image using font files from fonts directory
+///
+/// Lines are split on `\n` and, if `max_width` is set, wrapped at word
+/// boundaries. The text is drawn into a transparent scratch buffer first, which
+/// is then rotated by `rotation` degrees around the text anchor (x, y) and alpha
+/// blended onto the image with the glyph coverage scaled by `opacity`. If a
+/// `background` (the parsed `background_color`) is given, a box padded by
+/// `padding` is filled behind the text.
 fn render_text_on_image(
     image: &mut image::RgbaImage,
-    text: &str,
-    x: u32,
-    y: u32,
-    font_size: u32,
+    text_data: &TextData,
     color: (u8, u8, u8),
-    font_family: &str,
-    _rotation: f32, // TODO: Implement rotation
+    background: Option<(u8, u8, u8, u8)>,
 ) -> Result<(), String> {
-    use ab_glyph::PxScale;
-    use imageproc::drawing::draw_text_mut;
-    
-    // Try to load the specific font by name
-    let font = load_font_by_name(font_family)?;
+    use ab_glyph::{Font, PxScale, ScaleFont};
+    use imageproc::drawing::{draw_filled_rect_mut, draw_text_mut, text_size};
+    use imageproc::rect::Rect;
+    
+    // Transparent margin around the text so rotated edges fade out cleanly
+    const PADDING: u32 = 2;
+    
+    // Prefer an explicit font file, otherwise look the font up by name
+    let font = match text_data.font_path.as_deref().map(load_font_from_file) {
+        Some(Ok(font)) => font,
+        Some(Err(e)) => {
+            println!("{}; falling back to font '{}'", e, text_data.font_family);
+            load_font_by_name(&text_data.font_family)?
+        }
+        None => load_font_by_name(&text_data.font_family)?,
+    };
     
     // Set font scale
-    let scale = PxScale::from(font_size as f32);
+    let scale = PxScale::from(text_data.font_size as f32);
     let text_color = image::Rgba([color.0, color.1, color.2, 255]);
-    
-    // Draw text using imageproc
-    draw_text_mut(
-        image,
-        text_color,
-        x as i32,
-        y as i32,
-        scale,
-        &font,
-        text,
+    let scaled_font = font.as_scaled(scale);
+    let line_height = (scaled_font.height() + scaled_font.line_gap()).ceil() as u32;
+    
+    let lines = layout_text_lines(&text_data.text, text_data.max_width, |line| {
+        text_size(scale, &font, line).0
+    });
+    let text_width = lines
+        .iter()
+        .map(|line| text_size(scale, &font, line).0)
+        .max()
+        .unwrap_or(0);
+    let text_height = line_height * lines.len() as u32;
+    
+    // The background box extends `padding` around the text; only pad when it's drawn
+    let box_padding = if background.is_some() { text_data.padding } else { 0 };
+    let origin = PADDING + box_padding;
+    
+    // Draw into a scratch buffer. Transparent pixels carry the color that will
+    // border them so bilinear sampling doesn't bleed black into the edges.
+    let clear_color = match background {
+        Some((r, g, b, _)) => image::Rgba([r, g, b, 0]),
+        None => image::Rgba([color.0, color.1, color.2, 0]),
+    };
+    let mut scratch = image::RgbaImage::from_pixel(
+        // Glyph outlines can overhang their advance slightly
+        text_width + text_data.font_size / 4 + origin * 2,
+        text_height + origin * 2,
+        clear_color,
     );
+    if let Some((r, g, b, a)) = background {
+        draw_filled_rect_mut(
+            &mut scratch,
+            Rect::at(PADDING as i32, PADDING as i32)
+                .of_size(text_width.max(1) + box_padding * 2, text_height.max(1) + box_padding * 2),
+            image::Rgba([r, g, b, a]),
+        );
+    }
+    for (index, line) in lines.iter().enumerate() {
+        draw_text_mut(
+            &mut scratch,
+            text_color,
+            origin as i32,
+            (origin + line_height * index as u32) as i32,
+            scale,
+            &font,
+            line,
+        );
+    }
+    
+    let rotation = text_data.rotation;
+    let anchor_x = text_data.x as f32;
+    let anchor_y = text_data.y as f32;
+    
+    // Calculate rotation parameters
+    let rotation_radians = rotation.to_radians();
+    let cos_angle = rotation_radians.cos();
+    let sin_angle = rotation_radians.sin();
+    
+    // Bounds of the rotated scratch buffer in image coordinates
+    let left = -(origin as f32);
+    let top = -(origin as f32);
+    let right = scratch.width() as f32 - origin as f32;
+    let bottom = scratch.height() as f32 - origin as f32;
+    let corners = [(left, top), (right, top), (left, bottom), (right, bottom)];
+    let rotated: Vec<(f32, f32)> = corners
+        .iter()
+        .map(|&(cx, cy)| (
+            anchor_x + cx * cos_angle - cy * sin_angle,
+            anchor_y + cx * sin_angle + cy * cos_angle,
+        ))
+        .collect();
+    let min_x = rotated.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let min_y = rotated.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let max_x = (rotated.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil().max(0.0) as u32)
+        .min(image.width());
+    let max_y = (rotated.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil().max(0.0) as u32)
+        .min(image.height());
+    
+    for base_y in min_y..max_y {
+        for base_x in min_x..max_x {
+            // Apply inverse rotation around the anchor to find the scratch pixel
+            let dx = base_x as f32 - anchor_x;
+            let dy = base_y as f32 - anchor_y;
+            let src_x = dx * cos_angle + dy * sin_angle + origin as f32;
+            let src_y = -dx * sin_angle + dy * cos_angle + origin as f32;
+            
+            if src_x < 0.0 || src_y < 0.0 ||
+               src_x > (scratch.width() - 1) as f32 || src_y > (scratch.height() - 1) as f32 {
+                continue;
+            }
+            
+            let sample = sample_bilinear(&scratch, src_x, src_y);
+            let layer_alpha = sample[3] / 255.0 * text_data.opacity;
+            if layer_alpha <= 0.0 {
+                continue;
+            }
+            
+            // Apply alpha blending
+            let base_pixel = image.get_pixel_mut(base_x, base_y);
+            let inv_alpha = 1.0 - layer_alpha;
+            for (base, layer) in base_pixel.0.iter_mut().zip(sample).take(3) {
+                *base = (*base as f32 * inv_alpha + layer * layer_alpha).round().clamp(0.0, 255.0) as u8;
+            }
+            
+            let combined_alpha = (base_pixel.0[3] as f32 / 255.0) * inv_alpha + layer_alpha;
+            base_pixel.0[3] = (combined_alpha * 255.0).round() as u8;
+        }
+    }
     
-    println!("Successfully rendered text '{}' using font '{}'", text, font_family);
+    println!(
+        "Successfully rendered {} line(s) of text '{}' using font '{}'",
+        lines.len(), text_data.text, text_data.font_family
+    );
     Ok(())
 }
 
+/// Split text into lines on `\n`, wrapping at word boundaries when max_width is set
+///
+/// `measure` returns the rendered width of a candidate line. Words wider than
+/// max_width on their own are kept on a line by themselves.
+fn layout_text_lines<F>(text: &str, max_width: Option<u32>, measure: F) -> Vec<String>
+where
+    F: Fn(&str) -> u32,
+{
+    let mut lines = Vec::new();
+    
+    for paragraph in text.split('\n') {
+        let paragraph = paragraph.trim_end_matches('\r');
+        
+        let Some(max_width) = max_width else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+        
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            if current.is_empty() {
+                current.push_str(word);
+                continue;
+            }
+            
+            let candidate = format!("{} {}", current, word);
+            if measure(&candidate) > max_width {
+                lines.push(std::mem::replace(&mut current, word.to_string()));
+            } else {
+                current = candidate;
+            }
+        }
+        lines.push(current);
+    }
+    
+    lines
+}
+
+/// Load a font from an explicit file path
+fn load_font_from_file(path: &str) -> Result<ab_glyph::FontArc, String> {
+    let font_data = fs::read(path)
+        .map_err(|e| format!("Failed to read font file '{}': {}", path, e))?;
+    
+    ab_glyph::FontArc::try_from_vec(font_data)
+        .map_err(|e| format!("Failed to parse font file '{}': {}", path, e))
+}
+
 /// Load a specific font by name from fonts directory
 fn load_font_by_name(font_name: &str) -> Result<ab_glyph::FontArc, String> {
     use ab_glyph::FontArc;
@@ -1401,7 +5438,7 @@ fn load_font_from_path(fonts_dir: &Path) -> Result<ab_glyph::FontArc, String> {
 
 /// Get available font files from the fonts directory
 #[tauri::command]
-async fn get_available_fonts() -> Result<Vec<String>, String> {
+async fn get_available_fonts() -> Result<Vec<String>, CommandError> {
     use std::fs;
     use std::path::Path;
     
@@ -1416,7 +5453,7 @@ async fn get_available_fonts() -> Result<Vec<String>, String> {
         .map_err(|e| format!("Failed to get executable path: {}", e))?;
     
     let exe_dir = exe_path.parent()
-        .ok_or("Failed to get executable directory")?;
+        .ok_or_else(|| AppError::OperationFailed("Failed to get executable directory".to_string()))?;
     
     // Try fonts directory relative to executable
     let bundled_fonts_dir = exe_dir.join("fonts");
@@ -1441,7 +5478,7 @@ async fn get_available_fonts() -> Result<Vec<String>, String> {
 }
 
 /// Get font file names from a specific directory path
-fn get_fonts_from_path(fonts_dir: &Path) -> Result<Vec<String>, String> {
+fn get_fonts_from_path(fonts_dir: &Path) -> Result<Vec<String>, CommandError> {
     use std::fs;
     
     // Supported font file extensions
@@ -1486,7 +5523,7 @@ pub struct FontData {
 
 /// Get font file data as Base64 for web font loading
 #[tauri::command]
-async fn get_font_data(font_name: String) -> Result<FontData, String> {
+async fn get_font_data(font_name: String) -> Result<FontData, CommandError> {
     use std::fs;
     use std::path::Path;
     use base64::{Engine as _, engine::general_purpose};
@@ -1529,7 +5566,7 @@ async fn get_font_data(font_name: String) -> Result<FontData, String> {
         }
     }
     
-    Err(format!("Font file '{}' not found", font_name))
+    Err(AppError::FileNotFound(format!("Font file '{}'", font_name)).into())
 }
 
 /// Get font file data from a specific directory path
@@ -1579,7 +5616,7 @@ fn get_font_data_from_path(fonts_dir: &Path, font_name: &str, font_extensions: &
 
 /// Get command line arguments (deprecated - use on_image_source_listener_ready for macOS)
 #[tauri::command]
-async fn get_command_line_args() -> Result<Vec<String>, String> {
+async fn get_command_line_args() -> Result<Vec<String>, CommandError> {
     let args: Vec<String> = std::env::args().collect();
     
     // Log all arguments for debugging
@@ -1601,29 +5638,110 @@ pub fn run() {
 
     tauri::Builder::default()
         .manage(app_state)
+        .manage(OperationLimiter::default())
+        .manage(CancelRegistry::new())
+        .manage(ThumbnailCache::default())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .invoke_handler(tauri::generate_handler![
             greet, 
             load_image,
+            probe_image,
+            quick_look,
+            clear_thumbnail_cache,
+            get_format_capabilities,
+            set_max_concurrent_operations,
+            cancel_operation,
+            get_pyramid_levels,
+            load_image_at_level,
+            load_image_capped,
+            load_image_downscaled,
+            load_image_from_clipboard,
+            copy_image_to_clipboard,
             get_directory_images,
             open_file_dialog,
             save_file_dialog,
             save_image,
+            export_npy,
             resize_image,
+            resize_image_scale,
+            generate_mipmaps,
+            generate_srcset,
+            create_gif,
+            create_icon,
+            get_resample_preset,
             convert_format,
             crop_image,
+            crop_image_normalized,
+            crop_image_aspect,
+            crop_image_rounded,
+            crop_image_circle,
+            pixelate_region,
+            crop_polygon,
+            split_panorama,
+            autocrop_opaque,
+            remove_letterbox,
+            create_comparison,
+            add_border,
+            pad_to_aspect,
+            flood_fill_mask,
+            replace_color,
+            crop_supersampled,
             set_background,
             rotate_image,
+            straighten_by_line,
+            auto_deskew,
+            grayscale_image,
+            premultiplied_to_straight,
+            straight_to_premultiplied,
+            compute_quality_metrics,
+            compare_images,
+            perceptual_hash,
+            hamming_distance,
+            diff_heatmap,
+            detect_banding,
+            apply_pipeline,
+            save_macro,
+            load_macro,
+            load_image_with_sidecar,
+            save_sidecar,
+            apply_macro_to_directory,
+            equalize_histogram_image,
+            auto_levels,
+            posterize_image,
+            threshold_image,
+            draw_composition_grid,
+            compute_histogram,
+            extract_palette,
+            render_histogram,
+            set_image_dpi,
+            get_image_dpi,
+            get_print_size,
+            get_pixel_aspect,
+            correct_pixel_aspect,
+            detect_clipping,
+            render_edit_timeline,
             apply_stickers,
+            apply_watermark,
             apply_texts,
+            suggest_text_color,
             get_all_favorites,
             add_favorite,
             remove_favorite,
+            update_favorite_note,
+            touch_favorite,
+            list_missing_favorites,
+            prune_missing_favorites,
             is_favorite,
             search_favorites_by_tags,
+            search_favorites_by_camera,
+            get_camera_info,
             get_all_tags,
+            rename_tag,
+            export_gallery_html,
+            generate_collection_cover,
+            create_contact_sheet,
             file_exists,
             get_available_fonts,
             get_font_data,
@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::error::{AppError, AppResult};
+
+/// Limits how many heavy operations (large encodes, compositing, batch work)
+/// run at the same time so they don't oversubscribe the CPU and freeze the UI
+///
+/// One limiter lives in Tauri managed state and is shared by all heavy commands.
+pub struct OperationLimiter {
+    /// Semaphore currently handed out to new operations
+    ///
+    /// Changing the limit swaps in a fresh semaphore; operations already running
+    /// keep the permit of the old one until they finish.
+    semaphore: Mutex<Arc<Semaphore>>,
+    max_concurrent: Mutex<usize>,
+}
+
+impl OperationLimiter {
+    /// Create a limiter allowing max_concurrent operations at once
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            semaphore: Mutex::new(Arc::new(Semaphore::new(max_concurrent))),
+            max_concurrent: Mutex::new(max_concurrent),
+        }
+    }
+
+    /// Get the current maximum number of concurrent operations
+    pub fn max_concurrent(&self) -> usize {
+        *self.max_concurrent.lock().unwrap()
+    }
+
+    /// Change the maximum number of concurrent operations
+    pub fn set_max_concurrent(&self, max_concurrent: usize) -> AppResult<()> {
+        if max_concurrent == 0 {
+            return Err(AppError::InvalidParameters(
+                "Maximum concurrent operations must be at least 1".to_string()
+            ));
+        }
+
+        *self.semaphore.lock().unwrap() = Arc::new(Semaphore::new(max_concurrent));
+        *self.max_concurrent.lock().unwrap() = max_concurrent;
+        Ok(())
+    }
+
+    /// Wait for a free slot; the slot is released when the permit is dropped
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        let semaphore = self.semaphore.lock().unwrap().clone();
+        semaphore
+            .acquire_owned()
+            .await
+            .expect("operation semaphore is never closed")
+    }
+}
+
+impl Default for OperationLimiter {
+    /// Defaults to one operation per available CPU core
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+        Self::new(cores)
+    }
+}
+
+/// Tracks the cancellation tokens of the operations currently running
+///
+/// Each cancellable operation registers its own token for as long as it runs,
+/// including while it waits for a limiter slot, so a cancel request only
+/// reaches operations that are queued or in progress. A request
+/// made while nothing runs is dropped instead of aborting the next operation.
+/// The registry used by cancel_operation lives in Tauri managed state.
+#[derive(Default)]
+pub struct CancelRegistry {
+    running: Mutex<Vec<Arc<AtomicBool>>>,
+}
+
+impl CancelRegistry {
+    /// Create a registry with no running operations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new operation, returning its token
+    ///
+    /// The token is unregistered when dropped, so keep it alive for the
+    /// whole operation.
+    pub fn start(&self) -> CancelToken<'_> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap().push(flag.clone());
+        CancelToken { registry: self, flag }
+    }
+
+    /// Cancel every running operation, returning how many there were
+    pub fn cancel_all(&self) -> usize {
+        let running = self.running.lock().unwrap();
+        for flag in running.iter() {
+            flag.store(true, Ordering::SeqCst);
+        }
+        running.len()
+    }
+}
+
+/// Cancellation flag of one running operation
+pub struct CancelToken<'a> {
+    registry: &'a CancelRegistry,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken<'_> {
+    /// The flag raised when the operation is cancelled
+    pub fn flag(&self) -> &AtomicBool {
+        &self.flag
+    }
+
+    /// Fail with "cancelled" if the operation was cancelled
+    pub fn check(&self) -> AppResult<()> {
+        check_cancelled(&self.flag)
+    }
+}
+
+impl Drop for CancelToken<'_> {
+    fn drop(&mut self) {
+        self.registry
+            .running
+            .lock()
+            .unwrap()
+            .retain(|flag| !Arc::ptr_eq(flag, &self.flag));
+    }
+}
+
+/// Fail with "cancelled" if the flag is raised
+///
+/// Only reads the flag, so every worker of a parallel operation sees the
+/// request; lowering it is up to the operation that owns the flag.
+pub fn check_cancelled(flag: &AtomicBool) -> AppResult<()> {
+    if flag.load(Ordering::SeqCst) {
+        return Err(AppError::OperationFailed("cancelled".to_string()));
+    }
+    Ok(())
+}
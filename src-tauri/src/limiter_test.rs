@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::limiter::{CancelRegistry, OperationLimiter};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Run two simulated heavy operations at once and return the highest
+    /// number that were ever running simultaneously
+    async fn peak_concurrency(limiter: Arc<OperationLimiter>) -> usize {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let active = active.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire().await;
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_single_permit_serializes_operations() {
+        let limiter = Arc::new(OperationLimiter::new(1));
+        assert_eq!(peak_concurrency(limiter).await, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_two_permits_allow_overlap() {
+        let limiter = Arc::new(OperationLimiter::new(2));
+        assert_eq!(peak_concurrency(limiter).await, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_set_max_concurrent() {
+        let limiter = Arc::new(OperationLimiter::new(2));
+
+        assert!(limiter.set_max_concurrent(0).is_err());
+        assert_eq!(limiter.max_concurrent(), 2);
+
+        limiter.set_max_concurrent(1).unwrap();
+        assert_eq!(limiter.max_concurrent(), 1);
+        assert_eq!(peak_concurrency(limiter).await, 1);
+    }
+
+    #[test]
+    fn test_cancel_only_reaches_running_operations() {
+        let registry = CancelRegistry::new();
+
+        // Nothing is running, so the request is dropped
+        assert_eq!(registry.cancel_all(), 0);
+        let later = registry.start();
+        assert!(later.check().is_ok());
+
+        let other = registry.start();
+        assert_eq!(registry.cancel_all(), 2);
+        assert_eq!(later.check().unwrap_err().to_string(), "Operation failed: cancelled");
+        assert!(other.check().is_err());
+
+        // Finished operations are unregistered
+        drop(later);
+        drop(other);
+        assert_eq!(registry.cancel_all(), 0);
+        assert!(registry.start().check().is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::camera;
+use crate::error::{AppError, AppResult};
+
+/// Value used for tokens whose data is missing
+const UNKNOWN: &str = "unknown";
+
+/// What a batch or export does when an output file already exists
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionStrategy {
+    /// Leave the existing file alone and don't write the output
+    Skip,
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Write next to it with a numeric suffix (`photo_1.jpg`, `photo_2.jpg`, ...)
+    Rename,
+}
+
+/// Decide where an output should be written given the collision strategy
+///
+/// Returns None if the file exists and should be skipped.
+pub fn resolve_collision(path: PathBuf, strategy: CollisionStrategy) -> Option<PathBuf> {
+    resolve_collision_with(path, strategy, |candidate| candidate.exists())
+}
+
+/// Like resolve_collision, with `exists` deciding which paths are taken
+///
+/// Lets a batch also treat the outputs it is about to write as existing.
+pub fn resolve_collision_with<E>(path: PathBuf, strategy: CollisionStrategy, exists: E) -> Option<PathBuf>
+where
+    E: Fn(&Path) -> bool,
+{
+    if !exists(&path) {
+        return Some(path);
+    }
+
+    match strategy {
+        CollisionStrategy::Skip => None,
+        CollisionStrategy::Overwrite => Some(path),
+        CollisionStrategy::Rename => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy()));
+
+            (1..)
+                .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension.as_deref().unwrap_or(""))))
+                .find(|candidate| !exists(candidate))
+        }
+    }
+}
+
+/// Build an output path from a naming template
+///
+/// The template is a relative path such as `{year}/{month}/{camera}_{index}`
+/// and the source file's extension is appended to the result. Supported tokens:
+///
+/// - `{name}`: source file name without extension
+/// - `{index}`: 1-based position of the file in the batch
+/// - `{year}`, `{month}`, `{day}`: EXIF capture date, falling back to the
+///   file's modification date (UTC)
+/// - `{camera}`: EXIF camera model (or make)
+/// - `{iso}`: EXIF ISO sensitivity
+///
+/// Missing EXIF values become "unknown". Characters that aren't safe in file
+/// names are replaced with underscores in every token value.
+pub fn render_output_path(template: &str, source: &Path, index: usize) -> AppResult<PathBuf> {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    // Capture data is only read if the template needs it
+    let mut date = None;
+    let mut camera_info = None;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            AppError::InvalidParameters(format!("Unclosed token in template: {}", template))
+        })? + start;
+
+        let token = &rest[start + 1..end];
+        let value = match token {
+            "name" => source
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| UNKNOWN.to_string()),
+            "index" => index.to_string(),
+            "year" | "month" | "day" => {
+                let (year, month, day) = *date.get_or_insert_with(|| capture_date(source));
+                match (token, year) {
+                    (_, 0) => UNKNOWN.to_string(),
+                    ("year", _) => format!("{:04}", year),
+                    ("month", _) => format!("{:02}", month),
+                    _ => format!("{:02}", day),
+                }
+            }
+            "camera" | "iso" => {
+                let info = camera_info
+                    .get_or_insert_with(|| camera::read_camera_info(source).unwrap_or_default());
+                let value = if token == "camera" {
+                    info.camera_model.clone().or_else(|| info.camera_make.clone())
+                } else {
+                    info.iso.map(|iso| iso.to_string())
+                };
+                value.unwrap_or_else(|| UNKNOWN.to_string())
+            }
+            other => {
+                return Err(AppError::InvalidParameters(format!("Unknown template token: {{{}}}", other)));
+            }
+        };
+
+        rendered.push_str(&sanitize(&value));
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    let mut path = PathBuf::from(rendered.trim());
+    let is_safe = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !is_safe || path.file_name().is_none() {
+        return Err(AppError::InvalidParameters(
+            format!("Template must produce a relative file path: {}", template)
+        ));
+    }
+
+    if let Some(extension) = source.extension() {
+        let file_name = format!(
+            "{}.{}",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            extension.to_string_lossy()
+        );
+        path.set_file_name(file_name);
+    }
+
+    Ok(path)
+}
+
+/// Capture date from EXIF or the modification time, (0, 0, 0) if neither is known
+fn capture_date(source: &Path) -> (u16, u8, u8) {
+    if let Some(date) = camera::read_capture_date(source) {
+        return date;
+    }
+
+    source
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| civil_from_days((elapsed.as_secs() / 86_400) as i64))
+        .unwrap_or((0, 0, 0))
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date
+fn civil_from_days(days: i64) -> (u16, u8, u8) {
+    // Howard Hinnant's days_from_civil inverse, counting eras of 400 years from March
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year as u16, month as u8, day as u8)
+}
+
+/// Replace characters that aren't safe in a file name
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
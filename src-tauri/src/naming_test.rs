@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use crate::naming::{render_output_path, CollisionStrategy};
+    use crate::pipeline::{apply_macro_to_directory, PipelineStep};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::AtomicBool;
+
+    /// Helper function to write a JPEG with a capture date and camera model in EXIF
+    fn write_jpeg_with_exif(path: &Path, date_taken: &str, model: &str) {
+        use exif::{Field, In, Tag, Value};
+
+        let ascii = |text: &str| Value::Ascii(vec![text.as_bytes().to_vec()]);
+        let fields = [
+            Field { tag: Tag::Model, ifd_num: In::PRIMARY, value: ascii(model) },
+            Field { tag: Tag::DateTimeOriginal, ifd_num: In::PRIMARY, value: ascii(date_taken) },
+        ];
+
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut jpeg = Vec::new();
+        image::RgbImage::from_pixel(16, 16, image::Rgb([120, 80, 40]))
+            .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .unwrap();
+
+        // Insert an APP1 Exif segment right after the SOI marker
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        segment.extend_from_slice(b"Exif\0\0");
+        segment.extend_from_slice(&tiff);
+        jpeg.splice(2..2, segment);
+
+        fs::write(path, jpeg).unwrap();
+    }
+
+    #[test]
+    fn test_macro_output_template_sorts_by_exif_date() {
+        let temp_dir = std::env::temp_dir().join("test_naming_exif_dates");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        write_jpeg_with_exif(&source_dir.join("a.jpg"), "2023:07:14 09:30:00", "X-T4");
+        write_jpeg_with_exif(&source_dir.join("b.jpg"), "2024:01:02 18:05:00", "Canon EOS R5");
+
+        let steps = vec![PipelineStep::Grayscale];
+        let results = apply_macro_to_directory(
+            &steps,
+            &source_dir,
+            &output_dir,
+            Some("{year}/{month}/{camera}_{index}"),
+            CollisionStrategy::Overwrite,
+            &AtomicBool::new(false),
+            |_, _, _| {},
+        )
+        .unwrap();
+
+        let outputs: Vec<PathBuf> = results
+            .iter()
+            .map(|result| PathBuf::from(result.output_path.clone().unwrap()))
+            .collect();
+        assert_eq!(outputs[0], output_dir.join("2023").join("07").join("X-T4_1.jpg"));
+        assert_eq!(outputs[1], output_dir.join("2024").join("01").join("Canon_EOS_R5_2.jpg"));
+        assert!(outputs.iter().all(|path| path.exists()));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_template_falls_back_without_exif() {
+        let temp_dir = std::env::temp_dir().join("test_naming_fallback");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let source = temp_dir.join("plain.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([0, 0, 0])).save(&source).unwrap();
+
+        let path = render_output_path("{camera}/{iso}/{name}", &source, 3).unwrap();
+        assert_eq!(path, Path::new("unknown").join("unknown").join("plain.png"));
+
+        // Dates fall back to the file's modification time
+        let dated = render_output_path("{year}-{month}-{day}", &source, 1).unwrap();
+        let name = dated.to_string_lossy().to_string();
+        assert_eq!(name.len(), "2024-01-02.png".len());
+        assert!(!name.contains("unknown"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_template_rejects_unknown_tokens_and_escapes() {
+        let source = Path::new("photo.jpg");
+
+        assert!(render_output_path("{lens}_{index}", source, 1).is_err());
+        assert!(render_output_path("{index", source, 1).is_err());
+        assert!(render_output_path("../{name}", source, 1).is_err());
+        assert!(render_output_path("/tmp/{name}", source, 1).is_err());
+        assert_eq!(render_output_path("out/{name}_{index}", source, 7).unwrap(), Path::new("out/photo_7.jpg"));
+    }
+}
@@ -0,0 +1,56 @@
+use image::DynamicImage;
+
+/// NumPy `.npy` magic string
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Encode the decoded pixels of an image as a NumPy `.npy` (format 1.0) file
+///
+/// The array has shape `[H, W, C]` in row-major order, where C is the image's
+/// channel count (1 gray, 2 gray+alpha, 3 RGB, 4 RGBA). 8-bit images use dtype
+/// `uint8`; 16-bit and floating point images use little-endian `uint16`.
+pub fn encode_npy(img: &DynamicImage) -> Vec<u8> {
+    use image::DynamicImage::*;
+
+    let channels = img.color().channel_count();
+
+    let (descr, data): (&str, Vec<u8>) = match img {
+        ImageLuma8(buffer) => ("|u1", buffer.as_raw().clone()),
+        ImageLumaA8(buffer) => ("|u1", buffer.as_raw().clone()),
+        ImageRgb8(buffer) => ("|u1", buffer.as_raw().clone()),
+        ImageRgba8(buffer) => ("|u1", buffer.as_raw().clone()),
+        ImageLuma16(buffer) => ("<u2", u16_to_le_bytes(buffer.as_raw())),
+        ImageLumaA16(buffer) => ("<u2", u16_to_le_bytes(buffer.as_raw())),
+        ImageRgb16(buffer) => ("<u2", u16_to_le_bytes(buffer.as_raw())),
+        ImageRgba16(buffer) => ("<u2", u16_to_le_bytes(buffer.as_raw())),
+        // Floating point sources are exported at 16-bit precision
+        _ if img.color().has_alpha() => ("<u2", u16_to_le_bytes(img.to_rgba16().as_raw())),
+        _ => ("<u2", u16_to_le_bytes(img.to_rgb16().as_raw())),
+    };
+
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}, {}), }}",
+        descr,
+        img.height(),
+        img.width(),
+        channels,
+    );
+
+    // Pad with spaces so the data starts on a 64-byte boundary, ending in a newline
+    let preamble_len = NPY_MAGIC.len() + 2 + 2;
+    let total_len = (preamble_len + header.len() + 1).div_ceil(64) * 64;
+    header.push_str(&" ".repeat(total_len - preamble_len - header.len() - 1));
+    header.push('\n');
+
+    let mut output = Vec::with_capacity(total_len + data.len());
+    output.extend_from_slice(NPY_MAGIC);
+    output.extend_from_slice(&[1, 0]);
+    output.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    output.extend_from_slice(header.as_bytes());
+    output.extend_from_slice(&data);
+    output
+}
+
+/// Flatten 16-bit samples into little-endian bytes
+fn u16_to_le_bytes(samples: &[u16]) -> Vec<u8> {
+    samples.iter().flat_map(|sample| sample.to_le_bytes()).collect()
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use base64::{Engine as _, engine::general_purpose};
+    use image::DynamicImage;
+    use std::fs;
+
+    /// Helper function to wrap an image as PNG ImageData
+    fn to_image_data(img: &DynamicImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: img.color().has_alpha(),
+            display_data: None,
+        }
+    }
+
+    /// Split an .npy file into its header text and data bytes
+    fn parse_npy(bytes: &[u8]) -> (String, &[u8]) {
+        assert_eq!(&bytes[..6], b"\x93NUMPY");
+        assert_eq!(&bytes[6..8], &[1, 0]);
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = String::from_utf8(bytes[10..10 + header_len].to_vec()).unwrap();
+        assert!(header.ends_with('\n'));
+        (header, &bytes[10 + header_len..])
+    }
+
+    #[tokio::test]
+    async fn test_export_npy_rgb8() {
+        let img = image::RgbImage::from_fn(4, 3, |x, y| image::Rgb([x as u8, y as u8, 200]));
+        let path = std::env::temp_dir().join("test_export_rgb8.npy");
+
+        let result = crate::export_npy(
+            to_image_data(&DynamicImage::ImageRgb8(img.clone())),
+            path.to_string_lossy().to_string(),
+        ).await;
+
+        assert!(result.is_ok());
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let (header, data) = parse_npy(&bytes);
+        assert!(header.contains("'descr': '|u1'"));
+        assert!(header.contains("'fortran_order': False"));
+        assert!(header.contains("'shape': (3, 4, 3)"));
+        assert_eq!(data, img.as_raw().as_slice());
+        // Pixel (x=2, y=1) sits at row 1, column 2
+        let offset = (4 + 2) * 3;
+        assert_eq!(&data[offset..offset + 3], &[2, 1, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_export_npy_rgba16() {
+        let img = image::ImageBuffer::from_pixel(2, 2, image::Rgba([1000u16, 2000, 3000, 65535]));
+        let path = std::env::temp_dir().join("test_export_rgba16.npy");
+
+        let result = crate::export_npy(
+            to_image_data(&DynamicImage::ImageRgba16(img)),
+            path.to_string_lossy().to_string(),
+        ).await;
+
+        assert!(result.is_ok());
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let (header, data) = parse_npy(&bytes);
+        assert!(header.contains("'descr': '<u2'"));
+        assert!(header.contains("'shape': (2, 2, 4)"));
+        assert_eq!(data.len(), 2 * 2 * 4 * 2);
+        assert_eq!(u16::from_le_bytes([data[0], data[1]]), 1000);
+        assert_eq!(u16::from_le_bytes([data[6], data[7]]), 65535);
+    }
+}
@@ -0,0 +1,95 @@
+use image::{imageops::FilterType, DynamicImage};
+use crate::types::RGBColor;
+
+/// Images are downsampled to fit this size before their colors are analyzed
+const SAMPLE_SIZE: u32 = 128;
+
+/// Find the dominant colors of an image with median cut
+///
+/// The pixels are split into up to `count` boxes, always cutting the box with
+/// the widest channel range at its median. Each box becomes the average of its
+/// pixels, ordered by how many pixels it holds (largest share first). Images
+/// with fewer distinct colors return fewer entries. Fully transparent pixels
+/// are ignored.
+pub fn extract_palette(img: &DynamicImage, count: usize) -> Vec<RGBColor> {
+    // Nearest neighbor keeps the original colors instead of inventing blends
+    let sample = if img.width() > SAMPLE_SIZE || img.height() > SAMPLE_SIZE {
+        img.resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Nearest)
+    } else {
+        img.clone()
+    };
+
+    let pixels: Vec<[u8; 3]> = sample
+        .to_rgba8()
+        .pixels()
+        .filter(|p| p.0[3] > 0)
+        .map(|p| [p.0[0], p.0[1], p.0[2]])
+        .collect();
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![pixels];
+    while boxes.len() < count {
+        // Widest box that still has more than one color
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .filter(|&(_, _, range)| range > 0)
+            .max_by_key(|&(_, _, range)| range)
+            .map(|(index, channel, _)| (index, channel))
+        else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        // Cut at the median, moved off a run of equal values so both halves
+        // are non-empty and no value is split across them
+        let median = pixels[pixels.len() / 2][channel];
+        let mut cut = pixels.partition_point(|pixel| pixel[channel] < median);
+        if cut == 0 {
+            cut = pixels.partition_point(|pixel| pixel[channel] <= median);
+        }
+        let upper = pixels.split_off(cut);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes.sort_by_key(|pixels| std::cmp::Reverse(pixels.len()));
+    boxes.iter().map(|pixels| average(pixels)).collect()
+}
+
+/// Channel with the largest spread of values and that spread
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = pixels.iter().map(|p| p[channel]).min().unwrap_or(0);
+            let max = pixels.iter().map(|p| p[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+/// Average color of a box of pixels
+fn average(pixels: &[[u8; 3]]) -> RGBColor {
+    let mut sums = [0u64; 3];
+    for pixel in pixels {
+        for (sum, &value) in sums.iter_mut().zip(pixel) {
+            *sum += value as u64;
+        }
+    }
+    let len = pixels.len().max(1) as u64;
+    let mean = |sum: u64| ((sum + len / 2) / len) as u8;
+
+    RGBColor {
+        r: mean(sums[0]),
+        g: mean(sums[1]),
+        b: mean(sums[2]),
+    }
+}
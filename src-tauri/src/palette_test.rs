@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat, RGBColor};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+
+    /// Helper function to wrap an image as PNG ImageData
+    fn encode_png(img: &image::RgbImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Whether a color is within a few levels of the expected one
+    fn is_close(color: &RGBColor, expected: [u8; 3]) -> bool {
+        [color.r, color.g, color.b]
+            .iter()
+            .zip(expected)
+            .all(|(&a, b)| a.abs_diff(b) <= 8)
+    }
+
+    #[tokio::test]
+    async fn test_half_red_half_blue_palette() {
+        // Larger than the sample size so the image is downsampled first;
+        // red covers a little more than half so the order is well defined
+        let img = image::RgbImage::from_fn(400, 300, |x, _y| {
+            if x < 220 { image::Rgb([255, 0, 0]) } else { image::Rgb([0, 0, 255]) }
+        });
+
+        let palette = crate::extract_palette(encode_png(&img), 2, limiter()).await.unwrap();
+
+        assert_eq!(palette.len(), 2);
+        assert!(is_close(&palette[0], [255, 0, 0]), "{:?}", palette[0]);
+        assert!(is_close(&palette[1], [0, 0, 255]), "{:?}", palette[1]);
+    }
+
+    #[tokio::test]
+    async fn test_palette_never_exceeds_distinct_colors() {
+        let img = image::RgbImage::from_pixel(20, 20, image::Rgb([10, 200, 30]));
+
+        let palette = crate::extract_palette(encode_png(&img), 5, limiter()).await.unwrap();
+
+        assert_eq!(palette.len(), 1);
+        assert!(is_close(&palette[0], [10, 200, 30]));
+        assert!(crate::extract_palette(encode_png(&img), 0, limiter()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_palette_splits_gradient_into_requested_count() {
+        let img = image::RgbImage::from_fn(256, 8, |x, _y| image::Rgb([x as u8, 128, 255 - x as u8]));
+
+        let palette = crate::extract_palette(encode_png(&img), 4, limiter()).await.unwrap();
+
+        assert_eq!(palette.len(), 4);
+        let mut reds: Vec<u8> = palette.iter().map(|c| c.r).collect();
+        reds.sort();
+        reds.dedup();
+        assert_eq!(reds.len(), 4);
+    }
+}
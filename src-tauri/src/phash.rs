@@ -0,0 +1,43 @@
+use image::{imageops::FilterType, DynamicImage};
+use crate::error::{AppError, AppResult};
+
+/// Compute a 64-bit difference hash (dHash) of an image
+///
+/// The image is reduced to a 9x8 grayscale thumbnail and each bit records
+/// whether a pixel is darker than its right-hand neighbor. Resizing,
+/// recompression and small color shifts change few bits, so near-duplicates
+/// have a small Hamming distance.
+pub fn difference_hash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let darker = small.get_pixel(x, y).0[0] < small.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | darker as u64;
+        }
+    }
+    hash
+}
+
+/// Format a hash as 16 lowercase hex digits
+pub fn to_hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+/// Parse a hash written by `to_hex`
+pub fn from_hex(hex: &str) -> AppResult<u64> {
+    if hex.len() != 16 {
+        return Err(AppError::InvalidParameters(format!(
+            "Hash must be 16 hex digits: {}", hex
+        )));
+    }
+
+    u64::from_str_radix(hex, 16)
+        .map_err(|_| AppError::InvalidParameters(format!("Invalid hash: {}", hex)))
+}
+
+/// Number of bits that differ between two hashes (0-64)
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
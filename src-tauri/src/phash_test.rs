@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgb};
+
+    /// Helper function to create a photo-like test image
+    fn create_test_image() -> image::RgbImage {
+        ImageBuffer::from_fn(200, 150, |x, y| {
+            let wave = ((x as f32 / 13.0).sin() * 60.0 + (y as f32 / 9.0).cos() * 50.0) as i32;
+            Rgb([
+                (120 + wave).clamp(0, 255) as u8,
+                (x * 255 / 200) as u8,
+                (y * 255 / 150) as u8,
+            ])
+        })
+    }
+
+    /// Helper function to encode an image as ImageData in the given format
+    fn encode(img: &image::RgbImage, format: image::ImageFormat) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), format).unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recompressed_copy_has_close_hash() {
+        let img = create_test_image();
+
+        let original = crate::perceptual_hash(encode(&img, image::ImageFormat::Png)).await.unwrap();
+        let mut jpeg = encode(&img, image::ImageFormat::Jpeg);
+        jpeg.format = ImageFormat::JPEG;
+        let recompressed = crate::perceptual_hash(jpeg).await.unwrap();
+
+        assert_eq!(original.len(), 16);
+        let distance = crate::hamming_distance(original, recompressed).await.unwrap();
+        assert!(distance <= 4, "distance was {}", distance);
+    }
+
+    #[tokio::test]
+    async fn test_different_images_are_far_apart() {
+        let img = create_test_image();
+        let flipped = image::imageops::flip_horizontal(&img);
+
+        let a = crate::perceptual_hash(encode(&img, image::ImageFormat::Png)).await.unwrap();
+        let b = crate::perceptual_hash(encode(&flipped, image::ImageFormat::Png)).await.unwrap();
+
+        let distance = crate::hamming_distance(a, b).await.unwrap();
+        assert!(distance > 20, "distance was {}", distance);
+    }
+
+    #[tokio::test]
+    async fn test_hamming_distance_rejects_malformed_hash() {
+        let valid = "00000000000000ff".to_string();
+
+        assert_eq!(crate::hamming_distance(valid.clone(), "0000000000000000".to_string()).await.unwrap(), 8);
+        assert!(crate::hamming_distance(valid.clone(), "xyz".to_string()).await.is_err());
+        assert!(crate::hamming_distance(valid, "000000000000000g".to_string()).await.is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use image::DynamicImage;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::error::{AppError, AppResult};
+use crate::limiter;
+use crate::naming::{self, CollisionStrategy};
+use crate::types::ImageData;
+
+/// Raster formats a macro can read and write back out
+const MACRO_EXTENSIONS: [&str; 9] = [
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff", "tif", "ico",
+];
+
+/// A single editing operation of a pipeline
+///
+/// Serialized as `{"op": "resize", "width": 800, ...}` so recorded macros stay
+/// readable and editable by hand.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PipelineStep {
+    /// Resize to the given dimensions, optionally fitting inside them
+    Resize {
+        width: u32,
+        height: u32,
+        keep_aspect_ratio: bool,
+    },
+    /// Crop a rectangular region
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Rotate by 90 degrees
+    Rotate { clockwise: bool },
+    /// Convert to grayscale, keeping the alpha channel
+    Grayscale,
+}
+
+/// Outcome of replaying a macro on one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroFileResult {
+    /// Source image path
+    pub path: String,
+    /// Path of the written result, if the file was processed
+    pub output_path: Option<String>,
+    /// Error message, if the file failed
+    pub error: Option<String>,
+    /// Whether the file was skipped because its output already existed
+    pub skipped: bool,
+}
+
+/// An image loaded with the edits recorded in its sidecar applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarImage {
+    /// The edited image
+    pub image: ImageData,
+    /// The recorded steps, empty if the image has no sidecar
+    pub steps: Vec<PipelineStep>,
+}
+
+/// Apply a single step to an image
+pub fn apply_step(img: DynamicImage, step: &PipelineStep) -> AppResult<DynamicImage> {
+    use image::imageops::FilterType;
+
+    match *step {
+        PipelineStep::Resize { width, height, keep_aspect_ratio } => {
+            if width == 0 || height == 0 {
+                return Err(AppError::InvalidParameters(
+                    "Width and height must be positive integers".to_string()
+                ));
+            }
+
+            Ok(if keep_aspect_ratio {
+                img.resize(width, height, FilterType::Lanczos3)
+            } else {
+                img.resize_exact(width, height, FilterType::Lanczos3)
+            })
+        }
+        PipelineStep::Crop { x, y, width, height } => {
+            if width == 0 || height == 0
+                || x.saturating_add(width) > img.width()
+                || y.saturating_add(height) > img.height()
+            {
+                return Err(AppError::InvalidParameters(
+                    format!(
+                        "Crop region ({}x{} at {},{}) exceeds image bounds ({}x{})",
+                        width, height, x, y, img.width(), img.height()
+                    )
+                ));
+            }
+
+            Ok(img.crop_imm(x, y, width, height))
+        }
+        PipelineStep::Rotate { clockwise } => {
+            Ok(if clockwise { img.rotate90() } else { img.rotate270() })
+        }
+        PipelineStep::Grayscale => Ok(img.grayscale()),
+    }
+}
+
+/// Apply every step of a pipeline in order
+pub fn apply_pipeline(img: DynamicImage, steps: &[PipelineStep]) -> AppResult<DynamicImage> {
+    steps.iter().try_fold(img, apply_step)
+}
+
+/// Write a pipeline to disk as a JSON macro file
+pub fn save_macro(path: &Path, steps: &[PipelineStep]) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(steps)
+        .map_err(|e| AppError::InvalidParameters(format!("Failed to serialize macro: {}", e)))?;
+
+    fs::write(path, content).map_err(AppError::IoError)
+}
+
+/// Read a pipeline from a JSON macro file
+pub fn load_macro(path: &Path) -> AppResult<Vec<PipelineStep>> {
+    if !path.exists() {
+        return Err(AppError::FileNotFound(path.display().to_string()));
+    }
+
+    let content = fs::read_to_string(path).map_err(AppError::IoError)?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidParameters(format!("Failed to parse macro: {}", e)))
+}
+
+/// Sidecar file recording the edits of an image: `<name>.edits.json` next to it
+pub fn sidecar_path(image_path: &Path) -> PathBuf {
+    let stem = image_path.file_stem().unwrap_or_default().to_string_lossy();
+    image_path.with_file_name(format!("{}.edits.json", stem))
+}
+
+/// Read the edits recorded for an image, if it has a sidecar
+pub fn load_sidecar(image_path: &Path) -> AppResult<Vec<PipelineStep>> {
+    let path = sidecar_path(image_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    load_macro(&path)
+}
+
+/// Record the edits of an image in its sidecar
+///
+/// Saving an empty pipeline removes the sidecar. Returns the sidecar path.
+pub fn save_sidecar(image_path: &Path, steps: &[PipelineStep]) -> AppResult<PathBuf> {
+    let path = sidecar_path(image_path);
+    if steps.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(AppError::IoError)?;
+        }
+    } else {
+        save_macro(&path, steps)?;
+    }
+    Ok(path)
+}
+
+/// Replay a pipeline over every image in a directory
+///
+/// Results are written to `output_dir` under their original file names, so the
+/// source images are never modified. With a `name_template` (see
+/// `naming::render_output_path`) each file is named from the template instead and
+/// subdirectories are created as needed. `collision` decides what happens when
+/// an output file already exists; skipped files are marked in their result. A
+/// failing file is reported in its result and does not stop the batch.
+///
+/// Files are rendered in parallel, but output names are chosen and files
+/// written in sorted order, so files of the same batch that map to one name
+/// collide as if processed one after another. Results are returned in sorted
+/// input order. `on_progress` is called with (done, total, path) after each
+/// file. `cancelled` is checked before each file; once raised the batch stops
+/// with a "cancelled" error, leaving the files already written in place, and
+/// the flag is lowered again.
+pub fn apply_macro_to_directory<F>(
+    steps: &[PipelineStep],
+    dir: &Path,
+    output_dir: &Path,
+    name_template: Option<&str>,
+    collision: CollisionStrategy,
+    cancelled: &AtomicBool,
+    on_progress: F,
+) -> AppResult<Vec<MacroFileResult>>
+where
+    F: FnMut(usize, usize, &str),
+{
+    if !dir.is_dir() {
+        return Err(AppError::FileNotFound(dir.display().to_string()));
+    }
+
+    if dir.canonicalize().ok() == output_dir.canonicalize().ok() {
+        return Err(AppError::InvalidParameters(
+            "Output directory must differ from the source directory".to_string()
+        ));
+    }
+
+    // Reject a malformed template before touching any file
+    if let Some(template) = name_template {
+        naming::render_output_path(template, Path::new("image.png"), 1)?;
+    }
+
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(AppError::IoError)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| MACRO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect();
+    files.sort();
+
+    fs::create_dir_all(output_dir).map_err(AppError::IoError)?;
+
+    // Paths claimed by earlier files of this batch count as existing
+    let mut claimed = HashSet::new();
+    let targets: Vec<AppResult<Option<PathBuf>>> = files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| {
+            let output_path = output_path_for(file, output_dir, name_template, index + 1)?;
+            let target = naming::resolve_collision_with(output_path, collision, |candidate| {
+                claimed.contains(candidate) || candidate.exists()
+            });
+            if let Some(target) = &target {
+                claimed.insert(target.clone());
+            }
+            Ok(target)
+        })
+        .collect();
+
+    let written = render_and_write(files, targets, steps, cancelled, on_progress);
+    if written.is_err() {
+        // The request is consumed by the batch it stopped
+        cancelled.store(false, Ordering::SeqCst);
+    }
+    written
+}
+
+/// Run the batch in chunks: render each chunk in parallel, then write it in order
+///
+/// Decoding, editing and encoding (the expensive part) run on rayon's pool,
+/// but files are written and reported one after another in sorted order. The
+/// flag is checked before each write, so a cancel stops the batch at the next
+/// file even while other workers are busy, and nothing after it is written.
+/// Chunks hold one file per worker, which bounds the encoded output kept in memory.
+fn render_and_write<F>(
+    files: Vec<PathBuf>,
+    targets: Vec<AppResult<Option<PathBuf>>>,
+    steps: &[PipelineStep],
+    cancelled: &AtomicBool,
+    mut on_progress: F,
+) -> AppResult<Vec<MacroFileResult>>
+where
+    F: FnMut(usize, usize, &str),
+{
+    let total = files.len();
+    let chunk_size = rayon::current_num_threads().max(1);
+    let mut jobs: Vec<_> = files.into_iter().zip(targets).collect();
+    let mut results = Vec::with_capacity(total);
+
+    while !jobs.is_empty() {
+        limiter::check_cancelled(cancelled)?;
+        let chunk: Vec<_> = jobs.drain(..chunk_size.min(jobs.len())).collect();
+
+        let rendered: Vec<_> = chunk
+            .into_par_iter()
+            .map(|(file, target)| {
+                let encoded = target.and_then(|target| match target {
+                    Some(output_path) => {
+                        // Workers only read the flag; the batch lowers it once
+                        limiter::check_cancelled(cancelled)?;
+                        let bytes = render_file(&file, &output_path, steps)?;
+                        Ok(Some((output_path, bytes)))
+                    }
+                    None => Ok(None),
+                });
+                (file, encoded)
+            })
+            .collect();
+
+        for (file, encoded) in rendered {
+            limiter::check_cancelled(cancelled)?;
+            let path = file.to_string_lossy().to_string();
+
+            let outcome = encoded.and_then(|encoded| {
+                let Some((output_path, bytes)) = encoded else {
+                    return Ok(None);
+                };
+                if let Some(parent) = output_path.parent() {
+                    fs::create_dir_all(parent).map_err(AppError::IoError)?;
+                }
+                // An interrupted batch then never leaves a half-written output behind
+                crate::write_file_atomically(&output_path, &bytes, true).map_err(AppError::IoError)?;
+                Ok(Some(output_path))
+            });
+
+            results.push(match outcome {
+                Ok(output_path) => MacroFileResult {
+                    path: path.clone(),
+                    skipped: output_path.is_none(),
+                    output_path: output_path.map(|p| p.to_string_lossy().to_string()),
+                    error: None,
+                },
+                Err(e) => MacroFileResult {
+                    path: path.clone(),
+                    output_path: None,
+                    error: Some(e.to_string()),
+                    skipped: false,
+                },
+            });
+
+            on_progress(results.len(), total, &path);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Decode file, apply the pipeline and encode it for output_path
+fn render_file(file: &Path, output_path: &Path, steps: &[PipelineStep]) -> AppResult<Vec<u8>> {
+    let format = image::ImageFormat::from_path(output_path).map_err(AppError::ImageError)?;
+    let img = crate::open_image(file)?;
+    let img = apply_pipeline(img, steps)?;
+
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .map_err(AppError::ImageError)?;
+    Ok(bytes)
+}
+
+/// Where a batch writes the result for `file`
+fn output_path_for(
+    file: &Path,
+    output_dir: &Path,
+    name_template: Option<&str>,
+    index: usize,
+) -> AppResult<std::path::PathBuf> {
+    match name_template {
+        Some(template) => Ok(output_dir.join(naming::render_output_path(template, file, index)?)),
+        None => Ok(output_dir.join(file.file_name().unwrap_or_default())),
+    }
+}
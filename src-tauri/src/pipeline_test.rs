@@ -0,0 +1,323 @@
+#[cfg(test)]
+mod tests {
+    use crate::naming::CollisionStrategy;
+    use crate::pipeline::{apply_macro_to_directory, load_macro, save_macro, PipelineStep};
+    use crate::test_state::limiter;
+    use std::fs;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    fn resize_and_grayscale() -> Vec<PipelineStep> {
+        vec![
+            PipelineStep::Resize { width: 50, height: 50, keep_aspect_ratio: true },
+            PipelineStep::Grayscale,
+        ]
+    }
+
+    #[test]
+    fn test_macro_round_trips_through_json() {
+        let temp_dir = std::env::temp_dir().join("test_macro_round_trip");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let macro_path = temp_dir.join("macro.json");
+
+        let steps = vec![
+            PipelineStep::Crop { x: 1, y: 2, width: 3, height: 4 },
+            PipelineStep::Rotate { clockwise: false },
+            PipelineStep::Grayscale,
+        ];
+        save_macro(&macro_path, &steps).unwrap();
+
+        let content = fs::read_to_string(&macro_path).unwrap();
+        assert!(content.contains("\"op\": \"crop\""));
+        assert_eq!(load_macro(&macro_path).unwrap(), steps);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_load_macro_rejects_invalid_json() {
+        let temp_dir = std::env::temp_dir().join("test_macro_invalid");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let macro_path = temp_dir.join("macro.json");
+        fs::write(&macro_path, "[{\"op\": \"explode\"}]").unwrap();
+
+        assert!(load_macro(&macro_path).is_err());
+        assert!(load_macro(&temp_dir.join("missing.json")).is_err());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_macro_replays_resize_and_grayscale_over_directory() {
+        let temp_dir = std::env::temp_dir().join("test_macro_directory");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        image::RgbImage::from_pixel(200, 100, image::Rgb([200, 30, 30]))
+            .save(source_dir.join("a.png"))
+            .unwrap();
+        image::RgbImage::from_pixel(100, 200, image::Rgb([30, 200, 30]))
+            .save(source_dir.join("b.png"))
+            .unwrap();
+        fs::write(source_dir.join("notes.txt"), "not an image").unwrap();
+
+        let macro_path = temp_dir.join("macro.json");
+        save_macro(&macro_path, &resize_and_grayscale()).unwrap();
+        let steps = load_macro(&macro_path).unwrap();
+
+        let progress = Mutex::new(Vec::new());
+        let results = apply_macro_to_directory(&steps, &source_dir, &output_dir, None, CollisionStrategy::Overwrite, &AtomicBool::new(false), |done, total, _| {
+            progress.lock().unwrap().push((done, total));
+        })
+        .unwrap();
+
+        // Files finish in any order, but every one is counted once
+        let mut progress = progress.into_inner().unwrap();
+        progress.sort();
+        assert_eq!(progress, vec![(1, 2), (2, 2)]);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.error.is_none()));
+
+        let a = image::open(output_dir.join("a.png")).unwrap();
+        let b = image::open(output_dir.join("b.png")).unwrap();
+        assert_eq!((a.width(), a.height()), (50, 25));
+        assert_eq!((b.width(), b.height()), (25, 50));
+        assert_eq!(a.color(), image::ColorType::L8);
+        assert_eq!(b.color(), image::ColorType::L8);
+
+        // Sources are left untouched
+        let source = image::open(source_dir.join("a.png")).unwrap();
+        assert_eq!((source.width(), source.height()), (200, 100));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_macro_reports_failing_files_and_continues() {
+        let temp_dir = std::env::temp_dir().join("test_macro_failures");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        fs::write(source_dir.join("broken.png"), "not really a png").unwrap();
+        image::RgbImage::from_pixel(80, 80, image::Rgb([10, 20, 30]))
+            .save(source_dir.join("good.png"))
+            .unwrap();
+
+        let results = apply_macro_to_directory(&resize_and_grayscale(), &source_dir, &output_dir, None, CollisionStrategy::Overwrite, &AtomicBool::new(false), |_, _, _| {})
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].error.is_some());
+        assert!(results[0].output_path.is_none());
+        assert!(results[1].error.is_none());
+        assert!(output_dir.join("good.png").exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_macro_collision_strategies() {
+        let temp_dir = std::env::temp_dir().join("test_macro_collisions");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::create_dir_all(&output_dir).unwrap();
+
+        image::RgbImage::from_pixel(80, 80, image::Rgb([10, 20, 30]))
+            .save(source_dir.join("photo.png"))
+            .unwrap();
+        fs::write(output_dir.join("photo.png"), "existing result").unwrap();
+
+        let steps = resize_and_grayscale();
+        let run = |strategy| {
+            apply_macro_to_directory(&steps, &source_dir, &output_dir, None, strategy, &AtomicBool::new(false), |_, _, _| {}).unwrap()
+        };
+
+        let skipped = run(CollisionStrategy::Skip);
+        assert!(skipped[0].skipped);
+        assert!(skipped[0].output_path.is_none());
+        assert!(skipped[0].error.is_none());
+        assert_eq!(fs::read_to_string(output_dir.join("photo.png")).unwrap(), "existing result");
+
+        for expected in ["photo_1.png", "photo_2.png"] {
+            let renamed = run(CollisionStrategy::Rename);
+            assert!(!renamed[0].skipped);
+            assert_eq!(renamed[0].output_path, Some(output_dir.join(expected).to_string_lossy().to_string()));
+            assert_eq!(image::open(output_dir.join(expected)).unwrap().width(), 50);
+        }
+        assert_eq!(fs::read_to_string(output_dir.join("photo.png")).unwrap(), "existing result");
+
+        let overwritten = run(CollisionStrategy::Overwrite);
+        assert!(!overwritten[0].skipped);
+        assert!(image::open(output_dir.join("photo.png")).is_ok());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_parallel_macro_batch_keeps_sorted_order() {
+        let temp_dir = std::env::temp_dir().join("test_macro_parallel");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        // Written out of order, with a broken file in the middle
+        let names = ["f.png", "b.png", "h.png", "a.png", "e.png", "c.png", "g.png"];
+        for (i, name) in names.iter().enumerate() {
+            image::RgbImage::from_pixel(60 + i as u32 * 10, 40, image::Rgb([10, 20, 30]))
+                .save(source_dir.join(name))
+                .unwrap();
+        }
+        fs::write(source_dir.join("d.png"), "not really a png").unwrap();
+
+        let results = apply_macro_to_directory(&resize_and_grayscale(), &source_dir, &output_dir, None, CollisionStrategy::Overwrite, &AtomicBool::new(false), |_, _, _| {})
+            .unwrap();
+
+        let mut sorted: Vec<_> = names.iter().chain(["d.png"].iter()).collect();
+        sorted.sort();
+        assert_eq!(results.len(), sorted.len());
+        for (result, name) in results.iter().zip(&sorted) {
+            assert_eq!(result.path, source_dir.join(name).to_string_lossy());
+            assert_eq!(result.error.is_some(), **name == "d.png", "{}", name);
+        }
+        for name in names {
+            assert_eq!(image::open(output_dir.join(name)).unwrap().width(), 50);
+        }
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_parallel_macro_batch_renames_outputs_sharing_a_name() {
+        let temp_dir = std::env::temp_dir().join("test_macro_parallel_rename");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        for name in ["a.png", "b.png", "c.png"] {
+            image::RgbImage::from_pixel(80, 80, image::Rgb([10, 20, 30]))
+                .save(source_dir.join(name))
+                .unwrap();
+        }
+
+        // Every file renders to the same name, as when the camera is unknown
+        let results = apply_macro_to_directory(&resize_and_grayscale(), &source_dir, &output_dir, Some("{camera}"), CollisionStrategy::Rename, &AtomicBool::new(false), |_, _, _| {})
+            .unwrap();
+
+        let outputs: Vec<_> = results.iter().map(|r| r.output_path.clone().unwrap()).collect();
+        let expected: Vec<_> = ["unknown.png", "unknown_1.png", "unknown_2.png"]
+            .iter()
+            .map(|name| output_dir.join(name).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(outputs, expected);
+        assert!(expected.iter().all(|path| image::open(path).is_ok()));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_macro_batch_stops_when_cancelled() {
+        let temp_dir = std::env::temp_dir().join("test_macro_cancel");
+        let source_dir = temp_dir.join("source");
+        let output_dir = temp_dir.join("output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&source_dir).unwrap();
+
+        for name in ["a.png", "b.png", "c.png"] {
+            image::RgbImage::from_pixel(80, 80, image::Rgb([10, 20, 30]))
+                .save(source_dir.join(name))
+                .unwrap();
+        }
+
+        // Cancelled before the batch starts: nothing is processed
+        let cancelled = AtomicBool::new(true);
+        let mut processed = 0;
+        let result = apply_macro_to_directory(&resize_and_grayscale(), &source_dir, &output_dir, None, CollisionStrategy::Overwrite, &cancelled, |_, _, _| {
+            processed += 1;
+        });
+        assert_eq!(result.unwrap_err().to_string(), "Operation failed: cancelled");
+        assert_eq!(processed, 0);
+        assert!(!output_dir.join("a.png").exists());
+        // The request is consumed by the operation it stopped
+        assert!(!cancelled.load(Ordering::SeqCst));
+
+        // Cancelled after the first file: the rest of the batch is skipped
+        let result = apply_macro_to_directory(&resize_and_grayscale(), &source_dir, &output_dir, None, CollisionStrategy::Overwrite, &cancelled, |_, _, _| {
+            cancelled.store(true, Ordering::SeqCst);
+        });
+        assert!(result.is_err());
+        assert!(output_dir.join("a.png").exists());
+        assert!(!output_dir.join("b.png").exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_resize_is_applied_on_load() {
+        let temp_dir = std::env::temp_dir().join("test_pipeline_sidecar");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("photo.png");
+        image::RgbImage::from_pixel(200, 100, image::Rgb([40, 80, 120]))
+            .save(&image_path)
+            .unwrap();
+        let path = image_path.to_string_lossy().to_string();
+
+        let unedited = crate::load_image_with_sidecar(path.clone(), limiter()).await.unwrap();
+        assert!(unedited.steps.is_empty());
+        assert_eq!((unedited.image.width, unedited.image.height), (200, 100));
+
+        let steps = vec![PipelineStep::Resize { width: 50, height: 50, keep_aspect_ratio: true }];
+        let sidecar = crate::save_sidecar(path.clone(), steps.clone()).await.unwrap();
+        assert_eq!(sidecar, temp_dir.join("photo.edits.json").to_string_lossy());
+
+        let edited = crate::load_image_with_sidecar(path.clone(), limiter()).await.unwrap();
+        assert_eq!(edited.steps, steps);
+        assert_eq!((edited.image.width, edited.image.height), (50, 25));
+        assert_eq!(edited.image.format, crate::types::ImageFormat::PNG);
+        // The original file is left untouched
+        assert_eq!(image::open(&image_path).unwrap().width(), 200);
+
+        // Saving no steps removes the sidecar
+        crate::save_sidecar(path, Vec::new()).await.unwrap();
+        assert!(!temp_dir.join("photo.edits.json").exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_apply_pipeline_command_updates_dimensions() {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let mut buffer = Vec::new();
+        image::RgbaImage::from_pixel(40, 20, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = crate::types::ImageData {
+            path: "test.png".to_string(),
+            width: 40,
+            height: 20,
+            format: crate::types::ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let steps = vec![
+            PipelineStep::Rotate { clockwise: true },
+            PipelineStep::Crop { x: 0, y: 0, width: 10, height: 30 },
+        ];
+        let result = crate::apply_pipeline(image_data, steps, limiter()).await.unwrap();
+
+        assert_eq!((result.width, result.height), (10, 30));
+    }
+}
@@ -0,0 +1,237 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ConversionOptions, ImageData, ImageFormat, MetadataPolicy, TiffCompression};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageDecoder, ImageEncoder, ImageReader};
+
+    /// A stand-in ICC profile; encoders and decoders carry it as opaque bytes
+    fn test_profile() -> Vec<u8> {
+        let mut profile = b"test-icc-profile".to_vec();
+        profile.extend((0..400u32).map(|i| (i * 7 % 256) as u8));
+        profile
+    }
+
+    /// Helper function to create a JPEG with an embedded ICC profile
+    fn create_jpeg_with_profile() -> ImageData {
+        let img = image::RgbImage::from_fn(40, 30, |x, y| image::Rgb([(x * 6) as u8, (y * 8) as u8, 128]));
+        let mut buffer = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut buffer);
+        encoder.set_icc_profile(test_profile()).unwrap();
+        encoder
+            .write_image(img.as_raw(), 40, 30, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        ImageData {
+            path: "profiled.jpg".to_string(),
+            width: 40,
+            height: 30,
+            format: ImageFormat::JPEG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to read the ICC profile back out of ImageData
+    fn read_profile(image_data: &ImageData) -> Option<Vec<u8>> {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        crate::extract_icc_profile(&bytes)
+    }
+
+    #[tokio::test]
+    async fn test_profile_survives_resize() {
+        let original = create_jpeg_with_profile();
+        assert_eq!(read_profile(&original), Some(test_profile()));
+
+        let resized = crate::resize_image(original, 20, 15, false, None, None, limiter()).await.unwrap();
+
+        assert_eq!((resized.width, resized.height), (20, 15));
+        assert_eq!(read_profile(&resized), Some(test_profile()));
+    }
+
+    #[tokio::test]
+    async fn test_profile_survives_crop_and_conversion() {
+        let cropped = crate::crop_image(create_jpeg_with_profile(), 5, 5, 10, 10, None).await.unwrap();
+        assert_eq!(read_profile(&cropped), Some(test_profile()));
+
+        let converted = crate::convert_format(create_jpeg_with_profile(), "PNG".to_string(), None, None, limiter())
+            .await
+            .unwrap();
+        assert_eq!(read_profile(&converted), Some(test_profile()));
+    }
+
+    #[tokio::test]
+    async fn test_profile_survives_tiff_round_trip() {
+        let tiff = crate::convert_format(create_jpeg_with_profile(), "TIFF".to_string(), None, None, limiter())
+            .await
+            .unwrap();
+        assert_eq!(tiff.format, ImageFormat::TIFF);
+        assert_eq!(read_profile(&tiff), Some(test_profile()));
+
+        // Edits of the TIFF keep the profile too
+        let resized = crate::resize_image(tiff, 20, 15, false, None, None, limiter()).await.unwrap();
+        assert_eq!((&resized.format, resized.width), (&ImageFormat::TIFF, 20));
+        assert_eq!(read_profile(&resized), Some(test_profile()));
+    }
+
+    #[tokio::test]
+    async fn test_strip_profile_opts_out() {
+        let resized = crate::resize_image(create_jpeg_with_profile(), 20, 15, false, None, Some(true), limiter())
+            .await
+            .unwrap();
+        assert_eq!(read_profile(&resized), None);
+
+        let options = ConversionOptions { strip_profile: Some(true), ..Default::default() };
+        let converted = crate::convert_format(
+            create_jpeg_with_profile(),
+            "PNG".to_string(),
+            Some(options),
+            None,
+            limiter(),
+        )
+            .await
+            .unwrap();
+        assert_eq!(read_profile(&converted), None);
+    }
+
+    /// Helper function to create a JPEG whose EXIF holds camera data and a GPS location
+    fn create_jpeg_with_exif() -> ImageData {
+        use exif::{Field, In, Rational, Tag, Value};
+
+        let ascii = |text: &str| Value::Ascii(vec![text.as_bytes().to_vec()]);
+        let degrees = |d| Value::Rational(vec![
+            Rational { num: d, denom: 1 },
+            Rational { num: 30, denom: 1 },
+            Rational { num: 0, denom: 1 },
+        ]);
+        let fields = [
+            Field { tag: Tag::Make, ifd_num: In::PRIMARY, value: ascii("Fujifilm") },
+            Field { tag: Tag::Model, ifd_num: In::PRIMARY, value: ascii("X-T4") },
+            Field { tag: Tag::GPSLatitudeRef, ifd_num: In::PRIMARY, value: ascii("N") },
+            Field { tag: Tag::GPSLatitude, ifd_num: In::PRIMARY, value: degrees(52) },
+            Field { tag: Tag::GPSLongitudeRef, ifd_num: In::PRIMARY, value: ascii("E") },
+            Field { tag: Tag::GPSLongitude, ifd_num: In::PRIMARY, value: degrees(13) },
+        ];
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        let img = image::RgbImage::from_pixel(16, 12, image::Rgb([120, 80, 40]));
+        let mut buffer = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut buffer);
+        encoder.set_exif_metadata(tiff.into_inner()).unwrap();
+        encoder
+            .write_image(img.as_raw(), 16, 12, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        ImageData {
+            path: "geotagged.jpg".to_string(),
+            width: 16,
+            height: 12,
+            format: ImageFormat::JPEG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to report whether converted output has camera data and a location
+    fn read_camera_and_gps(image_data: &ImageData) -> (bool, bool) {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        // A TIFF holds its EXIF fields in its own directories rather than a blob
+        let exif = if image_data.format == ImageFormat::TIFF {
+            exif::Reader::new()
+                .read_from_container(&mut std::io::Cursor::new(bytes))
+                .unwrap()
+        } else {
+            let exif = ImageReader::new(std::io::Cursor::new(bytes))
+                .with_guessed_format()
+                .unwrap()
+                .into_decoder()
+                .unwrap()
+                .exif_metadata()
+                .unwrap();
+            let Some(exif) = exif else {
+                return (false, false);
+            };
+            exif::Reader::new().read_raw(exif).unwrap()
+        };
+        let has = |tag| exif.get_field(tag, exif::In::PRIMARY).is_some();
+        (
+            has(exif::Tag::Make) && has(exif::Tag::Model),
+            has(exif::Tag::GPSLatitude) || has(exif::Tag::GPSLongitude),
+        )
+    }
+
+    /// Helper function to convert with a metadata policy
+    async fn convert_with_policy(format: &str, policy: Option<MetadataPolicy>) -> ImageData {
+        let options = ConversionOptions { metadata_policy: policy, ..Default::default() };
+        crate::convert_format(create_jpeg_with_exif(), format.to_string(), Some(options), None, limiter())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_metadata_preserved_by_default() {
+        assert_eq!(read_camera_and_gps(&create_jpeg_with_exif()), (true, true));
+
+        for format in ["JPEG", "PNG", "WEBP", "TIFF"] {
+            let converted = convert_with_policy(format, None).await;
+            assert_eq!(read_camera_and_gps(&converted), (true, true), "{}", format);
+
+            let converted = convert_with_policy(format, Some(MetadataPolicy::Preserve)).await;
+            assert_eq!(read_camera_and_gps(&converted), (true, true), "{}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metadata_strip_removes_all_exif() {
+        for format in ["JPEG", "PNG", "WEBP", "TIFF"] {
+            let converted = convert_with_policy(format, Some(MetadataPolicy::Strip)).await;
+            assert_eq!(read_camera_and_gps(&converted), (false, false), "{}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metadata_strip_gps_only_keeps_camera_data() {
+        for format in ["JPEG", "PNG", "WEBP", "TIFF"] {
+            let converted = convert_with_policy(format, Some(MetadataPolicy::StripGpsOnly)).await;
+            assert_eq!(read_camera_and_gps(&converted), (true, false), "{}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compressed_tiff_follows_metadata_policy() {
+        let convert = |policy| async move {
+            let options = ConversionOptions {
+                metadata_policy: policy,
+                tiff_compression: Some(TiffCompression::Lzw),
+                ..Default::default()
+            };
+            crate::convert_format(create_jpeg_with_exif(), "TIFF".to_string(), Some(options), None, limiter())
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(read_camera_and_gps(&convert(None).await), (true, true));
+        assert_eq!(read_camera_and_gps(&convert(Some(MetadataPolicy::StripGpsOnly)).await), (true, false));
+        assert_eq!(read_camera_and_gps(&convert(Some(MetadataPolicy::Strip)).await), (false, false));
+
+        let options = ConversionOptions { tiff_compression: Some(TiffCompression::Deflate), ..Default::default() };
+        let tiff = crate::convert_format(create_jpeg_with_profile(), "TIFF".to_string(), Some(options), None, limiter())
+            .await
+            .unwrap();
+        assert_eq!(read_profile(&tiff), Some(test_profile()));
+    }
+
+    #[test]
+    fn test_metadata_policy_deserializes_from_snake_case() {
+        let options: ConversionOptions =
+            serde_json::from_str(r#"{"metadata_policy": "strip_gps_only"}"#).unwrap();
+        assert_eq!(options.metadata_policy, Some(MetadataPolicy::StripGpsOnly));
+    }
+}
@@ -0,0 +1,103 @@
+use image::{DynamicImage, ImageBuffer};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::ColorType;
+use crate::error::{AppError, AppResult};
+
+/// A single resolution level of a multi-resolution (pyramid) image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PyramidLevel {
+    /// Index of the level inside the file (0 is the first stored image)
+    pub level: u32,
+    /// Width of this level in pixels
+    pub width: u32,
+    /// Height of this level in pixels
+    pub height: u32,
+}
+
+/// Open a TIFF decoder for the given file
+fn open_tiff(path: &str) -> AppResult<Decoder<BufReader<File>>> {
+    let file = File::open(path).map_err(AppError::IoError)?;
+
+    Decoder::new(BufReader::new(file))
+        .map_err(|e| AppError::InvalidImageData(format!("Failed to read TIFF: {}", e)))
+}
+
+/// List every image stored in a TIFF file as a pyramid level
+///
+/// Each IFD in the main chain is reported in file order, which is how tiled
+/// pyramid TIFFs store their reduced-resolution copies.
+pub fn read_tiff_levels(path: &str) -> AppResult<Vec<PyramidLevel>> {
+    let mut decoder = open_tiff(path)?;
+    let mut levels = Vec::new();
+
+    loop {
+        let (width, height) = decoder.dimensions()
+            .map_err(|e| AppError::InvalidImageData(format!("Failed to read TIFF level: {}", e)))?;
+
+        levels.push(PyramidLevel {
+            level: levels.len() as u32,
+            width,
+            height,
+        });
+
+        if !decoder.more_images() {
+            break;
+        }
+
+        decoder.next_image()
+            .map_err(|e| AppError::InvalidImageData(format!("Failed to read TIFF level: {}", e)))?;
+    }
+
+    Ok(levels)
+}
+
+/// Decode a single level of a TIFF file without touching the other levels
+pub fn decode_tiff_level(path: &str, level: u32) -> AppResult<DynamicImage> {
+    let mut decoder = open_tiff(path)?;
+
+    decoder.seek_to_image(level as usize)
+        .map_err(|_| AppError::InvalidParameters(format!("Pyramid level {} does not exist", level)))?;
+
+    let (width, height) = decoder.dimensions()
+        .map_err(|e| AppError::InvalidImageData(format!("Failed to read TIFF level: {}", e)))?;
+    let color_type = decoder.colortype()
+        .map_err(|e| AppError::InvalidImageData(format!("Failed to read TIFF level: {}", e)))?;
+    let pixels = decoder.read_image()
+        .map_err(|e| AppError::InvalidImageData(format!("Failed to decode TIFF level: {}", e)))?;
+
+    let img = match (color_type, pixels) {
+        (ColorType::Gray(8), DecodingResult::U8(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::GrayA(8), DecodingResult::U8(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8)
+        }
+        (ColorType::RGB(8), DecodingResult::U8(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::RGBA(8), DecodingResult::U8(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
+        (ColorType::Gray(16), DecodingResult::U16(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma16)
+        }
+        (ColorType::RGB(16), DecodingResult::U16(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb16)
+        }
+        (ColorType::RGBA(16), DecodingResult::U16(data)) => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba16)
+        }
+        (color_type, _) => {
+            return Err(AppError::UnsupportedFormat(
+                format!("Unsupported TIFF color type: {:?}", color_type)
+            ));
+        }
+    };
+
+    img.ok_or_else(|| AppError::InvalidImageData(
+        format!("TIFF level {} has an unexpected pixel buffer size", level)
+    ))
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    /// Helper function to write a TIFF storing three levels (64x64, 32x32, 16x16)
+    fn create_pyramid_tiff(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let file = fs::File::create(&path).unwrap();
+        let mut encoder = TiffEncoder::new(file).unwrap();
+
+        for size in [64u32, 32, 16] {
+            let data: Vec<u8> = (0..size * size)
+                .flat_map(|i| [(i % 256) as u8, 128, 64])
+                .collect();
+            encoder.write_image::<colortype::RGB8>(size, size, &data).unwrap();
+        }
+
+        path
+    }
+
+    #[tokio::test]
+    async fn test_get_pyramid_levels_multi_level_tiff() {
+        let path = create_pyramid_tiff("test_pyramid_levels.tiff");
+
+        let result = get_pyramid_levels(path.to_string_lossy().to_string()).await;
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_ok());
+        let levels = result.unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0], PyramidLevel { level: 0, width: 64, height: 64 });
+        assert_eq!(levels[1], PyramidLevel { level: 1, width: 32, height: 32 });
+        assert_eq!(levels[2], PyramidLevel { level: 2, width: 16, height: 16 });
+    }
+
+    #[tokio::test]
+    async fn test_load_image_at_level() {
+        let path = create_pyramid_tiff("test_pyramid_load.tiff");
+        let path_str = path.to_string_lossy().to_string();
+
+        let level = load_image_at_level(path_str.clone(), 2).await;
+        let missing = load_image_at_level(path_str, 5).await;
+        fs::remove_file(&path).unwrap();
+
+        assert!(level.is_ok());
+        let level = level.unwrap();
+        assert_eq!(level.width, 16);
+        assert_eq!(level.height, 16);
+        assert_eq!(level.format, ImageFormat::TIFF);
+
+        assert!(missing.is_err());
+        assert!(missing.unwrap_err().message.contains("does not exist"));
+    }
+
+    #[tokio::test]
+    async fn test_single_level_fallback() {
+        let path = std::env::temp_dir().join("test_pyramid_single.png");
+        image::RgbImage::new(20, 10).save(&path).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let levels = get_pyramid_levels(path_str.clone()).await;
+        let level = load_image_at_level(path_str, 0).await;
+        fs::remove_file(&path).unwrap();
+
+        let levels = levels.unwrap();
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].width, 20);
+        assert_eq!(levels[0].height, 10);
+        assert_eq!(level.unwrap().format, ImageFormat::PNG);
+    }
+}
@@ -0,0 +1,297 @@
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
+use serde::{Deserialize, Serialize};
+use crate::error::{AppError, AppResult};
+
+/// Standard deviation of the Gaussian SSIM window
+const SSIM_SIGMA: f32 = 1.5;
+/// Stabilizing constants for 8-bit data: (0.01 * 255)^2 and (0.03 * 255)^2
+const SSIM_C1: f32 = 6.5025;
+const SSIM_C2: f32 = 58.5225;
+
+/// Objective similarity between an original image and a processed copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityMetrics {
+    /// Peak signal-to-noise ratio over the RGB channels in dB
+    ///
+    /// Infinite for identical images, which is sent to the frontend as `null`.
+    pub psnr: f64,
+    /// Mean structural similarity of the luma channel (1.0 = identical)
+    pub ssim: f64,
+}
+
+/// Pixel-level differences between two images of the same size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageComparison {
+    /// Mean absolute difference over all RGBA channel values (0.0-255.0)
+    pub mean_absolute_error: f64,
+    /// Largest difference of any single channel value (0-255)
+    pub max_difference: u8,
+    /// Percentage of pixels identical in every channel (0.0-100.0)
+    pub matching_percent: f64,
+}
+
+/// Largest step between neighboring pixels still treated as part of a smooth
+/// region; bigger steps are real edges
+const BANDING_EDGE_LIMIT: u8 = 64;
+
+/// Precision actually used by each color channel, in bits (0-8)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChannelBitDepth {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+/// How strongly smooth areas of an image show visible banding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandingReport {
+    /// 0.0 for smooth gradients up to 1.0 for hard steps between flat bands
+    pub score: f64,
+    /// Bits needed to represent the distinct values each channel uses
+    pub effective_bits: ChannelBitDepth,
+}
+
+/// Look for banding in smooth gradient regions
+///
+/// Every change between neighboring pixels that isn't a real edge is weighed.
+/// Changes that jump between two flat plateaus count as banding for every level
+/// skipped, while single-level steps are the normal 8-bit gradient. The score is
+/// the share of the total change in smooth regions caused by banding.
+pub fn detect_banding(img: &DynamicImage) -> BandingReport {
+    let rgb = img.to_rgb8();
+    let (width, height) = rgb.dimensions();
+
+    let pixel = |x: u32, y: u32| rgb.get_pixel(x, y).0;
+    let step = |a: [u8; 3], b: [u8; 3]| {
+        a.iter().zip(b).map(|(&a, b)| a.abs_diff(b)).max().unwrap_or(0)
+    };
+
+    let mut total_change = 0u64;
+    let mut banded_change = 0u64;
+
+    // Scan rows and columns as lines of pixels
+    let lines = (0..height)
+        .map(|y| (0..width).map(|x| (x, y)).collect::<Vec<_>>())
+        .chain((0..width).map(|x| (0..height).map(|y| (x, y)).collect()));
+
+    for line in lines {
+        let values: Vec<[u8; 3]> = line.into_iter().map(|(x, y)| pixel(x, y)).collect();
+        for i in 0..values.len().saturating_sub(1) {
+            let change = step(values[i], values[i + 1]);
+            if change == 0 || change > BANDING_EDGE_LIMIT {
+                continue;
+            }
+            total_change += change as u64;
+
+            let flat_before = i > 0 && values[i - 1] == values[i];
+            let flat_after = i + 2 < values.len() && values[i + 2] == values[i + 1];
+            if flat_before && flat_after {
+                banded_change += change as u64 - 1;
+            }
+        }
+    }
+
+    let score = if total_change == 0 {
+        0.0
+    } else {
+        banded_change as f64 / total_change as f64
+    };
+
+    BandingReport {
+        score,
+        effective_bits: effective_bit_depth(&rgb),
+    }
+}
+
+/// Bits needed for the number of distinct values in each channel
+fn effective_bit_depth(rgb: &image::RgbImage) -> ChannelBitDepth {
+    let mut used = [[false; 256]; 3];
+    for pixel in rgb.pixels() {
+        for (channel, &value) in pixel.0.iter().enumerate() {
+            used[channel][value as usize] = true;
+        }
+    }
+
+    let bits = |channel: usize| {
+        let levels = used[channel].iter().filter(|&&u| u).count() as u32;
+        // ceil(log2(levels)); a single level needs no bits
+        (u32::BITS - levels.saturating_sub(1).leading_zeros()) as u8
+    };
+
+    ChannelBitDepth {
+        red: bits(0),
+        green: bits(1),
+        blue: bits(2),
+    }
+}
+
+/// Compare two images of the same size
+pub fn compute_quality_metrics(
+    original: &DynamicImage,
+    compressed: &DynamicImage,
+) -> AppResult<QualityMetrics> {
+    check_dimensions(original, compressed)?;
+
+    Ok(QualityMetrics {
+        psnr: psnr(original, compressed),
+        ssim: ssim(original, compressed),
+    })
+}
+
+/// Compare two images of the same size pixel by pixel
+///
+/// Alpha is compared along with the color channels, so identical results mean
+/// the images really are the same.
+pub fn compare_images(a: &DynamicImage, b: &DynamicImage) -> AppResult<ImageComparison> {
+    check_dimensions(a, b)?;
+
+    let a = a.to_rgba8();
+    let b = b.to_rgba8();
+
+    let mut total_difference = 0u64;
+    let mut max_difference = 0u8;
+    let mut matching_pixels = 0u64;
+
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let mut identical = true;
+        for (&x, &y) in pa.0.iter().zip(&pb.0) {
+            let diff = x.abs_diff(y);
+            total_difference += diff as u64;
+            max_difference = max_difference.max(diff);
+            identical &= diff == 0;
+        }
+        matching_pixels += identical as u64;
+    }
+
+    let pixel_count = (a.width() as u64 * a.height() as u64).max(1);
+
+    Ok(ImageComparison {
+        mean_absolute_error: total_difference as f64 / (pixel_count * 4) as f64,
+        max_difference,
+        matching_percent: matching_pixels as f64 * 100.0 / pixel_count as f64,
+    })
+}
+
+/// Render where two images of the same size differ
+///
+/// Each pixel's brightness is the largest absolute difference of any channel,
+/// alpha included, so identical images give an all-black result.
+pub fn diff_heatmap(a: &DynamicImage, b: &DynamicImage) -> AppResult<GrayImage> {
+    check_dimensions(a, b)?;
+
+    let a = a.to_rgba8();
+    let b = b.to_rgba8();
+
+    Ok(GrayImage::from_fn(a.width(), a.height(), |x, y| {
+        let (pa, pb) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let diff = pa.0.iter().zip(&pb.0).map(|(&x, &y)| x.abs_diff(y)).max().unwrap_or(0);
+        Luma([diff])
+    }))
+}
+
+/// Reject image pairs whose dimensions differ
+fn check_dimensions(a: &DynamicImage, b: &DynamicImage) -> AppResult<()> {
+    if a.dimensions() != b.dimensions() {
+        return Err(AppError::InvalidParameters(format!(
+            "Images must have the same dimensions ({}x{} vs {}x{})",
+            a.width(), a.height(), b.width(), b.height()
+        )));
+    }
+    Ok(())
+}
+
+/// Peak signal-to-noise ratio of the 8-bit RGB channels
+fn psnr(a: &DynamicImage, b: &DynamicImage) -> f64 {
+    let a = a.to_rgb8();
+    let b = b.to_rgb8();
+
+    let squared_error: f64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| {
+            let diff = x as f64 - y as f64;
+            diff * diff
+        })
+        .sum();
+    let mse = squared_error / a.as_raw().len() as f64;
+
+    if mse == 0.0 {
+        return f64::INFINITY;
+    }
+
+    10.0 * (255.0 * 255.0 / mse).log10()
+}
+
+/// Mean SSIM of the luma channel using a Gaussian window
+fn ssim(a: &DynamicImage, b: &DynamicImage) -> f64 {
+    let width = a.width() as usize;
+    let height = a.height() as usize;
+
+    let x: Vec<f32> = a.to_luma8().as_raw().iter().map(|&v| v as f32).collect();
+    let y: Vec<f32> = b.to_luma8().as_raw().iter().map(|&v| v as f32).collect();
+    let xx: Vec<f32> = x.iter().map(|v| v * v).collect();
+    let yy: Vec<f32> = y.iter().map(|v| v * v).collect();
+    let xy: Vec<f32> = x.iter().zip(&y).map(|(a, b)| a * b).collect();
+
+    let mu_x = gaussian_blur(&x, width, height);
+    let mu_y = gaussian_blur(&y, width, height);
+    let sigma_xx = gaussian_blur(&xx, width, height);
+    let sigma_yy = gaussian_blur(&yy, width, height);
+    let sigma_xy = gaussian_blur(&xy, width, height);
+
+    let total: f64 = (0..width * height)
+        .map(|i| {
+            let (mx, my) = (mu_x[i], mu_y[i]);
+            let var_x = sigma_xx[i] - mx * mx;
+            let var_y = sigma_yy[i] - my * my;
+            let cov = sigma_xy[i] - mx * my;
+
+            let numerator = (2.0 * mx * my + SSIM_C1) * (2.0 * cov + SSIM_C2);
+            let denominator = (mx * mx + my * my + SSIM_C1) * (var_x + var_y + SSIM_C2);
+            (numerator / denominator) as f64
+        })
+        .sum();
+
+    total / (width * height) as f64
+}
+
+/// Separable Gaussian blur of a single-channel plane, clamping at the edges
+fn gaussian_blur(plane: &[f32], width: usize, height: usize) -> Vec<f32> {
+    let radius = (SSIM_SIGMA * 3.0).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp())
+        .collect();
+    let kernel_sum: f32 = kernel.iter().sum();
+
+    let blur_line = |get: &dyn Fn(isize) -> f32| -> f32 {
+        kernel
+            .iter()
+            .enumerate()
+            .map(|(k, weight)| weight * get(k as isize - radius))
+            .sum::<f32>()
+            / kernel_sum
+    };
+
+    let clamp = |value: isize, len: usize| value.clamp(0, len as isize - 1) as usize;
+
+    let mut horizontal = vec![0.0; plane.len()];
+    for row in 0..height {
+        for col in 0..width {
+            horizontal[row * width + col] = blur_line(&|offset| {
+                plane[row * width + clamp(col as isize + offset, width)]
+            });
+        }
+    }
+
+    let mut output = vec![0.0; plane.len()];
+    for row in 0..height {
+        for col in 0..width {
+            output[row * width + col] = blur_line(&|offset| {
+                horizontal[clamp(row as isize + offset, height) * width + col]
+            });
+        }
+    }
+
+    output
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgb};
+
+    /// Helper function to create a textured test image
+    fn create_test_image() -> image::RgbImage {
+        ImageBuffer::from_fn(64, 48, |x, y| {
+            Rgb([(x * 4) as u8, (y * 5) as u8, ((x ^ y) * 8) as u8])
+        })
+    }
+
+    /// Helper function to wrap an image as PNG ImageData
+    fn encode_png(img: &image::RgbImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_images_are_perfect() {
+        let img = encode_png(&create_test_image());
+
+        let metrics = crate::compute_quality_metrics(img.clone(), img, limiter()).await.unwrap();
+
+        assert!(metrics.psnr.is_infinite());
+        assert!((metrics.ssim - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_degraded_copy_scores_lower() {
+        let original = create_test_image();
+        let mut noisy = original.clone();
+        for (i, pixel) in noisy.pixels_mut().enumerate() {
+            let noise = if i % 2 == 0 { 20 } else { -20 };
+            for channel in pixel.0.iter_mut() {
+                *channel = (*channel as i32 + noise).clamp(0, 255) as u8;
+            }
+        }
+        let blurred = image::imageops::blur(&original, 2.0);
+
+        let noisy_metrics = crate::compute_quality_metrics(encode_png(&original), encode_png(&noisy), limiter())
+            .await
+            .unwrap();
+        let blurred_metrics = crate::compute_quality_metrics(encode_png(&original), encode_png(&blurred), limiter())
+            .await
+            .unwrap();
+
+        for metrics in [&noisy_metrics, &blurred_metrics] {
+            assert!(metrics.psnr.is_finite());
+            assert!(metrics.psnr > 10.0 && metrics.psnr < 40.0, "psnr was {}", metrics.psnr);
+            assert!(metrics.ssim < 0.99, "ssim was {}", metrics.ssim);
+            assert!(metrics.ssim > 0.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatch_is_rejected() {
+        let original = encode_png(&create_test_image());
+        let smaller = encode_png(&ImageBuffer::from_pixel(10, 10, Rgb([0, 0, 0])));
+
+        let result = crate::compute_quality_metrics(original, smaller, limiter()).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("same dimensions"));
+    }
+
+    #[tokio::test]
+    async fn test_posterized_gradient_scores_high_for_banding() {
+        let smooth = ImageBuffer::from_fn(256, 16, |x, _y| Rgb([x as u8, x as u8, 255 - x as u8]));
+        // Eight levels per channel, 36 apart
+        let posterized = ImageBuffer::from_fn(256, 16, |x, _y| {
+            let level = (x as u8 >> 5) * 36;
+            Rgb([level, level, 252 - level])
+        });
+
+        let smooth_report = crate::detect_banding(encode_png(&smooth), limiter()).await.unwrap();
+        let banded_report = crate::detect_banding(encode_png(&posterized), limiter()).await.unwrap();
+
+        assert!(smooth_report.score < 0.05, "smooth score was {}", smooth_report.score);
+        assert!(banded_report.score > 0.9, "banded score was {}", banded_report.score);
+        assert_eq!(smooth_report.effective_bits.red, 8);
+        assert_eq!(
+            banded_report.effective_bits,
+            crate::ChannelBitDepth { red: 3, green: 3, blue: 3 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_textured_image_is_not_banded() {
+        let report = crate::detect_banding(encode_png(&create_test_image()), limiter()).await.unwrap();
+
+        assert!(report.score < 0.5, "score was {}", report.score);
+    }
+
+    #[tokio::test]
+    async fn test_compare_image_with_itself() {
+        let img = encode_png(&create_test_image());
+
+        let comparison = crate::compare_images(img.clone(), img, limiter()).await.unwrap();
+
+        assert_eq!(comparison.mean_absolute_error, 0.0);
+        assert_eq!(comparison.max_difference, 0);
+        assert_eq!(comparison.matching_percent, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_compare_image_with_shifted_copy() {
+        let original = create_test_image();
+        let shifted = ImageBuffer::from_fn(64, 48, |x, y| *original.get_pixel((x + 1) % 64, y));
+
+        let comparison = crate::compare_images(encode_png(&original), encode_png(&shifted), limiter())
+            .await
+            .unwrap();
+
+        assert!(comparison.mean_absolute_error > 0.0);
+        assert!(comparison.max_difference > 0);
+        assert!(comparison.matching_percent < 100.0);
+
+        let smaller = encode_png(&ImageBuffer::from_pixel(10, 10, Rgb([0, 0, 0])));
+        let result = crate::compare_images(encode_png(&original), smaller, limiter()).await;
+        assert!(result.unwrap_err().message.contains("same dimensions"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_heatmap_of_identical_images_is_black() {
+        let img = encode_png(&create_test_image());
+
+        let heatmap = crate::diff_heatmap(img.clone(), img, limiter()).await.unwrap();
+
+        assert_eq!(heatmap.format, ImageFormat::PNG);
+        assert_eq!(heatmap.path, "test_diff.png");
+        assert_eq!((heatmap.width, heatmap.height), (64, 48));
+        let bytes = general_purpose::STANDARD.decode(&heatmap.data).unwrap();
+        let pixels = image::load_from_memory(&bytes).unwrap().to_luma8();
+        assert!(pixels.pixels().all(|p| p.0[0] == 0));
+    }
+
+    #[tokio::test]
+    async fn test_diff_heatmap_highlights_changed_region() {
+        let original = create_test_image();
+        let mut edited = original.clone();
+        for y in 10..20 {
+            for x in 30..40 {
+                let pixel = edited.get_pixel_mut(x, y);
+                pixel.0[0] = pixel.0[0].wrapping_add(128);
+            }
+        }
+
+        let heatmap = crate::diff_heatmap(encode_png(&original), encode_png(&edited), limiter()).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&heatmap.data).unwrap();
+        let pixels = image::load_from_memory(&bytes).unwrap().to_luma8();
+
+        assert_eq!(pixels.get_pixel(35, 15).0[0], 128);
+        assert_eq!(pixels.get_pixel(5, 5).0[0], 0);
+        assert_eq!(pixels.get_pixel(45, 15).0[0], 0);
+
+        let smaller = encode_png(&ImageBuffer::from_pixel(10, 10, Rgb([0, 0, 0])));
+        assert!(crate::diff_heatmap(encode_png(&original), smaller, limiter()).await.is_err());
+    }
+}
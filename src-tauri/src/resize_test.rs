@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod tests {
     use crate::types::{ImageData, ImageFormat};
-    use crate::resize_image;
+    use crate::{resize_image, CollisionStrategy};
+    use crate::test_state::{limiter, cancel_registry};
     use base64::{Engine as _, engine::general_purpose};
     use image::{ImageBuffer, Rgba};
 
@@ -30,13 +31,14 @@ mod tests {
             format: ImageFormat::PNG,
             data: base64_data,
             has_alpha: false,
+            display_data: None,
         }
     }
 
     #[tokio::test]
     async fn test_resize_without_aspect_ratio() {
         let image = create_test_image(100, 100);
-        let result = resize_image(image, 50, 75, false).await;
+        let result = resize_image(image, 50, 75, false, None, None, limiter()).await;
 
         assert!(result.is_ok());
         let resized = result.unwrap();
@@ -47,7 +49,7 @@ mod tests {
     #[tokio::test]
     async fn test_resize_with_aspect_ratio() {
         let image = create_test_image(100, 50);
-        let result = resize_image(image, 200, 200, true).await;
+        let result = resize_image(image, 200, 200, true, None, None, limiter()).await;
 
         assert!(result.is_ok());
         let resized = result.unwrap();
@@ -61,14 +63,14 @@ mod tests {
         let image = create_test_image(100, 100);
         
         // Test zero width
-        let result = resize_image(image.clone(), 0, 50, false).await;
+        let result = resize_image(image.clone(), 0, 50, false, None, None, limiter()).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("positive integers"));
+        assert!(result.unwrap_err().message.contains("positive integers"));
 
         // Test zero height
-        let result = resize_image(image, 50, 0, false).await;
+        let result = resize_image(image, 50, 0, false, None, None, limiter()).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("positive integers"));
+        assert!(result.unwrap_err().message.contains("positive integers"));
     }
 
     #[tokio::test]
@@ -76,7 +78,7 @@ mod tests {
         let image = create_test_image(100, 100);
         let original_format = image.format.clone();
         
-        let result = resize_image(image, 50, 50, false).await;
+        let result = resize_image(image, 50, 50, false, None, None, limiter()).await;
         assert!(result.is_ok());
         
         let resized = result.unwrap();
@@ -87,7 +89,7 @@ mod tests {
     async fn test_resize_aspect_ratio_calculation() {
         // Test landscape image
         let image = create_test_image(200, 100);
-        let result = resize_image(image, 100, 100, true).await;
+        let result = resize_image(image, 100, 100, true, None, None, limiter()).await;
         assert!(result.is_ok());
         let resized = result.unwrap();
         assert_eq!(resized.width, 100);
@@ -95,10 +97,255 @@ mod tests {
 
         // Test portrait image
         let image = create_test_image(100, 200);
-        let result = resize_image(image, 100, 100, true).await;
+        let result = resize_image(image, 100, 100, true, None, None, limiter()).await;
         assert!(result.is_ok());
         let resized = result.unwrap();
         assert_eq!(resized.width, 50); // Maintains 1:2 ratio
         assert_eq!(resized.height, 100);
     }
+
+    #[tokio::test]
+    async fn test_generate_mipmaps_levels() {
+        let image = create_test_image(256, 256);
+        let result = crate::generate_mipmaps(image, limiter()).await;
+
+        assert!(result.is_ok());
+        let levels = result.unwrap();
+        assert_eq!(levels.len(), 9);
+
+        for (index, level) in levels.iter().enumerate() {
+            let expected = 256 >> index;
+            assert_eq!(level.width, expected);
+            assert_eq!(level.height, expected);
+            assert_eq!(level.format, ImageFormat::PNG);
+        }
+        assert_eq!(levels[0].path, "test.png");
+        assert_eq!(levels[3].path, "test_mip3.png");
+    }
+
+    #[tokio::test]
+    async fn test_generate_mipmaps_gamma_correct_average() {
+        // A red/blue checkerboard averages to purple; in linear light the
+        // channels come out at ~188 instead of the too-dark 128
+        let image = create_test_image(4, 4);
+        let levels = crate::generate_mipmaps(image, limiter()).await.unwrap();
+
+        let bytes = general_purpose::STANDARD.decode(&levels[1].data).unwrap();
+        let pixel = *image::load_from_memory(&bytes).unwrap().to_rgba8().get_pixel(0, 0);
+        assert!((180..=195).contains(&pixel.0[0]));
+        assert!((180..=195).contains(&pixel.0[2]));
+        assert_eq!(pixel.0[1], 0);
+    }
+
+    #[tokio::test]
+    async fn test_generate_mipmaps_non_square() {
+        let image = create_test_image(8, 2);
+        let levels = crate::generate_mipmaps(image, limiter()).await.unwrap();
+
+        let dimensions: Vec<(u32, u32)> = levels.iter().map(|l| (l.width, l.height)).collect();
+        assert_eq!(dimensions, vec![(8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    /// Mean absolute difference between two same-size RGBA images
+    fn mean_difference(a: &ImageData, b: &ImageData) -> f64 {
+        let decode = |image: &ImageData| {
+            let bytes = general_purpose::STANDARD.decode(&image.data).unwrap();
+            image::load_from_memory(&bytes).unwrap().to_rgba8()
+        };
+        let (a, b) = (decode(a), decode(b));
+        let total: u64 = a
+            .as_raw()
+            .iter()
+            .zip(b.as_raw())
+            .map(|(x, y)| x.abs_diff(*y) as u64)
+            .sum();
+        total as f64 / a.as_raw().len() as f64
+    }
+
+    #[tokio::test]
+    async fn test_resample_presets_produce_different_output() {
+        use crate::types::{ResampleFilter, ResampleOptions};
+
+        let best = ResampleOptions::preset("best").unwrap();
+        let fast = ResampleOptions::preset("FAST").unwrap();
+        assert_eq!(best, ResampleOptions { filter: ResampleFilter::Lanczos3, gamma_correct: true });
+        assert_eq!(fast.filter, ResampleFilter::Nearest);
+        assert!(ResampleOptions::preset("ultra").is_none());
+
+        let image = create_test_image(64, 64);
+        let best_result = resize_image(image.clone(), 16, 16, false, Some(best), None, limiter()).await.unwrap();
+        let fast_result = resize_image(image, 16, 16, false, Some(fast), None, limiter()).await.unwrap();
+
+        assert_eq!((best_result.width, best_result.height), (16, 16));
+        assert_eq!((fast_result.width, fast_result.height), (16, 16));
+        // Nearest keeps pure red/blue pixels while the gamma-correct Lanczos
+        // average turns the checkerboard into bright purple
+        assert!(mean_difference(&best_result, &fast_result) > 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_resample_preset_rejects_unknown_names() {
+        assert!(crate::get_resample_preset("balanced".to_string()).await.is_ok());
+        assert!(crate::get_resample_preset("ultra".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nearest_upscale_duplicates_pixels() {
+        use crate::types::{ResampleFilter, ResampleOptions};
+
+        let nearest = ResampleOptions { filter: ResampleFilter::Nearest, gamma_correct: false };
+        let result = resize_image(create_test_image(2, 2), 8, 8, false, Some(nearest), None, limiter()).await.unwrap();
+
+        assert_eq!((result.width, result.height), (8, 8));
+
+        // Every source pixel becomes an exact 4x4 block of the same color
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        let pixels = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        for (x, y, pixel) in pixels.enumerate_pixels() {
+            let expected = if (x / 4 + y / 4) % 2 == 0 { [255, 0, 0, 255] } else { [0, 0, 255, 255] };
+            assert_eq!(pixel.0, expected, "pixel at {},{}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_resample_filter_names() {
+        use crate::types::ResampleFilter;
+
+        let filter: ResampleFilter = serde_json::from_str("\"triangle\"").unwrap();
+        assert_eq!(filter, ResampleFilter::Triangle);
+        assert!(serde_json::from_str::<ResampleFilter>("\"bicubic\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resize_scale() {
+        let half = crate::resize_image_scale(create_test_image(200, 100), 0.5, None, limiter()).await.unwrap();
+        assert_eq!((half.width, half.height), (100, 50));
+
+        let double = crate::resize_image_scale(create_test_image(3, 5), 2.0, None, limiter()).await.unwrap();
+        assert_eq!((double.width, double.height), (6, 10));
+
+        // Tiny results never drop below one pixel
+        let tiny = crate::resize_image_scale(create_test_image(10, 2), 0.1, None, limiter()).await.unwrap();
+        assert_eq!((tiny.width, tiny.height), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_resize_scale_rejects_non_positive() {
+        assert!(crate::resize_image_scale(create_test_image(10, 10), 0.0, None, limiter()).await.is_err());
+        assert!(crate::resize_image_scale(create_test_image(10, 10), -1.0, None, limiter()).await.is_err());
+        assert!(crate::resize_image_scale(create_test_image(10, 10), f32::NAN, None, limiter()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_srcset_writes_one_file_per_width() {
+        let output_dir = std::env::temp_dir().join("test_srcset");
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let image = ImageData { path: "/photos/hero.png".to_string(), ..create_test_image(800, 400) };
+        let entries = crate::generate_srcset(
+            image,
+            vec![640, 1280, 160, 320],
+            "jpeg".to_string(),
+            Some(80),
+            output_dir.to_string_lossy().to_string(),
+            None,
+            limiter(),
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+
+        // 1280 is wider than the source and is skipped; the rest come back sorted
+        let sizes: Vec<(u32, u32)> = entries.iter().map(|e| (e.width, e.height)).collect();
+        assert_eq!(sizes, vec![(160, 80), (320, 160), (640, 320)]);
+
+        for entry in &entries {
+            let expected = output_dir.join(format!("hero-{}w.jpg", entry.width));
+            assert_eq!(std::path::Path::new(&entry.path), expected);
+
+            let bytes = std::fs::read(&entry.path).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), image::ImageFormat::Jpeg);
+            let decoded = image::load_from_memory(&bytes).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (entry.width, entry.height));
+        }
+        assert_eq!(std::fs::read_dir(&output_dir).unwrap().count(), 3);
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_generate_srcset_follows_collision_strategy() {
+        let output_dir = std::env::temp_dir().join("test_srcset_collisions");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let existing = output_dir.join("hero-100w.png");
+        std::fs::write(&existing, b"keep me").unwrap();
+
+        let srcset = |strategy| crate::generate_srcset(
+            ImageData { path: "hero.png".to_string(), ..create_test_image(200, 100) },
+            vec![100, 50],
+            "png".to_string(),
+            None,
+            output_dir.to_string_lossy().to_string(),
+            Some(strategy),
+            limiter(),
+            cancel_registry(),
+        );
+
+        let skipped = srcset(CollisionStrategy::Skip).await.unwrap();
+        assert_eq!(skipped.iter().map(|e| e.width).collect::<Vec<_>>(), vec![50]);
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep me");
+
+        let renamed = srcset(CollisionStrategy::Rename).await.unwrap();
+        assert_eq!(std::path::Path::new(&renamed[1].path), output_dir.join("hero-100w_1.png"));
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep me");
+
+        srcset(CollisionStrategy::Overwrite).await.unwrap();
+        assert!(image::load_from_memory(&std::fs::read(&existing).unwrap()).is_ok());
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_generate_srcset_rejects_bad_input() {
+        let output_dir = std::env::temp_dir().join("test_srcset_invalid").to_string_lossy().to_string();
+
+        let empty = crate::generate_srcset(
+            create_test_image(100, 100),
+            vec![],
+            "png".to_string(),
+            None,
+            output_dir.clone(),
+            None,
+            limiter(),
+            cancel_registry(),
+        ).await;
+        assert!(empty.is_err());
+
+        let zero = crate::generate_srcset(
+            create_test_image(100, 100),
+            vec![0, 50],
+            "png".to_string(),
+            None,
+            output_dir.clone(),
+            None,
+            limiter(),
+            cancel_registry(),
+        ).await;
+        assert!(zero.is_err());
+
+        let format = crate::generate_srcset(
+            create_test_image(100, 100),
+            vec![50],
+            "xyz".to_string(),
+            None,
+            output_dir.clone(),
+            None,
+            limiter(),
+            cancel_registry(),
+        ).await;
+        assert!(format.is_err());
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
 }
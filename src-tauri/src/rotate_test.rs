@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat, RGBColor};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba};
+
+    /// Helper function to create a white image crossed by a black line at the given angle
+    /// Returns the image and the line's end points
+    fn create_tilted_line_image(size: u32, degrees: f32) -> (ImageData, (f32, f32, f32, f32)) {
+        let center = size as f32 / 2.0;
+        let half_length = size as f32 * 0.4;
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        let img = ImageBuffer::from_fn(size, size, |x, y| {
+            // Distance from the line through the center
+            let dx = x as f32 - center;
+            let dy = y as f32 - center;
+            let along = dx * cos + dy * sin;
+            let across = -dx * sin + dy * cos;
+            if across.abs() <= 1.5 && along.abs() <= half_length {
+                Rgba([0u8, 0, 0, 255])
+            } else {
+                Rgba([255u8, 255, 255, 255])
+            }
+        });
+
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        let line = (
+            center - half_length * cos,
+            center - half_length * sin,
+            center + half_length * cos,
+            center + half_length * sin,
+        );
+
+        let image_data = ImageData {
+            path: "test.png".to_string(),
+            width: size,
+            height: size,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        (image_data, line)
+    }
+
+    /// Helper function to decode ImageData back into RGBA pixels
+    fn decode_rgba(image_data: &ImageData) -> image::RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    /// Mean row of the dark line pixels in each column that contains any
+    fn line_rows(img: &image::RgbaImage) -> Vec<f32> {
+        (0..img.width())
+            .filter_map(|x| {
+                let rows: Vec<u32> = (0..img.height())
+                    .filter(|&y| {
+                        let p = img.get_pixel(x, y);
+                        p.0[3] == 255 && p.0[0] < 100
+                    })
+                    .collect();
+                if rows.is_empty() {
+                    None
+                } else {
+                    Some(rows.iter().sum::<u32>() as f32 / rows.len() as f32)
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_straighten_by_line_levels_a_10_degree_line() {
+        let (image_data, (x1, y1, x2, y2)) = create_tilted_line_image(200, 10.0);
+
+        let before = line_rows(&decode_rgba(&image_data));
+        let before_spread = before.iter().cloned().fold(f32::MIN, f32::max)
+            - before.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(before_spread > 20.0);
+
+        let result = crate::straighten_by_line(image_data, x1, y1, x2, y2, None, limiter()).await.unwrap();
+
+        // Canvas grows to fit a 10° rotation: 200 * (cos 10° + sin 10°)
+        assert_eq!(result.width, 232);
+        assert_eq!(result.height, 232);
+        assert!(result.has_alpha);
+
+        let after = line_rows(&decode_rgba(&result));
+        let after_spread = after.iter().cloned().fold(f32::MIN, f32::max)
+            - after.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(after_spread <= 1.5, "line should be horizontal, rows spread by {}", after_spread);
+    }
+
+    #[tokio::test]
+    async fn test_straighten_by_line_ignores_drawing_direction() {
+        let (image_data, (x1, y1, x2, y2)) = create_tilted_line_image(200, 10.0);
+
+        let forward = crate::straighten_by_line(image_data.clone(), x1, y1, x2, y2, None, limiter()).await.unwrap();
+        let backward = crate::straighten_by_line(image_data, x2, y2, x1, y1, None, limiter()).await.unwrap();
+
+        assert_eq!((forward.width, forward.height), (backward.width, backward.height));
+
+        // Folding the reversed angle can differ by float rounding only
+        let forward = decode_rgba(&forward);
+        let backward = decode_rgba(&backward);
+        let max_difference = forward
+            .as_raw()
+            .iter()
+            .zip(backward.as_raw())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap();
+        assert!(max_difference <= 1);
+    }
+
+    #[tokio::test]
+    async fn test_straighten_by_line_fills_corners() {
+        let (image_data, (x1, y1, x2, y2)) = create_tilted_line_image(200, 10.0);
+
+        let result = crate::straighten_by_line(
+            image_data,
+            x1, y1, x2, y2,
+            Some(RGBColor { r: 255, g: 0, b: 0 }),
+            limiter(),
+        ).await.unwrap();
+
+        let img = decode_rgba(&result);
+        assert_eq!(img.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[tokio::test]
+    async fn test_straighten_by_line_rejects_degenerate_line() {
+        let (image_data, _) = create_tilted_line_image(50, 10.0);
+
+        let result = crate::straighten_by_line(image_data, 10.0, 10.0, 10.0, 10.0, None, limiter()).await;
+
+        assert!(result.is_err());
+    }
+}
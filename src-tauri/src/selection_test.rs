@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::cancel_registry;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba, RgbaImage};
+
+    /// Helper function to wrap an RGBA image as PNG ImageData
+    fn to_image_data(img: &RgbaImage) -> ImageData {
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+
+        ImageData {
+            path: "photo.png".to_string(),
+            width: img.width(),
+            height: img.height(),
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode a mask back to grayscale pixels
+    fn decode_mask(image_data: &ImageData) -> image::GrayImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_luma8()
+    }
+
+    /// A noisy gray background with a solid blue 10x6 rectangle at (5, 4)
+    fn create_image_with_region() -> RgbaImage {
+        ImageBuffer::from_fn(24, 16, |x, y| {
+            if (5..15).contains(&x) && (4..10).contains(&y) {
+                Rgba([20, 40, 200, 255])
+            } else {
+                let v = 100 + ((x * 7 + y * 13) % 40) as u8;
+                Rgba([v, v, v, 255])
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_flood_fill_mask_covers_solid_region() {
+        let image_data = to_image_data(&create_image_with_region());
+
+        let result = crate::flood_fill_mask(image_data, 9, 6, 10, None).await.unwrap();
+
+        assert_eq!(result.path, "photo_mask.png");
+        assert_eq!((result.width, result.height), (24, 16));
+
+        let mask = decode_mask(&result);
+        for (x, y, pixel) in mask.enumerate_pixels() {
+            let inside = (5..15).contains(&x) && (4..10).contains(&y);
+            assert_eq!(pixel.0[0], if inside { 255 } else { 0 }, "pixel at {},{}", x, y);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flood_fill_mask_connectivity() {
+        // Two blocks that only touch at a corner
+        let img: RgbaImage = ImageBuffer::from_fn(8, 8, |x, y| {
+            if (x < 4) == (y < 4) {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        });
+        let image_data = to_image_data(&img);
+
+        let four = decode_mask(&crate::flood_fill_mask(image_data.clone(), 0, 0, 0, None).await.unwrap());
+        assert_eq!(four.pixels().filter(|p| p.0[0] == 255).count(), 16);
+
+        let eight = decode_mask(&crate::flood_fill_mask(image_data, 0, 0, 0, Some(true)).await.unwrap());
+        assert_eq!(eight.pixels().filter(|p| p.0[0] == 255).count(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_flood_fill_mask_rejects_seed_outside_image() {
+        let image_data = to_image_data(&create_image_with_region());
+
+        assert!(crate::flood_fill_mask(image_data, 24, 0, 10, None).await.is_err());
+    }
+
+    /// A green-screen image: pure green with slight noise around a red square
+    fn create_green_screen() -> RgbaImage {
+        ImageBuffer::from_fn(20, 20, |x, y| {
+            if (6..14).contains(&x) && (6..14).contains(&y) {
+                Rgba([220, 30, 30, 255])
+            } else {
+                let noise = ((x * 3 + y * 5) % 8) as u8;
+                Rgba([noise, 255 - noise, noise, 255])
+            }
+        })
+    }
+
+    /// Helper function to decode ImageData back to RGBA pixels
+    fn decode_rgba(image_data: &ImageData) -> RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    #[tokio::test]
+    async fn test_replace_color_keys_out_green() {
+        let green = crate::RGBColor { r: 0, g: 255, b: 0 };
+
+        let keyed = crate::replace_color(to_image_data(&create_green_screen()), green, None, 10, cancel_registry())
+            .await
+            .unwrap();
+
+        assert!(keyed.has_alpha);
+        let pixels = decode_rgba(&keyed);
+        for (x, y, pixel) in pixels.enumerate_pixels() {
+            let inside = (6..14).contains(&x) && (6..14).contains(&y);
+            if inside {
+                assert_eq!(pixel, &Rgba([220, 30, 30, 255]), "({}, {})", x, y);
+            } else {
+                assert_eq!(pixel.0[3], 0, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_color_recolors_and_forces_alpha_format() {
+        let green = crate::RGBColor { r: 0, g: 255, b: 0 };
+        let blue = crate::RGBColor { r: 0, g: 0, b: 255 };
+
+        let recolored = crate::replace_color(
+            to_image_data(&create_green_screen()),
+            green.clone(),
+            Some(blue),
+            10,
+            cancel_registry(),
+        )
+            .await
+            .unwrap();
+        let pixels = decode_rgba(&recolored);
+        assert_eq!(pixels.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(pixels.get_pixel(10, 10), &Rgba([220, 30, 30, 255]));
+
+        // Keying a JPEG has to switch to a format with transparency
+        let mut jpeg = Vec::new();
+        image::DynamicImage::ImageRgba8(create_green_screen()).to_rgb8()
+            .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+            .unwrap();
+        let jpeg_data = ImageData {
+            path: "screen.jpg".to_string(),
+            format: ImageFormat::JPEG,
+            data: general_purpose::STANDARD.encode(&jpeg),
+            ..to_image_data(&create_green_screen())
+        };
+        let keyed = crate::replace_color(jpeg_data, green, None, 40, cancel_registry()).await.unwrap();
+        assert_eq!(keyed.format, ImageFormat::PNG);
+        assert_eq!(decode_rgba(&keyed).get_pixel(0, 0).0[3], 0);
+    }
+}
@@ -0,0 +1,232 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat, StickerData};
+    use crate::test_state::{limiter, cancel_registry};
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba, RgbaImage};
+
+    /// Helper function to encode an RGBA image as PNG bytes
+    fn png_bytes(img: &RgbaImage) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+        buffer
+    }
+
+    /// Helper function to create a base image with a gradient
+    fn create_base(width: u32, height: u32) -> RgbaImage {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            Rgba([(x * 5) as u8, (y * 7) as u8, 120, 255])
+        })
+    }
+
+    /// Helper function to create a sticker with varying color and alpha
+    fn create_sticker(x: u32, y: u32, rotation: f32) -> StickerData {
+        let img: RgbaImage = ImageBuffer::from_fn(12, 8, |sx, sy| {
+            Rgba([255, (sx * 20) as u8, (sy * 30) as u8, 100 + (sx * 10) as u8])
+        });
+
+        StickerData {
+            image_data: general_purpose::STANDARD.encode(png_bytes(&img)),
+            x,
+            y,
+            width: 12,
+            height: 8,
+            rotation,
+            opacity: 1.0,
+        }
+    }
+
+    /// Reference implementation scanning the whole base image for every sticker
+    fn reference_apply(base: &RgbaImage, sticker: &StickerData) -> RgbaImage {
+        let mut base = base.clone();
+        let bytes = general_purpose::STANDARD.decode(&sticker.image_data).unwrap();
+        let sticker_rgba = image::load_from_memory(&bytes)
+            .unwrap()
+            .resize_exact(sticker.width, sticker.height, image::imageops::FilterType::Lanczos3)
+            .to_rgba8();
+
+        let radians = sticker.rotation * std::f32::consts::PI / 180.0;
+        let (cos, sin) = (radians.cos(), radians.sin());
+        let center_x = sticker.x as f32 + sticker.width as f32 / 2.0;
+        let center_y = sticker.y as f32 + sticker.height as f32 / 2.0;
+
+        for base_y in 0..base.height() {
+            for base_x in 0..base.width() {
+                let dx = base_x as f32 - center_x;
+                let dy = base_y as f32 - center_y;
+                let src_x = dx * cos + dy * sin + sticker.width as f32 / 2.0;
+                let src_y = -dx * sin + dy * cos + sticker.height as f32 / 2.0;
+                if src_x < 0.0 || src_x >= sticker.width as f32 || src_y < 0.0 || src_y >= sticker.height as f32 {
+                    continue;
+                }
+
+                let x0 = src_x.floor() as u32;
+                let y0 = src_y.floor() as u32;
+                let x1 = (x0 + 1).min(sticker.width - 1);
+                let y1 = (y0 + 1).min(sticker.height - 1);
+                let fx = src_x - x0 as f32;
+                let fy = src_y - y0 as f32;
+                let p00 = sticker_rgba.get_pixel(x0, y0).0;
+                let p10 = sticker_rgba.get_pixel(x1, y0).0;
+                let p01 = sticker_rgba.get_pixel(x0, y1).0;
+                let p11 = sticker_rgba.get_pixel(x1, y1).0;
+                let value: [u8; 4] = std::array::from_fn(|c| {
+                    ((p00[c] as f32 * (1.0 - fx) + p10[c] as f32 * fx) * (1.0 - fy)
+                        + (p01[c] as f32 * (1.0 - fx) + p11[c] as f32 * fx) * fy) as u8
+                });
+
+                if value[3] == 0 {
+                    continue;
+                }
+
+                let pixel = base.get_pixel_mut(base_x, base_y);
+                let alpha = value[3] as f32 / 255.0;
+                let inv_alpha = 1.0 - alpha;
+                for (channel, layer) in pixel.0.iter_mut().zip(value).take(3) {
+                    *channel = ((*channel as f32 * inv_alpha) + (layer as f32 * alpha)) as u8;
+                }
+                pixel.0[3] = (((pixel.0[3] as f32 / 255.0) * inv_alpha + alpha) * 255.0) as u8;
+            }
+        }
+
+        base
+    }
+
+    #[tokio::test]
+    async fn test_bounded_sticker_loop_matches_full_scan() {
+        let base = create_base(48, 36);
+        let image_data = ImageData {
+            path: "test.png".to_string(),
+            width: 48,
+            height: 36,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(png_bytes(&base)),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        // Unrotated, rotated, and partially off-canvas stickers
+        for sticker in [
+            create_sticker(10, 6, 0.0),
+            create_sticker(10, 6, 33.0),
+            create_sticker(20, 12, -120.0),
+            create_sticker(40, 30, 45.0),
+        ] {
+            let expected = reference_apply(&base, &sticker);
+
+            let result = crate::apply_stickers(
+                image_data.clone(),
+                vec![sticker.clone()],
+                limiter(),
+                cancel_registry(),
+            ).await.unwrap();
+            let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+            let actual = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+            assert_eq!(actual, expected, "mismatch for rotation {}", sticker.rotation);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transparent_sticker_border_leaves_base_unchanged() {
+        // Semi-transparent base so alpha changes are detected too
+        let base: RgbaImage = ImageBuffer::from_fn(40, 40, |x, y| {
+            Rgba([(x * 6) as u8, (y * 6) as u8, 90, 200])
+        });
+        let image_data = ImageData {
+            path: "test.png".to_string(),
+            width: 40,
+            height: 40,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(png_bytes(&base)),
+            has_alpha: true,
+            display_data: None,
+        };
+
+        // 20x20 sticker: opaque 10x10 red square inside a 5px transparent border
+        let sticker_img: RgbaImage = ImageBuffer::from_fn(20, 20, |x, y| {
+            if (5..15).contains(&x) && (5..15).contains(&y) {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let sticker = StickerData {
+            image_data: general_purpose::STANDARD.encode(png_bytes(&sticker_img)),
+            x: 10,
+            y: 10,
+            width: 20,
+            height: 20,
+            rotation: 30.0,
+            opacity: 1.0,
+        };
+
+        let result = crate::apply_stickers(image_data, vec![sticker], limiter(), cancel_registry()).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        let output = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        // The rotated opaque square lies within its circumscribed circle around (20, 20);
+        // allow a pixel of interpolation spread
+        let radius = 5.0 * std::f32::consts::SQRT_2 + 1.5;
+        for (x, y, pixel) in output.enumerate_pixels() {
+            let distance = ((x as f32 - 20.0).powi(2) + (y as f32 - 20.0).powi(2)).sqrt();
+            if distance > radius {
+                assert_eq!(pixel, base.get_pixel(x, y), "base changed at ({}, {})", x, y);
+            }
+        }
+        assert_eq!(output.get_pixel(20, 20).0, [255, 0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_sticker_opacity_scales_alpha() {
+        // Black opaque base with an opaque white sticker
+        let base: RgbaImage = ImageBuffer::from_pixel(20, 20, Rgba([0, 0, 0, 255]));
+        let image_data = ImageData {
+            path: "test.png".to_string(),
+            width: 20,
+            height: 20,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(png_bytes(&base)),
+            has_alpha: false,
+            display_data: None,
+        };
+        let sticker_img: RgbaImage = ImageBuffer::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        let sticker = |opacity: f32| StickerData {
+            image_data: general_purpose::STANDARD.encode(png_bytes(&sticker_img)),
+            x: 5,
+            y: 5,
+            width: 10,
+            height: 10,
+            rotation: 0.0,
+            opacity,
+        };
+        let decode = |result: &ImageData| {
+            let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+            image::load_from_memory(&bytes).unwrap().to_rgba8()
+        };
+
+        let invisible = crate::apply_stickers(
+            image_data.clone(),
+            vec![sticker(0.0)],
+            limiter(),
+            cancel_registry(),
+        ).await.unwrap();
+        assert_eq!(decode(&invisible), base);
+
+        let half = crate::apply_stickers(
+            image_data.clone(),
+            vec![sticker(0.5)],
+            limiter(),
+            cancel_registry(),
+        ).await.unwrap();
+        let center = decode(&half).get_pixel(10, 10).0;
+        assert!((126..=128).contains(&center[0]), "center was {:?}", center);
+        assert_eq!(center[3], 255);
+
+        assert!(crate::apply_stickers(image_data, vec![sticker(-0.1)], limiter(), cancel_registry()).await.is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Manager, State};
+use crate::limiter::{CancelRegistry, OperationLimiter};
+use crate::thumbnail_cache::ThumbnailCache;
+
+thread_local! {
+    /// A mock app managing the same state as the real one, one per test thread
+    static APP: &'static App<MockRuntime> = {
+        let app = mock_app();
+        app.manage(OperationLimiter::default());
+        app.manage(CancelRegistry::new());
+        app.manage(ThumbnailCache::default());
+        Box::leak(Box::new(app))
+    };
+}
+
+fn app() -> &'static App<MockRuntime> {
+    APP.with(|app| *app)
+}
+
+/// The operation limiter, for calling heavy commands directly from tests
+pub fn limiter() -> State<'static, OperationLimiter> {
+    app().state()
+}
+
+/// The cancel registry, for calling cancellable commands directly from tests
+pub fn cancel_registry() -> State<'static, CancelRegistry> {
+    app().state()
+}
+
+/// The thumbnail cache, for calling quick_look directly from tests
+pub fn thumbnails() -> State<'static, ThumbnailCache> {
+    app().state()
+}
@@ -0,0 +1,301 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat, TextData};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba};
+
+    /// Helper function to create a plain white test image
+    fn create_white_image(width: u32, height: u32) -> ImageData {
+        let img = ImageBuffer::from_pixel(width, height, Rgba([255u8, 255, 255, 255]));
+
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width,
+            height,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: true,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to create black text at the given rotation
+    fn create_text(rotation: f32) -> TextData {
+        TextData {
+            text: "WATERMARK".to_string(),
+            x: 60,
+            y: 60,
+            font_size: 32,
+            font_family: "AlimamaShuHeiTi-Bold".to_string(),
+            color: "#000000".to_string(),
+            rotation,
+            max_width: None,
+            font_path: None,
+            opacity: 1.0,
+            background_color: None,
+            padding: 0,
+        }
+    }
+
+    /// Helper function to render texts onto a white canvas
+    async fn render(texts: Vec<TextData>) -> image::RgbaImage {
+        let result = crate::apply_texts(create_white_image(400, 400), texts, limiter()).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    /// Helper function to render texts and return the bounds of the non-white pixels
+    async fn rendered_bounds(texts: Vec<TextData>) -> (u32, u32, u32, u32) {
+        let img = render(texts).await;
+        crate::content_bounds(&img, |p| p.0[0] < 128).expect("text should be drawn")
+    }
+
+    /// First row of each vertical run of rows containing text pixels
+    fn text_line_starts(img: &image::RgbaImage) -> Vec<u32> {
+        let has_text = |y: u32| (0..img.width()).any(|x| img.get_pixel(x, y).0[0] < 128);
+
+        (0..img.height())
+            .filter(|&y| has_text(y) && (y == 0 || !has_text(y - 1)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rotated_text_leaves_unrotated_bounds() {
+        let (x, y, width, height) = rendered_bounds(vec![create_text(0.0)]).await;
+        let (rx, ry, rwidth, rheight) = rendered_bounds(vec![create_text(45.0)]).await;
+
+        // Rotating clockwise around the anchor swings the end of the text downwards
+        assert!(
+            ry + rheight > y + height + 20,
+            "rotated text should extend below the unrotated box ({}, {}, {}, {}) vs ({}, {}, {}, {})",
+            x, y, width, height, rx, ry, rwidth, rheight
+        );
+        assert!(rwidth < width, "rotated text should be narrower than horizontal text");
+    }
+
+    #[tokio::test]
+    async fn test_unrotated_text_starts_at_anchor() {
+        let (x, y, _, _) = rendered_bounds(vec![create_text(0.0)]).await;
+
+        assert!((60..70).contains(&x));
+        assert!((60..80).contains(&y));
+    }
+
+    #[tokio::test]
+    async fn test_newline_renders_second_line_one_line_height_down() {
+        use ab_glyph::{Font, PxScale, ScaleFont};
+
+        let mut text = create_text(0.0);
+        text.text = "HHH\nHHH".to_string();
+
+        let font = crate::load_font_by_name(&text.font_family).unwrap();
+        let scaled = font.as_scaled(PxScale::from(text.font_size as f32));
+        let line_height = (scaled.height() + scaled.line_gap()).ceil() as u32;
+
+        let starts = text_line_starts(&render(vec![text]).await);
+
+        assert_eq!(starts.len(), 2, "expected two separate lines, got {:?}", starts);
+        assert_eq!(starts[1] - starts[0], line_height);
+    }
+
+    #[tokio::test]
+    async fn test_max_width_wraps_words() {
+        let mut text = create_text(0.0);
+        text.text = "one two three four five six".to_string();
+        text.max_width = Some(120);
+
+        let img = render(vec![text]).await;
+        let (x, _, width, _) = crate::content_bounds(&img, |p| p.0[0] < 128).unwrap();
+
+        assert!(text_line_starts(&img).len() >= 3);
+        assert!(x + width <= 60 + 120 + 8, "wrapped text should respect max_width");
+    }
+
+    #[test]
+    fn test_layout_text_lines() {
+        // Every character is 10 pixels wide
+        let measure = |line: &str| line.chars().count() as u32 * 10;
+
+        assert_eq!(
+            crate::layout_text_lines("a\nb", None, measure),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            crate::layout_text_lines("aa bb cc\ndd", Some(50), measure),
+            vec!["aa bb", "cc", "dd"]
+        );
+        // Words longer than the limit stay whole on their own line
+        assert_eq!(
+            crate::layout_text_lines("a verylongword b", Some(50), measure),
+            vec!["a", "verylongword", "b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_font_path_loads_font_file() {
+        let mut text = create_text(0.0);
+        text.font_family = "NoSuchFont".to_string();
+        text.font_path = Some(concat!(env!("CARGO_MANIFEST_DIR"), "/fonts/AlimamaShuHeiTi-Bold.ttf").to_string());
+
+        let result = crate::apply_texts(create_white_image(400, 400), vec![text.clone()], limiter()).await;
+        assert!(result.is_ok());
+
+        // Same glyphs as looking the bundled font up by name
+        let by_path = rendered_bounds(vec![text]).await;
+        let by_name = rendered_bounds(vec![create_text(0.0)]).await;
+        assert_eq!(by_path, by_name);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_font_path_falls_back_to_font_family() {
+        let mut text = create_text(0.0);
+        text.font_path = Some("/nonexistent/brand.ttf".to_string());
+
+        let result = crate::apply_texts(create_white_image(400, 400), vec![text], limiter()).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_half_opacity_text_is_mid_gray_on_black() {
+        let img = ImageBuffer::from_pixel(400, 400, Rgba([0u8, 0, 0, 255]));
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let black = ImageData {
+            data: general_purpose::STANDARD.encode(&buffer),
+            ..create_white_image(400, 400)
+        };
+
+        let mut text = create_text(0.0);
+        text.color = "#FFFFFF".to_string();
+        text.opacity = 0.5;
+
+        let result = crate::apply_texts(black, vec![text], limiter()).await.unwrap();
+        let bytes = general_purpose::STANDARD.decode(&result.data).unwrap();
+        let rendered = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        // Fully covered glyph pixels blend to half white, never brighter
+        let brightest = rendered.pixels().map(|p| p.0[0]).max().unwrap();
+        assert!((126..=129).contains(&brightest), "brightest text pixel was {}", brightest);
+        assert!(rendered.pixels().all(|p| p.0[3] == 255));
+    }
+
+    #[tokio::test]
+    async fn test_opacity_out_of_range_is_rejected() {
+        let mut text = create_text(0.0);
+        text.opacity = 1.5;
+
+        let result = crate::apply_texts(create_white_image(100, 100), vec![text], limiter()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_background_box_fills_padded_text_bounds() {
+        let mut text = create_text(0.0);
+        text.background_color = Some("#FF0000".to_string());
+        text.padding = 10;
+
+        let plain = rendered_bounds(vec![create_text(0.0)]).await;
+        let img = render(vec![text]).await;
+
+        let is_red = |x: u32, y: u32| img.get_pixel(x, y).0 == [255, 0, 0, 255];
+        let is_white = |x: u32, y: u32| img.get_pixel(x, y).0 == [255, 255, 255, 255];
+
+        // The padding band around the anchor is filled outside the glyph strokes
+        assert!(is_red(55, 55));
+        assert!(is_red(60, 52));
+        assert!(is_red(52, 60));
+        // Right of the last glyph, still inside the padded box
+        let (glyph_x, glyph_y, glyph_width, glyph_height) = plain;
+        assert!(is_red(glyph_x + glyph_width + 3, glyph_y + glyph_height / 2));
+        // Beyond the padding the image is untouched
+        assert!(is_white(45, 60));
+        assert!(is_white(60, 45));
+
+        // Glyphs are still drawn on top of the box
+        let dark = img.pixels().filter(|p| p.0[0] < 64).count();
+        assert!(dark > 50);
+    }
+
+    #[tokio::test]
+    async fn test_semi_transparent_background_blends() {
+        let mut text = create_text(0.0);
+        text.background_color = Some("#00000080".to_string());
+        text.padding = 6;
+
+        let img = render(vec![text]).await;
+
+        let pixel = img.get_pixel(56, 56);
+        assert!((126..=128).contains(&pixel.0[0]), "pixel was {:?}", pixel);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_colors_are_invalid_parameters() {
+        let mut text = create_text(0.0);
+        text.color = "#12345".to_string();
+        let result = crate::apply_texts(create_white_image(100, 100), vec![text], limiter()).await;
+        assert_eq!(result.unwrap_err().code, "INVALID_PARAMETERS");
+
+        let mut text = create_text(0.0);
+        text.background_color = Some("#GG000080".to_string());
+        let result = crate::apply_texts(create_white_image(100, 100), vec![text], limiter()).await;
+        assert_eq!(result.unwrap_err().code, "INVALID_PARAMETERS");
+    }
+
+    #[tokio::test]
+    async fn test_cjk_text_without_font_errors_instead_of_drawing() {
+        let mut text = create_text(0.0);
+        text.text = "你好世界".to_string();
+        text.font_family = "NoSuchFont".to_string();
+
+        let result = crate::apply_texts(create_white_image(100, 100), vec![text], limiter()).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("NoSuchFont"));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_text_color_contrasts_with_region() {
+        // Dark navy left half, pale yellow right half
+        let img = ImageBuffer::from_fn(40, 20, |x, _y| {
+            if x < 20 { Rgba([10u8, 20, 60, 255]) } else { Rgba([250u8, 240, 200, 255]) }
+        });
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+        let image_data = ImageData {
+            path: "test.png".to_string(),
+            width: 40,
+            height: 20,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        };
+
+        let dark = crate::suggest_text_color(image_data.clone(), 0, 0, 20, 20).await.unwrap();
+        assert_eq!((dark.color.r, dark.color.g, dark.color.b), (255, 255, 255));
+        assert_eq!((dark.complementary.r, dark.complementary.g, dark.complementary.b), (245, 235, 195));
+        assert!(dark.luminance < 0.05);
+        assert!(dark.contrast_ratio > 10.0);
+
+        // The region is clamped to the image
+        let light = crate::suggest_text_color(image_data, 20, 0, 100, 100).await.unwrap();
+        assert_eq!((light.color.r, light.color.g, light.color.b), (0, 0, 0));
+        assert!(light.luminance > 0.8);
+
+        let white = crate::suggest_text_color(create_white_image(8, 8), 0, 0, 8, 8).await.unwrap();
+        assert_eq!(white.color.r, 0);
+        assert!((white.contrast_ratio - 21.0).abs() < 1e-6);
+    }
+}
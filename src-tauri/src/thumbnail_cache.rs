@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use crate::error::{AppError, AppResult};
+use crate::types::ImageData;
+
+/// Number of thumbnails kept by the default cache
+const DEFAULT_CAPACITY: usize = 512;
+
+struct CacheEntry {
+    /// Modification time of the file when the thumbnail was generated
+    modified: SystemTime,
+    thumbnail: ImageData,
+    /// Value of the use counter when the entry was last read or written
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<(PathBuf, u32), CacheEntry>,
+    use_counter: u64,
+}
+
+/// Least-recently-used cache of generated thumbnails
+///
+/// Entries are keyed by file path and thumbnail size and remember the file's
+/// modification time, so a file that changed on disk is regenerated instead
+/// of served stale. When full, the entry unused for longest is evicted.
+pub struct ThumbnailCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl ThumbnailCache {
+    /// Create a cache holding at most capacity thumbnails
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Get the thumbnail of path at size, generating it on a miss
+    ///
+    /// The lock is not held while generating, so two callers missing the same
+    /// entry at once may both generate it; the later result is kept.
+    pub async fn get_or_insert_with<F, Fut>(&self, path: &Path, size: u32, generate: F) -> AppResult<ImageData>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<ImageData>>,
+    {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(AppError::IoError)?;
+        let key = (path.to_path_buf(), size);
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.use_counter += 1;
+            let now = state.use_counter;
+            match state.entries.get_mut(&key) {
+                Some(entry) if entry.modified == modified => {
+                    entry.last_used = now;
+                    return Ok(entry.thumbnail.clone());
+                }
+                Some(_) => {
+                    state.entries.remove(&key);
+                }
+                None => {}
+            }
+        }
+
+        let thumbnail = generate().await?;
+
+        let mut state = self.state.lock().unwrap();
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.use_counter += 1;
+        let last_used = state.use_counter;
+        state.entries.insert(key, CacheEntry { modified, thumbnail: thumbnail.clone(), last_used });
+
+        Ok(thumbnail)
+    }
+
+    /// Number of thumbnails currently cached
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Whether the cache holds no thumbnails
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached thumbnail
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+}
+
+impl Default for ThumbnailCache {
+    /// The capacity used for the cache in Tauri managed state
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
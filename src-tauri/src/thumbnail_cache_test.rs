@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use crate::thumbnail_cache::ThumbnailCache;
+    use crate::types::{ImageData, ImageFormat};
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    /// Helper function to make a distinguishable fake thumbnail
+    fn thumbnail(data: &str) -> ImageData {
+        ImageData {
+            path: "photo_thumb.jpg".to_string(),
+            width: 16,
+            height: 16,
+            format: ImageFormat::JPEG,
+            data: data.to_string(),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to create a file to cache thumbnails for
+    fn create_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, b"image bytes").unwrap();
+        path
+    }
+
+    /// Look up path in the cache, counting how often the thumbnail is generated
+    async fn lookup(cache: &ThumbnailCache, path: &Path, size: u32, generated: &mut usize) -> ImageData {
+        cache
+            .get_or_insert_with(path, size, || {
+                *generated += 1;
+                let data = format!("thumbnail {}", generated);
+                async move { Ok(thumbnail(&data)) }
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_file_is_served_from_cache() {
+        let path = create_file("test_thumbnail_cache_hit.png");
+        let cache = ThumbnailCache::new(8);
+        let mut generated = 0;
+
+        let first = lookup(&cache, &path, 160, &mut generated).await;
+        let second = lookup(&cache, &path, 160, &mut generated).await;
+        assert_eq!(generated, 1);
+        assert_eq!(second.data, first.data);
+
+        // Another size is a separate entry
+        lookup(&cache, &path, 64, &mut generated).await;
+        assert_eq!(generated, 2);
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        lookup(&cache, &path, 160, &mut generated).await;
+        assert_eq!(generated, 3);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_modified_file_is_regenerated() {
+        let path = create_file("test_thumbnail_cache_modified.png");
+        let cache = ThumbnailCache::new(8);
+        let mut generated = 0;
+
+        let before = lookup(&cache, &path, 160, &mut generated).await;
+
+        let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+
+        let after = lookup(&cache, &path, 160, &mut generated).await;
+        assert_eq!(generated, 2);
+        assert_ne!(after.data, before.data);
+        assert_eq!(cache.len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_entry_is_evicted() {
+        let paths: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|name| create_file(&format!("test_thumbnail_cache_lru_{}.png", name)))
+            .collect();
+        let cache = ThumbnailCache::new(2);
+        let mut generated = 0;
+
+        lookup(&cache, &paths[0], 160, &mut generated).await;
+        lookup(&cache, &paths[1], 160, &mut generated).await;
+        // Reading a keeps it fresher than b, so adding c evicts b
+        lookup(&cache, &paths[0], 160, &mut generated).await;
+        lookup(&cache, &paths[2], 160, &mut generated).await;
+        assert_eq!((generated, cache.len()), (3, 2));
+
+        lookup(&cache, &paths[0], 160, &mut generated).await;
+        assert_eq!(generated, 3);
+        lookup(&cache, &paths[1], 160, &mut generated).await;
+        assert_eq!(generated, 4);
+
+        for path in paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_an_error() {
+        let cache = ThumbnailCache::new(2);
+        let result = cache
+            .get_or_insert_with(Path::new("/nonexistent/photo.png"), 160, || async { Ok(thumbnail("unused")) })
+            .await;
+        assert!(result.is_err());
+    }
+}
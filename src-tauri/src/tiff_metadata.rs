@@ -0,0 +1,249 @@
+use std::borrow::Cow;
+use std::io::{Cursor, Seek, Write};
+use exif::{Context, Field, In, Value};
+use tiff::decoder::{Decoder, Limits};
+use tiff::encoder::{DirectoryEncoder, TiffEncoder, TiffKindStandard, TiffValue};
+use tiff::tags::{Tag, Type};
+use tiff::TiffResult;
+
+/// Tags of the image directory that the TIFF encoder owns
+///
+/// They describe the pixel data written alongside, so copying them from the
+/// source EXIF would corrupt the output.
+const STRUCTURAL_TAGS: [Tag; 21] = [
+    Tag::ImageWidth,
+    Tag::ImageLength,
+    Tag::BitsPerSample,
+    Tag::Compression,
+    Tag::PhotometricInterpretation,
+    Tag::StripOffsets,
+    Tag::SamplesPerPixel,
+    Tag::RowsPerStrip,
+    Tag::StripByteCounts,
+    Tag::PlanarConfiguration,
+    Tag::Predictor,
+    Tag::TileWidth,
+    Tag::TileLength,
+    Tag::TileOffsets,
+    Tag::TileByteCounts,
+    Tag::ExtraSamples,
+    Tag::SampleFormat,
+    Tag::JPEGTables,
+    Tag::ExifDirectory,
+    Tag::GpsDirectory,
+    Tag::IccProfile,
+];
+
+/// Interoperability IFD pointer, which lives in the EXIF directory
+const INTEROP_POINTER: u16 = 0xa005;
+
+/// Opaque bytes written with the UNDEFINED field type
+///
+/// The tiff crate only writes byte slices as BYTE, but the TIFF and EXIF
+/// specifications declare binary blobs such as the ICC profile as UNDEFINED.
+pub struct Undefined<'a>(pub &'a [u8]);
+
+impl TiffValue for Undefined<'_> {
+    const BYTE_LEN: u8 = 1;
+    const FIELD_TYPE: Type = Type::UNDEFINED;
+
+    fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.0)
+    }
+}
+
+/// NUL-terminated ASCII strings, possibly several in one field
+struct Ascii(Vec<u8>);
+
+impl TiffValue for Ascii {
+    const BYTE_LEN: u8 = 1;
+    const FIELD_TYPE: Type = Type::ASCII;
+
+    fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
+
+/// Unsigned rationals stored as numerator, denominator pairs
+///
+/// The tiff crate only writes a single rational per field, while EXIF uses
+/// lists of them (GPS coordinates are three).
+struct Rationals(Vec<u32>);
+
+impl TiffValue for Rationals {
+    const BYTE_LEN: u8 = 8;
+    const FIELD_TYPE: Type = Type::RATIONAL;
+
+    fn count(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.0.iter().flat_map(|v| v.to_ne_bytes()).collect())
+    }
+}
+
+/// Signed rationals stored as numerator, denominator pairs
+struct SRationals(Vec<i32>);
+
+impl TiffValue for SRationals {
+    const BYTE_LEN: u8 = 8;
+    const FIELD_TYPE: Type = Type::SRATIONAL;
+
+    fn count(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    fn data(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.0.iter().flat_map(|v| v.to_ne_bytes()).collect())
+    }
+}
+
+/// Metadata embedded in a TIFF's image directory
+///
+/// TIFF has no EXIF blob: the EXIF fields are split into the image directory
+/// itself plus EXIF and GPS sub-directories that it points to. Those
+/// sub-directories have to be written before the image, so a TIFF writer calls
+/// `write_sub_directories` first and `write_image_tags` once the image
+/// directory is open.
+pub struct TiffMetadata<'a> {
+    icc_profile: Option<&'a [u8]>,
+    primary: Vec<Field>,
+    exif: Vec<Field>,
+    gps: Vec<Field>,
+    exif_offset: Option<u32>,
+    gps_offset: Option<u32>,
+}
+
+impl<'a> TiffMetadata<'a> {
+    /// Collect the metadata to embed
+    ///
+    /// `exif` is a raw EXIF block (the TIFF structure without the JPEG "Exif"
+    /// header). A block that doesn't parse is dropped. Only the primary image's
+    /// fields are kept; thumbnail and interoperability fields are not.
+    pub fn new(icc_profile: Option<&'a [u8]>, exif: Option<&[u8]>) -> Self {
+        let mut metadata = Self {
+            icc_profile,
+            primary: Vec::new(),
+            exif: Vec::new(),
+            gps: Vec::new(),
+            exif_offset: None,
+            gps_offset: None,
+        };
+
+        let fields = exif.and_then(|block| exif::Reader::new().read_raw(block.to_vec()).ok());
+        for field in fields.iter().flat_map(|fields| fields.fields()) {
+            if field.ifd_num != In::PRIMARY {
+                continue;
+            }
+            match field.tag.context() {
+                Context::Tiff if !STRUCTURAL_TAGS.contains(&Tag::from_u16_exhaustive(field.tag.number())) => {
+                    metadata.primary.push(field.clone())
+                }
+                Context::Exif if field.tag.number() != INTEROP_POINTER => metadata.exif.push(field.clone()),
+                Context::Gps => metadata.gps.push(field.clone()),
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    /// Write the EXIF and GPS sub-directories, ahead of the image directory
+    pub fn write_sub_directories<W: Write + Seek>(
+        &mut self,
+        encoder: &mut TiffEncoder<W, TiffKindStandard>,
+    ) -> TiffResult<()> {
+        self.exif_offset = write_sub_directory(encoder, &self.exif)?;
+        self.gps_offset = write_sub_directory(encoder, &self.gps)?;
+        Ok(())
+    }
+
+    /// Write the metadata tags of the image directory
+    ///
+    /// The ICC profile goes in the InterColorProfile tag (34675), next to the
+    /// EXIF fields of the image directory and the sub-directory pointers.
+    pub fn write_image_tags<W: Write + Seek>(
+        &self,
+        directory: &mut DirectoryEncoder<'_, W, TiffKindStandard>,
+    ) -> TiffResult<()> {
+        if let Some(profile) = self.icc_profile {
+            directory.write_tag(Tag::IccProfile, Undefined(profile))?;
+        }
+        for field in &self.primary {
+            write_field(directory, field)?;
+        }
+        if let Some(offset) = self.exif_offset {
+            directory.write_tag(Tag::ExifDirectory, offset)?;
+        }
+        if let Some(offset) = self.gps_offset {
+            directory.write_tag(Tag::GpsDirectory, offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write fields into a directory of their own, returning its offset
+fn write_sub_directory<W: Write + Seek>(
+    encoder: &mut TiffEncoder<W, TiffKindStandard>,
+    fields: &[Field],
+) -> TiffResult<Option<u32>> {
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    let mut directory = encoder.extra_directory()?;
+    for field in fields {
+        write_field(&mut directory, field)?;
+    }
+    Ok(Some(directory.finish_with_offsets()?.offset))
+}
+
+/// Write one EXIF field as a tag with the same number and type
+fn write_field<W: Write + Seek>(
+    directory: &mut DirectoryEncoder<'_, W, TiffKindStandard>,
+    field: &Field,
+) -> TiffResult<()> {
+    let tag = Tag::from_u16_exhaustive(field.tag.number());
+    match &field.value {
+        Value::Byte(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Ascii(strings) => {
+            let mut bytes = Vec::new();
+            for string in strings {
+                bytes.extend_from_slice(string);
+                bytes.push(0);
+            }
+            directory.write_tag(tag, Ascii(bytes))
+        }
+        Value::Short(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Long(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Rational(values) => {
+            directory.write_tag(tag, Rationals(values.iter().flat_map(|r| [r.num, r.denom]).collect()))
+        }
+        Value::SByte(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Undefined(bytes, _) => directory.write_tag(tag, Undefined(bytes)),
+        Value::SShort(values) => directory.write_tag(tag, values.as_slice()),
+        Value::SLong(values) => directory.write_tag(tag, values.as_slice()),
+        Value::SRational(values) => {
+            directory.write_tag(tag, SRationals(values.iter().flat_map(|r| [r.num, r.denom]).collect()))
+        }
+        Value::Float(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Double(values) => directory.write_tag(tag, values.as_slice()),
+        Value::Unknown(..) => Ok(()),
+    }
+}
+
+/// Read the ICC profile of a TIFF's first image, if it has one
+pub fn read_icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    Decoder::new(Cursor::new(bytes))
+        .ok()?
+        .with_limits(Limits::unlimited())
+        .get_tag_u8_vec(Tag::IccProfile)
+        .ok()
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::limiter;
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{ImageBuffer, Rgba};
+
+    /// Helper function to create a solid-color test image
+    fn create_solid_image(width: u32, height: u32, color: [u8; 3]) -> ImageData {
+        let img = ImageBuffer::from_fn(width, height, |_, _| {
+            Rgba([color[0], color[1], color[2], 255])
+        });
+
+        let mut buffer = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width,
+            height,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeline_has_one_thumbnail_per_snapshot() {
+        let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255]];
+        let snapshots: Vec<ImageData> = colors
+            .iter()
+            .map(|c| create_solid_image(200, 100, *c))
+            .collect();
+        let labels = vec!["Open".to_string(), "Crop".to_string(), "Resize".to_string()];
+
+        let result = crate::render_edit_timeline(snapshots, labels, Some(50), limiter()).await;
+
+        assert!(result.is_ok());
+        let strip = result.unwrap();
+        assert_eq!(strip.format, ImageFormat::PNG);
+        // Three 50px cells with 8px gaps around them
+        assert_eq!(strip.width, 3 * 50 + 4 * 8);
+
+        let bytes = general_purpose::STANDARD.decode(&strip.data).unwrap();
+        let pixels = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+        // The center of each cell shows that snapshot's color
+        for (index, color) in colors.iter().enumerate() {
+            let center_x = 8 + index as u32 * (50 + 8) + 25;
+            let pixel = pixels.get_pixel(center_x, 8 + 25);
+            assert_eq!(&pixel.0[..3], &color[..]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeline_rejects_mismatched_labels() {
+        let snapshots = vec![create_solid_image(10, 10, [0, 0, 0])];
+
+        let result = crate::render_edit_timeline(snapshots, vec![], None, limiter()).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -50,6 +50,33 @@ impl ImageFormat {
         }
     }
 
+    /// Look up the format for a lowercase file extension (without the dot)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "png" => Some(ImageFormat::PNG),
+            "jpg" | "jpeg" => Some(ImageFormat::JPEG),
+            "gif" => Some(ImageFormat::GIF),
+            "bmp" => Some(ImageFormat::BMP),
+            "webp" => Some(ImageFormat::WEBP),
+            "svg" => Some(ImageFormat::SVG),
+            "tiff" | "tif" => Some(ImageFormat::TIFF),
+            "ico" => Some(ImageFormat::ICO),
+            "heic" | "heif" => Some(ImageFormat::HEIC),
+            "avif" => Some(ImageFormat::AVIF),
+            _ => None,
+        }
+    }
+
+    /// Maximum width and height the format can store, if it has a practical limit
+    pub fn max_dimension(&self) -> Option<u32> {
+        match self {
+            ImageFormat::ICO => Some(256),
+            ImageFormat::WEBP => Some(16383),
+            ImageFormat::GIF | ImageFormat::JPEG => Some(65535),
+            _ => None,
+        }
+    }
+
     /// Convert to image crate's ImageFormat
     pub fn to_image_format(&self) -> Option<image::ImageFormat> {
         match self {
@@ -67,6 +94,50 @@ impl ImageFormat {
     }
 }
 
+/// Operations the backend can perform on a given image format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FormatCapabilities {
+    /// The image can be resized
+    pub can_resize: bool,
+    /// The image can be cropped
+    pub can_crop: bool,
+    /// The image can be rotated
+    pub can_rotate: bool,
+    /// A background color can be applied to transparent areas
+    pub can_set_background: bool,
+    /// The image can be converted to another format
+    pub can_convert: bool,
+    /// The format can store animations
+    pub can_animate: bool,
+    /// The format can store an alpha (transparency) channel
+    pub supports_alpha: bool,
+}
+
+impl ImageFormat {
+    /// Get the operations supported for this format
+    /// 
+    /// Pixel edits require that the format can be both decoded and re-encoded
+    /// by the image crate, which rules out SVG and HEIC.
+    pub fn capabilities(&self) -> FormatCapabilities {
+        let editable = self.to_image_format().is_some();
+        let supports_alpha = !matches!(self, ImageFormat::JPEG);
+        let can_animate = matches!(
+            self,
+            ImageFormat::PNG | ImageFormat::GIF | ImageFormat::WEBP | ImageFormat::AVIF
+        );
+
+        FormatCapabilities {
+            can_resize: editable,
+            can_crop: editable,
+            can_rotate: editable,
+            can_set_background: editable && supports_alpha,
+            can_convert: editable,
+            can_animate,
+            supports_alpha,
+        }
+    }
+}
+
 /// Core image data structure containing image metadata and encoded data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageData {
@@ -83,14 +154,217 @@ pub struct ImageData {
     /// Whether the image has an alpha (transparency) channel
     #[serde(rename = "hasAlpha")]
     pub has_alpha: bool,
+    /// Base64 encoded PNG of the pixels converted to sRGB, for display
+    ///
+    /// Only set by load_image for files whose color profile isn't sRGB; `data`
+    /// then still holds the file's own bytes.
+    #[serde(rename = "displayData", default, skip_serializing_if = "Option::is_none")]
+    pub display_data: Option<String>,
 }
 
-/// Options for format conversion operations
+/// An image loaded under a size budget, with the dimensions of the file itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CappedImageData {
+    /// The loaded image, downscaled if the file exceeded the budget
+    pub image: ImageData,
+    /// Width of the image stored in the file
+    pub original_width: u32,
+    /// Height of the image stored in the file
+    pub original_height: u32,
+    /// Whether the image was downscaled to fit the budget
+    pub downscaled: bool,
+}
+
+/// Basic facts about an image file, read from its header without decoding pixels
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImageProbe {
+    /// File path of the image
+    pub path: String,
+    /// Image width in pixels (0 for SVG, which has no fixed size)
+    pub width: u32,
+    /// Image height in pixels (0 for SVG, which has no fixed size)
+    pub height: u32,
+    /// Image format
+    pub format: ImageFormat,
+    /// Size of the file in bytes
+    pub file_size: u64,
+}
+
+/// Everything a hover preview needs about an image file, gathered in one call
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickLook {
+    /// Dimensions, format and file size of the full image
+    #[serde(flatten)]
+    pub probe: ImageProbe,
+    /// Small preview image
+    pub thumbnail: ImageData,
+    /// Date the photo was taken as YYYY-MM-DD, from EXIF
+    pub capture_date: Option<String>,
+}
+
+/// Options for format conversion operations
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ConversionOptions {
     /// Quality parameter for lossy formats (JPEG, WEBP, AVIF)
     /// Valid range: 1-100
     pub quality: Option<u8>,
+    /// Downscale images that exceed the target format's maximum dimensions
+    /// instead of failing (default false)
+    pub downscale_to_fit: Option<bool>,
+    /// Drop the embedded ICC color profile instead of carrying it over to PNG,
+    /// JPEG or WEBP output (default false)
+    pub strip_profile: Option<bool>,
+    /// Which EXIF metadata to carry over to PNG, JPEG or WEBP output
+    /// (default preserve)
+    pub metadata_policy: Option<MetadataPolicy>,
+    /// Chroma subsampling of JPEG output (default 4:4:4)
+    ///
+    /// The image crate's JPEG encoder always stores full-resolution chroma, so
+    /// 4:2:2 and 4:2:0 are rejected as invalid.
+    pub jpeg_subsampling: Option<JpegSubsampling>,
+    /// Compression of TIFF output (default none)
+    pub tiff_compression: Option<TiffCompression>,
+    /// Encode WEBP output losslessly, ignoring quality (default true)
+    ///
+    /// The image crate can only write lossless WebP, so WEBP output is lossless
+    /// either way and false is rejected as invalid.
+    pub webp_lossless: Option<bool>,
+}
+
+/// What happens to a source image's EXIF metadata during conversion
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataPolicy {
+    /// Copy the EXIF block to the output unchanged
+    #[default]
+    Preserve,
+    /// Write the output without any EXIF metadata
+    Strip,
+    /// Keep camera and exposure data but drop the GPS location
+    StripGpsOnly,
+}
+
+/// Resolution of the color (chroma) channels relative to brightness in JPEG output
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JpegSubsampling {
+    /// Full color resolution; keeps sharp colored edges and fine colored text
+    #[serde(rename = "4:4:4")]
+    S444,
+    /// Color averaged over pairs of horizontally adjacent pixels
+    #[serde(rename = "4:2:2")]
+    S422,
+    /// Color averaged over 2x2 pixel blocks, the usual choice for photos
+    #[serde(rename = "4:2:0")]
+    S420,
+}
+
+/// Lossless compression applied to TIFF output
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TiffCompression {
+    /// Store the pixels as they are
+    None,
+    /// LZW, the most widely supported TIFF compression
+    Lzw,
+    /// Deflate (zlib), usually smaller than LZW
+    Deflate,
+}
+
+/// How the color channels of an image relate to its alpha channel
+///
+/// PNG and most formats store straight alpha, but some exporters write
+/// premultiplied values anyway and nothing in the file records it. Such images
+/// show dark fringes around semi-transparent edges, and no color channel is ever
+/// larger than the pixel's alpha. Callers must say explicitly when a file is
+/// premultiplied.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AlphaMode {
+    /// Color channels are independent of alpha (the normal case)
+    #[default]
+    Straight,
+    /// Color channels have already been multiplied by alpha
+    Premultiplied,
+}
+
+/// How two images are arranged in a before/after comparison
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ComparisonOrientation {
+    /// Side by side, before on the left
+    Horizontal,
+    /// Stacked, before on top
+    Vertical,
+}
+
+/// Resampling filter used when resizing
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResampleFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+impl ResampleFilter {
+    /// Convert to image crate's FilterType
+    pub fn to_filter_type(&self) -> image::imageops::FilterType {
+        use image::imageops::FilterType;
+
+        match self {
+            ResampleFilter::Nearest => FilterType::Nearest,
+            ResampleFilter::Triangle => FilterType::Triangle,
+            ResampleFilter::CatmullRom => FilterType::CatmullRom,
+            ResampleFilter::Gaussian => FilterType::Gaussian,
+            ResampleFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// Options controlling resize quality versus speed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResampleOptions {
+    /// Resampling filter
+    pub filter: ResampleFilter,
+    /// Resample in linear light instead of sRGB, avoiding darkened edges
+    #[serde(default)]
+    pub gamma_correct: bool,
+}
+
+impl ResampleOptions {
+    /// Nearest neighbour: fastest, blocky results
+    pub fn fast() -> Self {
+        Self { filter: ResampleFilter::Nearest, gamma_correct: false }
+    }
+
+    /// Catmull-Rom in sRGB: good quality at moderate cost, suited to thumbnails
+    pub fn balanced() -> Self {
+        Self { filter: ResampleFilter::CatmullRom, gamma_correct: false }
+    }
+
+    /// Lanczos3 in linear light: highest quality, slowest
+    pub fn best() -> Self {
+        Self { filter: ResampleFilter::Lanczos3, gamma_correct: true }
+    }
+
+    /// Look up a preset by name ("fast", "balanced" or "best")
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "fast" => Some(Self::fast()),
+            "balanced" => Some(Self::balanced()),
+            "best" => Some(Self::best()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ResampleOptions {
+    /// Lanczos3 in sRGB, matching the original resize behavior
+    fn default() -> Self {
+        Self { filter: ResampleFilter::Lanczos3, gamma_correct: false }
+    }
 }
 
 /// RGB color representation
@@ -104,6 +378,39 @@ pub struct RGBColor {
     pub b: u8,
 }
 
+/// Readable text colors for a region of an image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextColorSuggestion {
+    /// Black or white, whichever contrasts more with the region
+    pub color: RGBColor,
+    /// Inverse of the region's average color
+    pub complementary: RGBColor,
+    /// Relative luminance of the region's average color (0.0-1.0)
+    pub luminance: f64,
+    /// WCAG contrast ratio between `color` and the region (1.0-21.0)
+    pub contrast_ratio: f64,
+}
+
+/// One file of a responsive image set, as listed in an `<img srcset>`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SrcsetEntry {
+    /// Path of the written file
+    pub path: String,
+    /// Width of the file in pixels (the "w" descriptor)
+    pub width: u32,
+    /// Height of the file in pixels
+    pub height: u32,
+}
+
+/// One frame of an animation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GifFrame {
+    /// Base64 encoded image data of the frame
+    pub data: String,
+    /// How long the frame is shown, in milliseconds
+    pub delay_ms: u32,
+}
+
 /// Represents a sticker to be applied to an image
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StickerData {
@@ -119,6 +426,9 @@ pub struct StickerData {
     pub height: u32,
     /// Rotation angle in degrees
     pub rotation: f32,
+    /// Multiplier for the sticker's own alpha from 0.0 (invisible) to 1.0 (unchanged)
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
 }
 
 /// Represents text to be rendered on an image
@@ -138,4 +448,24 @@ pub struct TextData {
     pub color: String,
     /// Rotation angle in degrees
     pub rotation: f32,
+    /// Wrap lines wider than this many pixels at word boundaries
+    #[serde(default)]
+    pub max_width: Option<u32>,
+    /// Font file to use instead of looking up font_family in the fonts directory
+    #[serde(default)]
+    pub font_path: Option<String>,
+    /// Text opacity from 0.0 (invisible) to 1.0 (opaque)
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Color of a box drawn behind the text (#RRGGBB or #RRGGBBAA)
+    #[serde(default)]
+    pub background_color: Option<String>,
+    /// Space in pixels between the text and the edge of its background box
+    #[serde(default)]
+    pub padding: u32,
+}
+
+/// Overlays are fully opaque unless an opacity is given
+fn default_opacity() -> f32 {
+    1.0
 }
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{ImageData, ImageFormat};
+    use crate::test_state::{limiter, cancel_registry};
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{Rgba, RgbaImage};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+    /// Helper function to wrap a solid-color image as PNG ImageData
+    fn solid_image(width: u32, height: u32, color: Rgba<u8>) -> ImageData {
+        let img = RgbaImage::from_pixel(width, height, color);
+        let mut buffer = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut buffer), image::ImageFormat::Png)
+            .unwrap();
+
+        ImageData {
+            path: "test.png".to_string(),
+            width,
+            height,
+            format: ImageFormat::PNG,
+            data: general_purpose::STANDARD.encode(&buffer),
+            has_alpha: false,
+            display_data: None,
+        }
+    }
+
+    /// Helper function to decode the result back to RGBA pixels
+    fn decode(image_data: &ImageData) -> RgbaImage {
+        let bytes = general_purpose::STANDARD.decode(&image_data.data).unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    #[tokio::test]
+    async fn test_watermark_bottom_right() {
+        let result = crate::apply_watermark(
+            solid_image(100, 80, WHITE),
+            solid_image(20, 10, RED),
+            1.0,
+            false,
+            5,
+            "bottom-right".to_string(),
+            limiter(),
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!((result.width, result.height), (100, 80));
+
+        // The watermark covers x 75..95 and y 65..75, 5 pixels from the edges
+        let pixels = decode(&result);
+        for (x, y, pixel) in pixels.enumerate_pixels() {
+            let inside = (75..95).contains(&x) && (65..75).contains(&y);
+            assert_eq!(*pixel, if inside { RED } else { WHITE }, "pixel at {},{}", x, y);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watermark_tiled_count() {
+        let result = crate::apply_watermark(
+            solid_image(100, 100, WHITE),
+            solid_image(10, 10, RED),
+            1.0,
+            true,
+            20,
+            "center".to_string(),
+            limiter(),
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+
+        // Tiles start every 30 pixels at 0, 30, 60 and 90 on both axes
+        let pixels = decode(&result);
+        let tile_corners = pixels
+            .enumerate_pixels()
+            .filter(|(x, y, pixel)| {
+                **pixel == RED
+                    && (*x == 0 || *pixels.get_pixel(x - 1, *y) == WHITE)
+                    && (*y == 0 || *pixels.get_pixel(*x, y - 1) == WHITE)
+            })
+            .count();
+        assert_eq!(tile_corners, 16);
+
+        let red_pixels = pixels.pixels().filter(|p| **p == RED).count();
+        assert_eq!(red_pixels, 16 * 100);
+    }
+
+    #[tokio::test]
+    async fn test_watermark_opacity_blends() {
+        let result = crate::apply_watermark(
+            solid_image(40, 40, WHITE),
+            solid_image(10, 10, RED),
+            0.5,
+            false,
+            0,
+            "center".to_string(),
+            limiter(),
+            cancel_registry(),
+        )
+        .await
+        .unwrap();
+
+        let pixel = decode(&result).get_pixel(20, 20).0;
+        assert_eq!(pixel[0], 255);
+        assert!((pixel[1] as i32 - 127).abs() <= 1, "green was {}", pixel[1]);
+    }
+
+    #[tokio::test]
+    async fn test_watermark_rejects_unknown_position_and_bad_opacity() {
+        let unknown = crate::apply_watermark(
+            solid_image(40, 40, WHITE),
+            solid_image(10, 10, RED),
+            1.0,
+            false,
+            0,
+            "middle-ish".to_string(),
+            limiter(),
+            cancel_registry(),
+        )
+        .await;
+        assert!(unknown.unwrap_err().message.contains("Unknown watermark position"));
+
+        let opacity = crate::apply_watermark(
+            solid_image(40, 40, WHITE),
+            solid_image(10, 10, RED),
+            1.5,
+            true,
+            0,
+            "center".to_string(),
+            limiter(),
+            cancel_registry(),
+        )
+        .await;
+        assert!(opacity.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reaches_watermark_waiting_for_a_slot() {
+        limiter().set_max_concurrent(1).unwrap();
+        let busy = limiter().acquire().await;
+
+        let watermark = crate::apply_watermark(
+            solid_image(40, 40, WHITE),
+            solid_image(10, 10, RED),
+            1.0,
+            true,
+            0,
+            "center".to_string(),
+            limiter(),
+            cancel_registry(),
+        );
+        let cancel_then_free_slot = async {
+            // Let the watermark register and queue behind the busy slot first
+            tokio::task::yield_now().await;
+            assert_eq!(cancel_registry().cancel_all(), 1);
+            drop(busy);
+        };
+        let (result, _) = tokio::join!(watermark, cancel_then_free_slot);
+        assert!(result.unwrap_err().message.contains("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_watermark_rejects_spacing_larger_than_image() {
+        for tile in [true, false] {
+            let result = crate::apply_watermark(
+                solid_image(40, 40, WHITE),
+                solid_image(10, 10, RED),
+                1.0,
+                tile,
+                u32::MAX,
+                "top-left".to_string(),
+                limiter(),
+                cancel_registry(),
+            )
+            .await;
+            assert_eq!(result.unwrap_err().code, "INVALID_PARAMETERS");
+        }
+    }
+}
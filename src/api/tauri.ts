@@ -8,7 +8,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { ImageData } from '../types/tauri';
+import type { ImageData, ImageProbe, QuickLook, ResampleOptions } from '../types/tauri';
 import { deepCopyImageData, areImageDataEqual } from '../utils/imageData';
 
 /**
@@ -16,12 +16,74 @@ import { deepCopyImageData, areImageDataEqual } from '../utils/imageData';
  * 
  * Supports: PNG, JPEG, GIF, BMP, WEBP, SVG, TIFF, ICO, HEIC, AVIF
  * 
+ * Images with an embedded color profile (e.g. Display-P3) get displayData, a
+ * lossless sRGB copy to show, unless convertToSrgb is false. data always holds
+ * the file's own bytes.
+ * 
  * @param path - File path to the image
+ * @param convertToSrgb - Convert embedded color profiles to sRGB (default: true)
  * @returns Promise resolving to ImageData containing metadata and Base64 encoded data
  * @throws Error if file not found, unsupported format, or invalid image data
  */
-export async function loadImage(path: string): Promise<ImageData> {
-  return await invoke<ImageData>('load_image', { path });
+export async function loadImage(path: string, convertToSrgb?: boolean): Promise<ImageData> {
+  return await invoke<ImageData>('load_image', { path, convertToSrgb });
+}
+
+/**
+ * Read an image's dimensions, format and file size without decoding its pixels
+ * 
+ * Much cheaper than loadImage, for file-list previews.
+ * 
+ * @param path - File path to the image
+ * @returns Promise resolving to the probed facts, without the Base64 payload
+ * @throws Error if file not found or unsupported format
+ */
+export async function probeImage(path: string): Promise<ImageProbe> {
+  return await invoke<ImageProbe>('probe_image', { path });
+}
+
+/**
+ * Get a thumbnail and key metadata for a hover preview in one call
+ * 
+ * Uses the thumbnail embedded in the EXIF data when it is large enough.
+ * Thumbnails are cached until the file changes on disk.
+ * 
+ * @param path - File path to the image
+ * @param size - Longest edge of the thumbnail in pixels (default 160)
+ * @returns Promise resolving to dimensions, format, file size, capture date and thumbnail
+ * @throws Error if file not found or the image can't be decoded
+ */
+export async function quickLook(path: string, size?: number): Promise<QuickLook> {
+  return await invoke<QuickLook>('quick_look', { path, size });
+}
+
+/**
+ * Drop every thumbnail cached by quickLook
+ * 
+ * @returns Promise that resolves once the cache is empty
+ */
+export async function clearThumbnailCache(): Promise<void> {
+  return await invoke<void>('clear_thumbnail_cache');
+}
+
+/**
+ * Load the image currently on the system clipboard (e.g. a screenshot)
+ * 
+ * @returns Promise resolving to PNG ImageData with the path "clipboard.png"
+ * @throws Error if the clipboard does not contain an image
+ */
+export async function loadImageFromClipboard(): Promise<ImageData> {
+  return await invoke<ImageData>('load_image_from_clipboard');
+}
+
+/**
+ * Copy an image to the system clipboard so it can be pasted into other apps
+ * 
+ * @param imageData - The image to copy
+ * @throws Error if the image cannot be decoded or the clipboard is unavailable
+ */
+export async function copyImageToClipboard(imageData: ImageData): Promise<void> {
+  await invoke<void>('copy_image_to_clipboard', { imageData });
 }
 
 /**
@@ -59,10 +121,15 @@ export async function saveFileDialog(defaultName: string): Promise<string | null
  * 
  * @param imageData - ImageData object containing the image to save
  * @param path - File path where the image should be saved
- * @throws Error if save fails (permission denied, disk full, etc.)
+ * @param overwrite - Replace an existing file at the path (default true)
+ * @throws Error if save fails (file exists, permission denied, disk full, etc.)
  */
-export async function saveImage(imageData: ImageData, path: string): Promise<void> {
-  return await invoke<void>('save_image', { imageData, path });
+export async function saveImage(
+  imageData: ImageData,
+  path: string,
+  overwrite?: boolean
+): Promise<void> {
+  return await invoke<void>('save_image', { imageData, path, overwrite });
 }
 
 /**
@@ -75,6 +142,7 @@ export async function saveImage(imageData: ImageData, path: string): Promise<voi
  * @param width - Target width in pixels (must be positive integer)
  * @param height - Target height in pixels (must be positive integer)
  * @param keepAspectRatio - If true, maintains aspect ratio (may result in smaller dimensions)
+ * @param resample - Resampling filter and gamma handling (default Lanczos3 in sRGB)
  * @returns Promise resolving to new ImageData with resized image
  * @throws Error if parameters are invalid, resize operation fails, or immutability is violated
  */
@@ -82,7 +150,8 @@ export async function resizeImage(
   imageData: ImageData,
   width: number,
   height: number,
-  keepAspectRatio: boolean
+  keepAspectRatio: boolean,
+  resample?: ResampleOptions
 ): Promise<ImageData> {
   // Create a snapshot of the original for immutability verification
   const originalSnapshot = deepCopyImageData(imageData);
@@ -93,6 +162,7 @@ export async function resizeImage(
     width,
     height,
     keepAspectRatio,
+    resample,
   });
   
   // Verify that the original was not mutated
@@ -115,13 +185,15 @@ export async function resizeImage(
  * @param imageData - ImageData object containing the image to convert
  * @param targetFormat - Target format (e.g., 'PNG', 'JPEG', 'WEBP')
  * @param options - Optional conversion options (quality for JPEG/WEBP/AVIF: 1-100)
+ * @param outputPath - Path stored in the result (default: source path with the new extension)
  * @returns Promise resolving to new ImageData with converted image
  * @throws Error if format is unsupported, conversion fails, or immutability is violated
  */
 export async function convertFormat(
   imageData: ImageData,
   targetFormat: string,
-  options?: { quality?: number }
+  options?: { quality?: number },
+  outputPath?: string
 ): Promise<ImageData> {
   // Create a snapshot of the original for immutability verification
   const originalSnapshot = deepCopyImageData(imageData);
@@ -131,6 +203,7 @@ export async function convertFormat(
     imageData,
     targetFormat,
     options: options || null,
+    outputPath: outputPath ?? null,
   });
   
   // Verify that the original was not mutated
@@ -198,6 +271,7 @@ export async function cropImage(
  * @param r - Red component (0-255)
  * @param g - Green component (0-255)
  * @param b - Blue component (0-255)
+ * @param linearBlend - Blend in linear light instead of gamma-encoded sRGB (default false)
  * @returns Promise resolving to new ImageData with background applied
  * @throws Error if image doesn't have transparency, operation fails, or immutability is violated
  */
@@ -205,7 +279,8 @@ export async function setBackground(
   imageData: ImageData,
   r: number,
   g: number,
-  b: number
+  b: number,
+  linearBlend?: boolean
 ): Promise<ImageData> {
   // Create a snapshot of the original for immutability verification
   const originalSnapshot = deepCopyImageData(imageData);
@@ -216,6 +291,7 @@ export async function setBackground(
     r,
     g,
     b,
+    linearBlend,
   });
   
   // Verify that the original was not mutated
@@ -266,6 +342,19 @@ export interface FavoriteImage {
   path: string;
   tags: string[];
   added_at: number;
+  note: string | null;
+  last_viewed: number | null;
+  camera: CameraInfo | null;
+}
+
+export interface CameraInfo {
+  camera_make: string | null;
+  camera_model: string | null;
+  lens_model: string | null;
+  focal_length: number | null;
+  aperture: number | null;
+  exposure_time: number | null;
+  iso: number | null;
 }
 
 /**
@@ -317,6 +406,16 @@ export async function searchFavoritesByTags(tags: string[]): Promise<FavoriteIma
   return await invoke<FavoriteImage[]>('search_favorites_by_tags', { tags });
 }
 
+/**
+ * Search favorites by camera make, camera model or lens model
+ * 
+ * @param query - Case-insensitive text to look for
+ * @returns Promise resolving to array of matching favorite images
+ */
+export async function searchFavoritesByCamera(query: string): Promise<FavoriteImage[]> {
+  return await invoke<FavoriteImage[]>('search_favorites_by_camera', { query });
+}
+
 /**
  * Get all unique tags from favorites
  * 
@@ -394,6 +493,19 @@ export async function applyStickers(
   return result;
 }
 
+/**
+ * Cancel the heavy operations in progress (stickers, watermarks, batch macros, srcset
+ * generation, pixelation, color replacement)
+ * 
+ * Running operations reject with an OPERATION_FAILED "cancelled" error at their next
+ * checkpoint. Calling it while nothing runs has no effect on later operations.
+ * 
+ * @returns Promise that resolves once the cancellation has been requested
+ */
+export async function cancelOperation(): Promise<void> {
+  return await invoke<void>('cancel_operation');
+}
+
 /**
  * Get available font files from the fonts directory
  * 
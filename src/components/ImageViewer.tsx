@@ -1413,7 +1413,11 @@ export const ImageViewer: React.FC = () => {
           >
             <img
               ref={imageRef}
-              src={`data:image/${state.currentImage.format.toLowerCase()};base64,${state.currentImage.data}`}
+              src={
+                state.currentImage.displayData
+                  ? `data:image/png;base64,${state.currentImage.displayData}`
+                  : `data:image/${state.currentImage.format.toLowerCase()};base64,${state.currentImage.data}`
+              }
               alt={state.currentImage.path}
               className="main-image"
               style={{
@@ -21,12 +21,66 @@ export interface ImageData {
   format: ImageFormat;
   data: string; // Base64 encoded image data
   hasAlpha: boolean;
+  displayData?: string; // Base64 PNG converted to sRGB, set by loadImage for wide-gamut files
+}
+
+export interface ImageProbe {
+  path: string;
+  width: number; // 0 for SVG
+  height: number; // 0 for SVG
+  format: ImageFormat;
+  file_size: number; // Bytes
+}
+
+export interface QuickLook extends ImageProbe {
+  thumbnail: ImageData; // JPEG, or PNG if the image has transparency
+  capture_date: string | null; // YYYY-MM-DD from EXIF
 }
 
 export interface ConversionOptions {
   quality?: number; // For JPEG, WEBP, AVIF (1-100)
+  downscale_to_fit?: boolean; // Shrink images larger than the format allows
+  strip_profile?: boolean; // Drop the embedded ICC color profile
+  metadata_policy?: MetadataPolicy; // EXIF handling (default "preserve")
+  jpeg_subsampling?: JpegSubsampling; // JPEG chroma resolution (only "4:4:4"; others are rejected)
+  tiff_compression?: TiffCompression; // Lossless TIFF compression (default "none")
+  webp_lossless?: boolean; // Pixel-exact WEBP output (default true; false is rejected)
 }
 
+export type JpegSubsampling = '4:4:4' | '4:2:2' | '4:2:0';
+
+export type TiffCompression = 'none' | 'lzw' | 'deflate';
+
+export type MetadataPolicy = 'preserve' | 'strip' | 'strip_gps_only';
+
+export type ResampleFilter = 'nearest' | 'triangle' | 'catmullrom' | 'gaussian' | 'lanczos3';
+
+export interface ResampleOptions {
+  filter: ResampleFilter; // Resampling filter
+  gamma_correct?: boolean; // Resample in linear light (default false)
+}
+
+// Error rejected by every backend command
+export interface CommandError {
+  code: ErrorCode; // Stable identifier to match on
+  message: string; // Human-readable description
+}
+
+export type ErrorCode =
+  | 'FILE_NOT_FOUND'
+  | 'UNSUPPORTED_FORMAT'
+  | 'INVALID_IMAGE_DATA'
+  | 'INVALID_PARAMETERS'
+  | 'SAVE_FAILED'
+  | 'OPERATION_FAILED'
+  | 'PERMISSION_DENIED'
+  | 'FILE_EXISTS'
+  | 'NO_ALPHA'
+  | 'CORRUPT_IMAGE'
+  | 'IO_ERROR'
+  | 'IMAGE_ERROR'
+  | 'BASE64_ERROR';
+
 export interface RGBColor {
   r: number; // 0-255
   g: number; // 0-255
@@ -53,6 +107,7 @@ export interface StickerApiData {
   width: number; // Width in image coordinates
   height: number; // Height in image coordinates
   rotation: number; // Rotation in degrees
+  opacity?: number; // Alpha multiplier from 0.0 to 1.0 (default 1.0)
 }
 
 export interface TextData {
@@ -76,4 +131,9 @@ export interface TextApiData {
   font_family: string; // Font family name
   color: string; // Text color (hex format: #RRGGBB)
   rotation: number; // Rotation in degrees
+  max_width?: number | null; // Wrap lines wider than this many pixels
+  font_path?: string | null; // Font file to use instead of font_family
+  opacity?: number; // Text opacity from 0.0 to 1.0 (default 1.0)
+  background_color?: string | null; // Box behind the text (#RRGGBB or #RRGGBBAA)
+  padding?: number; // Space between the text and its background box edge
 }
@@ -24,6 +24,7 @@ export function deepCopyImageData(imageData: ImageData): ImageData {
     format: imageData.format,
     data: imageData.data,
     hasAlpha: imageData.hasAlpha,
+    displayData: imageData.displayData,
   };
 }
 
@@ -44,7 +45,8 @@ export function areImageDataEqual(original: ImageData, current: ImageData): bool
     original.height === current.height &&
     original.format === current.format &&
     original.data === current.data &&
-    original.hasAlpha === current.hasAlpha
+    original.hasAlpha === current.hasAlpha &&
+    original.displayData === current.displayData
   );
 }
 